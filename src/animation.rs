@@ -0,0 +1,664 @@
+//! # Animation
+//! An [`AnimationGraph`] describes the animation states of a character (idle, run, jump), the
+//! [clip](AnimationClip) each state plays and the transitions between the states. The transitions
+//! are driven by parameters that gameplay code sets on the [`Animator`] component of an entity:
+//!
+//! ```
+//! use game_engine::animation::{
+//!     AnimationClip, AnimationGraph, Animator, AnimatorSystem, Condition, Transition,
+//! };
+//! use game_engine::ecs::{System, World};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let mut graph = AnimationGraph::new();
+//! let idle = graph.add_state("idle", AnimationClip::new(Duration::from_secs(1)).looping());
+//! let run = graph.add_state("run", AnimationClip::new(Duration::from_millis(600)).looping());
+//! let jump = graph.add_state("jump", AnimationClip::new(Duration::from_millis(400)));
+//!
+//! graph.add_transition(
+//!     Transition::new(idle, run)
+//!         .when(Condition::Greater("speed".into(), 0.1))
+//!         .with_blend(Duration::from_millis(150)),
+//! );
+//! graph.add_transition(Transition::new(run, idle).when(Condition::Less("speed".into(), 0.1)));
+//! graph.add_transition(Transition::from_any(jump).when(Condition::Trigger("jump".into())));
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.add_system(AnimatorSystem::new());
+//!
+//! let mut animator = Animator::new(Arc::new(graph));
+//! animator.set_float("speed", 3.0);
+//! let _ = world.build_entity().with_component(animator).build();
+//!
+//! world.tick(Duration::from_millis(16));
+//! ```
+//!
+//! The animator does not change any components by itself. Rendering or sprite systems read the
+//! [poses](Animator::poses) of the animator, i.e. the playing states with their blend weights.
+//! Gameplay code can sync to the animation with [events](AnimationClip::with_event) on the clips,
+//! which are sent as [`AnimationEvent`]s when the playback crosses them.
+use crate::ecs::{EntityId, Storage, System};
+use crate::time::Time;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The index of a state in its [`AnimationGraph`].
+pub type StateId = usize;
+
+/// The timing of an animation, e.g. of a sprite sheet or a skeletal animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationClip {
+    pub duration: Duration,
+    pub looping: bool,
+    /// Named events and the time in the clip they occur at, sorted by time.
+    events: Vec<(Duration, String)>,
+}
+
+impl AnimationClip {
+    #[must_use]
+    pub const fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            looping: false,
+            events: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    /// Add a named event at a time in the clip, e.g. a footstep or the frame in which a hitbox
+    /// becomes active. The [`AnimatorSystem`] sends an [`AnimationEvent`] whenever the playback
+    /// crosses it.
+    #[must_use]
+    pub fn with_event(mut self, time: Duration, name: impl Into<String>) -> Self {
+        let index = self.events.partition_point(|(other, _)| *other <= time);
+        self.events.insert(index, (time, name.into()));
+        self
+    }
+
+    /// The names of the events in the playback time range `start..end`, in the order they occur.
+    /// Looping clips fire their events once per loop.
+    pub fn events_between(&self, start: Duration, end: Duration) -> impl Iterator<Item = &str> {
+        let cycles = if self.looping && !self.duration.is_zero() {
+            start.as_nanos() / self.duration.as_nanos()..=end.as_nanos() / self.duration.as_nanos()
+        } else {
+            0..=0
+        };
+
+        cycles.flat_map(move |cycle| {
+            self.events.iter().filter_map(move |(time, name)| {
+                let time = cycle * self.duration.as_nanos() + time.as_nanos();
+                (start.as_nanos()..end.as_nanos())
+                    .contains(&time)
+                    .then_some(name.as_str())
+            })
+        })
+    }
+
+    /// The position in the clip between 0.0 and 1.0 after playing it for `time`.
+    #[must_use]
+    pub fn normalized_time(&self, time: Duration) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+
+        let time = time.as_secs_f32() / self.duration.as_secs_f32();
+        if self.looping {
+            time.fract()
+        } else {
+            time.min(1.0)
+        }
+    }
+
+    /// Returns true if the clip does not loop and was played to its end.
+    #[must_use]
+    pub fn is_finished(&self, time: Duration) -> bool {
+        !self.looping && time >= self.duration
+    }
+}
+
+/// A condition on the parameters of an [`Animator`]. Parameters that were never set are false
+/// and 0.0.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// The bool parameter has the given value.
+    Bool(String, bool),
+    /// The float parameter is greater than the value.
+    Greater(String, f32),
+    /// The float parameter is less than the value.
+    Less(String, f32),
+    /// The trigger was set. It is reset when the transition is taken.
+    Trigger(String),
+    /// The clip of the current state was played to its end.
+    Finished,
+}
+
+/// A transition between two states of an [`AnimationGraph`]. It is taken as soon as all of its
+/// conditions are met.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// The state the transition starts in, or None for any state.
+    pub from: Option<StateId>,
+    pub to: StateId,
+    pub conditions: Vec<Condition>,
+    /// The time the previous state keeps playing while its weight fades out.
+    pub blend: Duration,
+}
+
+impl Transition {
+    #[must_use]
+    pub const fn new(from: StateId, to: StateId) -> Self {
+        Self {
+            from: Some(from),
+            to,
+            conditions: Vec::new(),
+            blend: Duration::ZERO,
+        }
+    }
+
+    /// A transition that can be taken from every other state, e.g. to a hit or death state.
+    #[must_use]
+    pub const fn from_any(to: StateId) -> Self {
+        Self {
+            from: None,
+            to,
+            conditions: Vec::new(),
+            blend: Duration::ZERO,
+        }
+    }
+
+    #[must_use]
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_blend(mut self, blend: Duration) -> Self {
+        self.blend = blend;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AnimationState {
+    name: String,
+    clip: AnimationClip,
+}
+
+/// The states and transitions of an animated entity, see the [module documentation](self). The
+/// first added state is the initial state. A graph is usually shared by many [`Animator`]s.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnimationGraph {
+    states: Vec<AnimationState>,
+    transitions: Vec<Transition>,
+}
+
+impl AnimationGraph {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_state(&mut self, name: impl Into<String>, clip: AnimationClip) -> StateId {
+        self.states.push(AnimationState {
+            name: name.into(),
+            clip,
+        });
+        self.states.len() - 1
+    }
+
+    /// Add a transition. Transitions are checked in the order they were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transition refers to a state that was not added.
+    pub fn add_transition(&mut self, transition: Transition) {
+        assert!(
+            transition.to < self.states.len()
+                && transition.from.is_none_or(|from| from < self.states.len()),
+            "Transition refers to an unknown animation state"
+        );
+
+        self.transitions.push(transition);
+    }
+
+    /// The state with the given name.
+    #[must_use]
+    pub fn state(&self, name: &str) -> Option<StateId> {
+        self.states.iter().position(|state| state.name == name)
+    }
+
+    /// The name of a state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state does not exist.
+    #[must_use]
+    pub fn state_name(&self, state: StateId) -> &str {
+        &self.states[state].name
+    }
+
+    /// The clip of a state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the state does not exist.
+    #[must_use]
+    pub fn clip(&self, state: StateId) -> &AnimationClip {
+        &self.states[state].clip
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Parameter {
+    Bool(bool),
+    Float(f32),
+    Trigger,
+}
+
+/// A state that is currently played by an [`Animator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    pub state: StateId,
+    /// The position in the clip of the state between 0.0 and 1.0.
+    pub normalized_time: f32,
+    /// The blend weight. The weights of all poses of an animator add up to 1.0.
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Blend {
+    from: StateId,
+    from_time: Duration,
+    elapsed: Duration,
+    duration: Duration,
+}
+
+/// Plays an [`AnimationGraph`] for an entity. The animator is advanced by the
+/// [`AnimatorSystem`].
+#[derive(Debug, Clone)]
+pub struct Animator {
+    graph: Arc<AnimationGraph>,
+    parameters: HashMap<String, Parameter>,
+    state: StateId,
+    time: Duration,
+    blend: Option<Blend>,
+    events: Vec<(StateId, String)>,
+    /// Playback speed multiplier. Negative speeds and NaN stop the playback, speeds above
+    /// [`Animator::MAX_SPEED`] play at the maximum.
+    pub speed: f32,
+}
+
+impl Animator {
+    /// The largest playback [speed](Animator::speed). Larger speeds would let a single update
+    /// overflow the state time.
+    pub const MAX_SPEED: f32 = 100.0;
+
+    /// Start playing the first state of the graph.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has no states.
+    #[must_use]
+    pub fn new(graph: Arc<AnimationGraph>) -> Self {
+        assert!(
+            !graph.states.is_empty(),
+            "Animation graph must have at least one state"
+        );
+
+        Self {
+            graph,
+            parameters: HashMap::new(),
+            state: 0,
+            time: Duration::ZERO,
+            blend: None,
+            events: Vec::new(),
+            speed: 1.0,
+        }
+    }
+
+    #[must_use]
+    pub fn graph(&self) -> &AnimationGraph {
+        &self.graph
+    }
+
+    pub fn set_bool(&mut self, name: &str, value: bool) {
+        self.parameters
+            .insert(name.to_owned(), Parameter::Bool(value));
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.parameters
+            .insert(name.to_owned(), Parameter::Float(value));
+    }
+
+    /// Set a trigger, which stays set until a transition that checks it is taken.
+    pub fn set_trigger(&mut self, name: &str) {
+        self.parameters.insert(name.to_owned(), Parameter::Trigger);
+    }
+
+    #[must_use]
+    pub fn get_bool(&self, name: &str) -> bool {
+        matches!(self.parameters.get(name), Some(Parameter::Bool(true)))
+    }
+
+    #[must_use]
+    pub fn get_float(&self, name: &str) -> f32 {
+        match self.parameters.get(name) {
+            Some(Parameter::Float(value)) => *value,
+            _ => 0.0,
+        }
+    }
+
+    #[must_use]
+    pub fn is_triggered(&self, name: &str) -> bool {
+        matches!(self.parameters.get(name), Some(Parameter::Trigger))
+    }
+
+    /// The state the animator is in or blends into.
+    #[must_use]
+    pub const fn state(&self) -> StateId {
+        self.state
+    }
+
+    #[must_use]
+    pub fn state_name(&self) -> &str {
+        self.graph.state_name(self.state)
+    }
+
+    /// How long the current state has been playing, scaled by the speed.
+    #[must_use]
+    pub const fn state_time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns true while the animator blends between two states.
+    #[must_use]
+    pub const fn is_blending(&self) -> bool {
+        self.blend.is_some()
+    }
+
+    /// Remove the events that the playback crossed since the last call, together with the state
+    /// whose clip contains them. The [`AnimatorSystem`] drains them and sends them as
+    /// [`AnimationEvent`]s.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = (StateId, String)> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Jump to a state without a blend.
+    pub fn play(&mut self, state: StateId) {
+        self.state = state;
+        self.time = Duration::ZERO;
+        self.blend = None;
+    }
+
+    /// The playing states with their weights: the current state and, while blending, the
+    /// previous state.
+    pub fn poses(&self) -> impl Iterator<Item = Pose> + '_ {
+        let weight = self.blend.as_ref().map_or(1.0, |blend| {
+            (blend.elapsed.as_secs_f32() / blend.duration.as_secs_f32()).min(1.0)
+        });
+
+        let previous = self.blend.as_ref().map(|blend| Pose {
+            state: blend.from,
+            normalized_time: self.graph.clip(blend.from).normalized_time(blend.from_time),
+            weight: 1.0 - weight,
+        });
+        let current = Pose {
+            state: self.state,
+            normalized_time: self.graph.clip(self.state).normalized_time(self.time),
+            weight,
+        };
+
+        previous.into_iter().chain(std::iter::once(current))
+    }
+
+    /// Advance the playback and take the first transition whose conditions are met. Returns the
+    /// new state if a transition was taken.
+    pub fn update(&mut self, delta: Duration) -> Option<StateId> {
+        let speed = if self.speed.is_nan() {
+            0.0
+        } else {
+            self.speed.clamp(0.0, Self::MAX_SPEED)
+        };
+        let delta = delta.mul_f32(speed);
+        let clip = self.graph.clip(self.state);
+        let events = clip.events_between(self.time, self.time + delta);
+        self.events
+            .extend(events.map(|name| (self.state, name.to_owned())));
+        self.time += delta;
+
+        if let Some(blend) = &mut self.blend {
+            blend.from_time += delta;
+            blend.elapsed += delta;
+
+            if blend.elapsed >= blend.duration {
+                self.blend = None;
+            }
+        }
+
+        let graph = Arc::clone(&self.graph);
+        let transition = graph.transitions.iter().find(|transition| {
+            transition
+                .from
+                .map_or(transition.to != self.state, |from| from == self.state)
+                && transition
+                    .conditions
+                    .iter()
+                    .all(|condition| self.is_met(condition))
+        })?;
+
+        for condition in &transition.conditions {
+            if let Condition::Trigger(name) = condition {
+                self.parameters.remove(name);
+            }
+        }
+
+        self.blend = (!transition.blend.is_zero()).then_some(Blend {
+            from: self.state,
+            from_time: self.time,
+            elapsed: Duration::ZERO,
+            duration: transition.blend,
+        });
+        self.state = transition.to;
+        self.time = Duration::ZERO;
+
+        Some(self.state)
+    }
+
+    fn is_met(&self, condition: &Condition) -> bool {
+        match condition {
+            Condition::Bool(name, value) => self.get_bool(name) == *value,
+            Condition::Greater(name, value) => self.get_float(name) > *value,
+            Condition::Less(name, value) => self.get_float(name) < *value,
+            Condition::Trigger(name) => self.is_triggered(name),
+            Condition::Finished => self.graph.clip(self.state).is_finished(self.time),
+        }
+    }
+}
+
+/// Sent as ECS event when the playback of an [`Animator`] crosses an event of the clip of its
+/// current state, see [`AnimationClip::with_event`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationEvent {
+    /// The entity of the animator.
+    pub entity: EntityId,
+    pub state: StateId,
+    pub name: String,
+}
+
+/// Advances all [`Animator`] components by the frame time of the [`Time`] resource and sends their
+/// [`AnimationEvent`]s. Does nothing if there is no time resource.
+pub struct AnimatorSystem;
+
+impl System for AnimatorSystem {
+    fn new() -> Self {
+        Self
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let Some(delta) = storage.get_resource::<Time>().map(Time::delta) else {
+            return;
+        };
+
+        let mut events = Vec::new();
+
+        for entity in storage.entities_with_component::<Animator>() {
+            let Some(animator) = storage.get_entity_component_mut::<Animator>(entity) else {
+                continue;
+            };
+
+            animator.update(delta);
+            events.extend(animator.drain_events().map(|(state, name)| AnimationEvent {
+                entity,
+                state,
+                name,
+            }));
+        }
+
+        for event in events {
+            storage.send_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::World;
+
+    fn graph() -> (AnimationGraph, [StateId; 3]) {
+        let mut graph = AnimationGraph::new();
+        let idle = graph.add_state("idle", AnimationClip::new(Duration::from_secs(1)).looping());
+        let run = graph.add_state("run", AnimationClip::new(Duration::from_secs(1)).looping());
+        let jump = graph.add_state("jump", AnimationClip::new(Duration::from_millis(500)));
+
+        graph.add_transition(
+            Transition::new(idle, run)
+                .when(Condition::Greater("speed".into(), 0.5))
+                .with_blend(Duration::from_millis(200)),
+        );
+        graph.add_transition(Transition::new(run, idle).when(Condition::Less("speed".into(), 0.5)));
+        graph.add_transition(Transition::from_any(jump).when(Condition::Trigger("jump".into())));
+        graph.add_transition(Transition::new(jump, idle).when(Condition::Finished));
+
+        (graph, [idle, run, jump])
+    }
+
+    #[test]
+    fn transitions_follow_parameters_and_blend() {
+        let (graph, [idle, run, _]) = graph();
+        let mut animator = Animator::new(Arc::new(graph));
+
+        assert_eq!(animator.update(Duration::from_millis(100)), None);
+        animator.set_float("speed", 2.0);
+        assert_eq!(animator.update(Duration::from_millis(100)), Some(run));
+        assert_eq!(animator.state_name(), "run");
+
+        animator.update(Duration::from_millis(100));
+        let poses: Vec<_> = animator.poses().collect();
+        assert_eq!(poses.len(), 2);
+        assert_eq!(poses[0].state, idle);
+        assert!((poses[0].weight - 0.5).abs() < 1e-6);
+        assert!((poses[0].normalized_time - 0.3).abs() < 1e-6);
+        assert!((poses[1].normalized_time - 0.1).abs() < 1e-6);
+
+        animator.update(Duration::from_millis(100));
+        assert!(!animator.is_blending());
+        assert_eq!(animator.poses().count(), 1);
+    }
+
+    #[test]
+    fn triggers_are_consumed_and_finished_clips_leave() {
+        let (graph, [idle, _, jump]) = graph();
+        let mut animator = Animator::new(Arc::new(graph));
+
+        animator.set_trigger("jump");
+        assert_eq!(animator.update(Duration::from_millis(10)), Some(jump));
+        assert!(!animator.is_triggered("jump"));
+
+        assert_eq!(animator.update(Duration::from_millis(400)), None);
+        assert_eq!(animator.update(Duration::from_millis(100)), Some(idle));
+    }
+
+    #[test]
+    fn speed_is_kept_finite() {
+        let (graph, _) = graph();
+        let mut animator = Animator::new(Arc::new(graph));
+
+        animator.speed = f32::INFINITY;
+        animator.update(Duration::from_millis(10));
+        assert_eq!(animator.state_time(), Duration::from_secs(1));
+
+        animator.speed = f32::NAN;
+        animator.update(Duration::from_millis(10));
+        assert_eq!(animator.state_time(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn clip_events_fire_when_crossed() {
+        let clip = AnimationClip::new(Duration::from_millis(100))
+            .looping()
+            .with_event(Duration::from_millis(50), "step_right")
+            .with_event(Duration::ZERO, "step_left");
+        let between = |start, end| {
+            clip.events_between(Duration::from_millis(start), Duration::from_millis(end))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(between(0, 50), ["step_left"]);
+        assert_eq!(between(50, 60), ["step_right"]);
+        assert_eq!(
+            between(60, 260),
+            ["step_left", "step_right", "step_left", "step_right"]
+        );
+        assert!(between(60, 100).is_empty());
+    }
+
+    #[test]
+    fn system_sends_animation_events() {
+        let mut graph = AnimationGraph::new();
+        let attack = graph.add_state(
+            "attack",
+            AnimationClip::new(Duration::from_millis(300))
+                .with_event(Duration::from_millis(100), "hitbox_on"),
+        );
+        let mut world = World::init().unwrap();
+        world.add_system(AnimatorSystem::new());
+        let entity = world
+            .build_entity()
+            .with_component(Animator::new(Arc::new(graph)))
+            .build();
+
+        world.tick(Duration::from_millis(60));
+        assert_eq!(world.storage.read_events::<AnimationEvent>().count(), 0);
+        world.tick(Duration::from_millis(60));
+        world.tick(Duration::from_millis(60));
+
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<AnimationEvent>()
+                .collect::<Vec<_>>(),
+            [AnimationEvent {
+                entity,
+                state: attack,
+                name: "hitbox_on".to_owned()
+            }]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown animation state")]
+    fn transitions_to_unknown_states_are_rejected() {
+        let mut graph = AnimationGraph::new();
+        graph.add_transition(Transition::from_any(3));
+    }
+}
@@ -0,0 +1,292 @@
+//! # Camera
+//! Helpers for 2D cameras. A camera is an entity with a [`Transform2D`], whose translation is the
+//! center of the visible area, and a [`Camera2D`] that describes where and how large the world is
+//! shown in the window.
+//!
+//! The [`CameraFollowSystem`] moves every camera with a [`CameraFollow`] component towards its
+//! target entity:
+//!
+//! ```
+//! use game_engine::camera::{CameraFollow, CameraFollowSystem};
+//! use game_engine::ecs::{System, World};
+//! use game_engine::math::{Rect, Transform2D, Vec2};
+//! use game_engine::time::Time;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.storage.insert_resource(Time::new());
+//! world.add_system(CameraFollowSystem::new());
+//!
+//! let player = world
+//!     .build_entity()
+//!     .with_component(Transform2D::from_xy(10.0, 0.0))
+//!     .build();
+//!
+//! let follow = CameraFollow::new(player)
+//!     .with_smoothing(8.0)
+//!     .with_dead_zone(Vec2::new(64.0, 32.0))
+//!     .with_bounds(Rect::from_corners(Vec2::ZERO, Vec2::new(2000.0, 1000.0)));
+//! let _ = world
+//!     .build_entity()
+//!     .with_component(Transform2D::IDENTITY)
+//!     .with_component(follow)
+//!     .build();
+//! ```
+use crate::ecs::{EntityId, Storage, System};
+use crate::math::{Rect, Transform2D, Vec2};
+use crate::time::Time;
+
+/// The projection of a 2D camera. Positions in the viewport are given in physical pixels relative
+/// to the top left corner of the window with y pointing down, like cursor positions. The world
+/// has y pointing up.
+///
+/// ```
+/// use game_engine::camera::Camera2D;
+/// use game_engine::math::{Rect, Transform2D, Vec2};
+///
+/// // a 1280x720 window on a display with a scale factor of 2
+/// let viewport = Rect::from_corners(Vec2::ZERO, Vec2::new(2560.0, 1440.0));
+/// let camera = Camera2D::new(viewport).with_scale_factor(2.0).with_zoom(2.0);
+/// let transform = Transform2D::from_xy(100.0, 50.0);
+///
+/// let clicked = camera.viewport_to_world(&transform, Vec2::new(2560.0, 1440.0));
+/// assert_eq!(clicked, Vec2::new(100.0 + 320.0, 50.0 - 180.0));
+/// assert_eq!(camera.world_to_viewport(&transform, clicked), Vec2::new(2560.0, 1440.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    /// The area of the window the camera renders to, in physical pixels.
+    pub viewport: Rect,
+    /// The number of physical pixels per logical pixel, see
+    /// [`Window::scale_factor`](winit::window::Window::scale_factor).
+    pub scale_factor: f32,
+    /// The number of logical pixels per world unit at the center of the viewport. Larger values
+    /// zoom in.
+    pub zoom: f32,
+}
+
+impl Camera2D {
+    #[must_use]
+    pub const fn new(viewport: Rect) -> Self {
+        Self {
+            viewport,
+            scale_factor: 1.0,
+            zoom: 1.0,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_zoom(mut self, zoom: f32) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// The world position shown at a position in the viewport, e.g. the position under the
+    /// cursor. `transform` is the transform of the camera entity.
+    #[must_use]
+    pub fn viewport_to_world(&self, transform: &Transform2D, position: Vec2) -> Vec2 {
+        let offset = (position - self.viewport.center()) / (self.scale_factor * self.zoom);
+
+        transform.transform_point(Vec2::new(offset.x, -offset.y))
+    }
+
+    /// The viewport position a world position is shown at. This is the inverse of
+    /// [`Camera2D::viewport_to_world`].
+    #[must_use]
+    pub fn world_to_viewport(&self, transform: &Transform2D, point: Vec2) -> Vec2 {
+        let offset = transform.compute_affine().inverse().transform_point2(point);
+
+        self.viewport.center() + Vec2::new(offset.x, -offset.y) * (self.scale_factor * self.zoom)
+    }
+
+    /// The area of the world that is visible, i.e. the bounds of the viewport in world space.
+    #[must_use]
+    pub fn visible_area(&self, transform: &Transform2D) -> Rect {
+        Rect::from_points(
+            self.viewport
+                .corners()
+                .map(|corner| self.viewport_to_world(transform, corner)),
+        )
+    }
+}
+
+/// Lets a camera follow the [`Transform2D`] of a target entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CameraFollow {
+    pub target: EntityId,
+    /// How fast the camera catches up with the target, as the rate per second of an exponential
+    /// approach. Higher values follow more tightly, [`f32::INFINITY`] snaps to the target.
+    pub smoothing: f32,
+    /// The size of the area around the center of the camera in which the target can move without
+    /// moving the camera.
+    pub dead_zone: Vec2,
+    /// The area the center of the camera stays inside. To keep the whole visible area inside the
+    /// level, shrink the level bounds by half the visible size.
+    pub bounds: Option<Rect>,
+}
+
+impl CameraFollow {
+    /// Follow the target with a smoothing of 5.0, without a dead zone and without bounds.
+    #[must_use]
+    pub const fn new(target: EntityId) -> Self {
+        Self {
+            target,
+            smoothing: 5.0,
+            dead_zone: Vec2::ZERO,
+            bounds: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_dead_zone(mut self, dead_zone: Vec2) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// The new center of the camera, given its current center, the position of the target and
+    /// the frame time in seconds.
+    #[must_use]
+    pub fn follow(&self, camera: Vec2, target: Vec2, delta_seconds: f32) -> Vec2 {
+        // move only as far as needed to bring the target back into the dead zone
+        let offset = target - camera;
+        let half_dead_zone = self.dead_zone.abs() * 0.5;
+        let goal = camera + offset - offset.clamp(-half_dead_zone, half_dead_zone);
+
+        let t = if self.smoothing == f32::INFINITY {
+            1.0
+        } else {
+            1.0 - (-self.smoothing.max(0.0) * delta_seconds).exp()
+        };
+        let position = camera.lerp(goal, t);
+
+        self.bounds
+            .map_or(position, |bounds| bounds.closest_point(position))
+    }
+}
+
+/// Moves all cameras with a [`CameraFollow`] component, using the [`Time`] resource for
+/// frame-rate independent smoothing. Cameras whose target does not exist anymore stay in place.
+pub struct CameraFollowSystem;
+
+impl System for CameraFollowSystem {
+    fn new() -> Self {
+        Self
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let delta_seconds = storage
+            .get_resource::<Time>()
+            .map_or(0.0, Time::delta_seconds);
+
+        for camera in storage.entities_with_component::<CameraFollow>() {
+            let Some(&follow) = storage.get_entity_component::<CameraFollow>(camera) else {
+                continue;
+            };
+            let Some(target) = storage
+                .get_entity_component::<Transform2D>(follow.target)
+                .map(|transform| transform.translation)
+            else {
+                continue;
+            };
+            let Some(transform) = storage.get_entity_component_mut::<Transform2D>(camera) else {
+                continue;
+            };
+
+            transform.translation = follow.follow(transform.translation, target, delta_seconds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::World;
+    use std::f32::consts::FRAC_PI_2;
+    use std::time::Duration;
+
+    #[test]
+    fn follow_respects_dead_zone_and_bounds() {
+        let follow = CameraFollow::new(0)
+            .with_smoothing(f32::INFINITY)
+            .with_dead_zone(Vec2::new(4.0, 2.0));
+        assert_eq!(
+            follow.follow(Vec2::ZERO, Vec2::new(5.0, 0.0), 0.0),
+            Vec2::new(3.0, 0.0)
+        );
+
+        assert_eq!(
+            follow.follow(Vec2::ZERO, Vec2::new(1.0, 1.0), 0.1),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            follow.follow(Vec2::ZERO, Vec2::new(5.0, -3.0), 0.1),
+            Vec2::new(3.0, -2.0)
+        );
+
+        let bounded = follow.with_bounds(Rect::from_corners(Vec2::ZERO, Vec2::splat(2.0)));
+        assert_eq!(
+            bounded.follow(Vec2::ZERO, Vec2::new(5.0, -3.0), 0.1),
+            Vec2::new(2.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn viewport_conversion_accounts_for_viewport_and_rotation() {
+        let viewport = Rect::from_corners(Vec2::new(100.0, 0.0), Vec2::new(300.0, 100.0));
+        let camera = Camera2D::new(viewport).with_zoom(0.5);
+        let transform = Transform2D::from_xy(10.0, 10.0).with_rotation(FRAC_PI_2);
+
+        // 50 pixels right of the center are 100 units along the rotated x axis
+        let world = camera.viewport_to_world(&transform, Vec2::new(250.0, 50.0));
+        assert!(world.abs_diff_eq(Vec2::new(10.0, 110.0), 1e-4), "{world}");
+
+        let back = camera.world_to_viewport(&transform, world);
+        assert!(back.abs_diff_eq(Vec2::new(250.0, 50.0), 1e-4), "{back}");
+
+        let visible = camera.visible_area(&Transform2D::IDENTITY);
+        assert_eq!(visible.size(), Vec2::new(400.0, 200.0));
+    }
+
+    #[test]
+    fn system_moves_cameras_towards_their_target() {
+        let mut world = World::init().unwrap();
+        world.add_system(CameraFollowSystem::new());
+        let player = world
+            .build_entity()
+            .with_component(Transform2D::from_xy(100.0, 0.0))
+            .build();
+        let camera = world
+            .build_entity()
+            .with_component(Transform2D::IDENTITY)
+            .with_component(CameraFollow::new(player))
+            .build();
+
+        world.tick(Duration::from_millis(100));
+        let x = world
+            .storage
+            .get_entity_component::<Transform2D>(camera)
+            .unwrap()
+            .translation
+            .x;
+
+        // 1 - e^(-5 * 0.1) of the distance
+        assert!((x - 39.346_93).abs() < 1e-3, "{x}");
+    }
+}
@@ -0,0 +1,122 @@
+//! # Clipboard
+//! Cross-platform access to the system clipboard. The [`Clipboard`] is meant to be inserted as a
+//! resource, so that systems (text fields, the debug console, ...) can copy and paste text:
+//!
+//! ```
+//! use game_engine::clipboard::Clipboard;
+//! use game_engine::ecs::World;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.storage.insert_resource(Clipboard::new());
+//!
+//! if let Some(clipboard) = world.storage.get_resource_mut::<Clipboard>() {
+//!     clipboard.set_text("Hello");
+//! }
+//! ```
+
+/// Text clipboard backed by the system clipboard. If the system clipboard is not available (e.g.
+/// on headless platforms) or an operation on it fails, text is kept in an in-process buffer
+/// instead, so copy and paste still works within the game.
+pub struct Clipboard {
+    system: Option<arboard::Clipboard>,
+    local: Option<String>,
+}
+
+impl Clipboard {
+    /// Create a clipboard connected to the system clipboard, falling back to an in-process buffer
+    /// if the connection fails.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            system: arboard::Clipboard::new().ok(),
+            local: None,
+        }
+    }
+
+    /// Create a clipboard that never touches the system clipboard.
+    #[must_use]
+    pub const fn local() -> Self {
+        Self {
+            system: None,
+            local: None,
+        }
+    }
+
+    /// Returns true if the clipboard is connected to the system clipboard.
+    #[must_use]
+    pub const fn is_system(&self) -> bool {
+        self.system.is_some()
+    }
+
+    /// Get the current text of the clipboard. Returns None if the clipboard is empty or does not
+    /// contain text.
+    pub fn get_text(&mut self) -> Option<String> {
+        if let Some(system) = &mut self.system {
+            if let Ok(text) = system.get_text() {
+                return Some(text);
+            }
+        }
+
+        self.local.clone()
+    }
+
+    /// Replace the text of the clipboard.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        let text = text.into();
+
+        if let Some(system) = &mut self.system {
+            if system.set_text(text.as_str()).is_ok() {
+                self.local = None;
+                return;
+            }
+        }
+
+        self.local = Some(text);
+    }
+
+    /// Remove all content from the clipboard.
+    pub fn clear(&mut self) {
+        if let Some(system) = &mut self.system {
+            // clearing can fail if another application owns the clipboard, in which case there
+            // is nothing left that we could do about it
+            let _ = system.clear();
+        }
+
+        self.local = None;
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_clipboard_stores_text() {
+        let mut clipboard = Clipboard::local();
+
+        assert!(!clipboard.is_system());
+        assert!(clipboard.get_text().is_none());
+
+        clipboard.set_text("foo");
+        assert_eq!(clipboard.get_text().as_deref(), Some("foo"));
+
+        clipboard.set_text(String::from("bar"));
+        assert_eq!(clipboard.get_text().as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn local_clipboard_clear_removes_text() {
+        let mut clipboard = Clipboard::local();
+        clipboard.set_text("foo");
+
+        clipboard.clear();
+
+        assert!(clipboard.get_text().is_none());
+    }
+}
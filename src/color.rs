@@ -0,0 +1,450 @@
+//! # Color
+//! The [`Color`] type is used everywhere the engine needs a color, so that there is a single place
+//! that knows about color spaces:
+//!
+//! ```
+//! use game_engine::color::{Color, Gradient};
+//!
+//! let orange: Color = "#ff8800".parse().expect("valid hex color");
+//! let faded = orange.with_alpha(0.5);
+//!
+//! let health_bar = Gradient::new(Color::RED)
+//!     .with_stop(0.5, Color::YELLOW)
+//!     .with_stop(1.0, Color::GREEN);
+//! let color = health_bar.sample(0.8);
+//! ```
+use crate::tween::Tweenable;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An RGBA color. The color channels are stored in the sRGB color space, which is how colors are
+/// usually picked and written down, and range from 0.0 to 1.0. Renderers and color math that has
+/// to be physically correct (like blending or [interpolation](Color::lerp)) work on
+/// [linear](Color::to_linear) colors instead. Alpha is linear in both spaces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Color {
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Self = Self::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Self = Self::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Self = Self::rgb(1.0, 0.0, 1.0);
+    pub const TRANSPARENT: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+
+    /// An opaque color from sRGB channels.
+    #[must_use]
+    pub const fn rgb(red: f32, green: f32, blue: f32) -> Self {
+        Self::rgba(red, green, blue, 1.0)
+    }
+
+    /// A color from sRGB channels and alpha.
+    #[must_use]
+    pub const fn rgba(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// A color from 8-bit sRGB channels and alpha, as used by most image formats and color
+    /// pickers.
+    #[must_use]
+    pub fn rgba_u8(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        let channel = |value: u8| f32::from(value) / 255.0;
+
+        Self::rgba(channel(red), channel(green), channel(blue), channel(alpha))
+    }
+
+    /// A color from channels in linear color space.
+    #[must_use]
+    pub fn linear_rgba(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Self::rgba(
+            linear_to_srgb(red),
+            linear_to_srgb(green),
+            linear_to_srgb(blue),
+            alpha,
+        )
+    }
+
+    /// An opaque color from hue (in degrees), saturation and lightness. Saturation and lightness
+    /// range from 0.0 to 1.0.
+    #[must_use]
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let chroma = (1.0 - 2.0f32.mul_add(lightness, -1.0).abs()) * saturation;
+
+        from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// An opaque color from hue (in degrees), saturation and value. Saturation and value range
+    /// from 0.0 to 1.0.
+    #[must_use]
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+
+        from_hue_chroma(hue, chroma, value - chroma)
+    }
+
+    #[must_use]
+    pub const fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// The channels of the color in linear color space, e.g. for passing them to a shader.
+    #[must_use]
+    pub fn to_linear(self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.red),
+            srgb_to_linear(self.green),
+            srgb_to_linear(self.blue),
+            self.alpha,
+        ]
+    }
+
+    /// The sRGB channels and alpha of the color.
+    #[must_use]
+    pub const fn to_array(self) -> [f32; 4] {
+        [self.red, self.green, self.blue, self.alpha]
+    }
+
+    /// The 8-bit sRGB channels and alpha of the color. Channels outside of the valid range are
+    /// clamped.
+    #[must_use]
+    pub fn to_u8_array(self) -> [u8; 4] {
+        // the value is clamped and rounded, so the truncation is intended
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        self.to_array()
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// Hue (in degrees), saturation and lightness of the color.
+    #[must_use]
+    pub fn to_hsl(self) -> [f32; 3] {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - 2.0f32.mul_add(lightness, -1.0).abs())
+        };
+
+        [hue, saturation, lightness]
+    }
+
+    /// Hue (in degrees), saturation and value of the color.
+    #[must_use]
+    pub fn to_hsv(self) -> [f32; 3] {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        [hue, saturation, max]
+    }
+
+    /// Interpolate between two colors in linear color space, which avoids the dark and muddy
+    /// transitions of interpolating sRGB channels directly.
+    #[must_use]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let [red, green, blue, alpha] = self.to_linear();
+        let [other_red, other_green, other_blue, other_alpha] = other.to_linear();
+        let lerp = |from: f32, to: f32| (to - from).mul_add(t, from);
+
+        Self::linear_rgba(
+            lerp(red, other_red),
+            lerp(green, other_green),
+            lerp(blue, other_blue),
+            lerp(alpha, other_alpha),
+        )
+    }
+
+    fn hue_max_min(self) -> (f32, f32, f32) {
+        let Self {
+            red, green, blue, ..
+        } = self;
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let chroma = max - min;
+
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == red {
+            60.0 * ((green - blue) / chroma).rem_euclid(6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / chroma + 2.0)
+        } else {
+            60.0 * ((red - green) / chroma + 4.0)
+        };
+
+        (hue, max, min)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([red, green, blue, alpha]: [f32; 4]) -> Self {
+        Self::rgba(red, green, blue, alpha)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.to_array()
+    }
+}
+
+impl Tweenable for Color {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        self.lerp(*end, t)
+    }
+}
+
+/// The error returned when parsing a [`Color`] from a hex string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(String);
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid hex color {:?}, expected #rgb, #rgba, #rrggbb or #rrggbbaa",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parse a hex color in one of the forms `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`. The leading
+    /// `#` is optional.
+    fn from_str(hex: &str) -> Result<Self, Self::Err> {
+        let error = || ParseColorError(hex.to_owned());
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+
+        if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+
+        let channel = |index: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[index * width..(index + 1) * width], 16)
+                .expect("Hex digits were checked before.");
+
+            // a single digit is repeated, e.g. #f80 is the same as #ff8800
+            if width == 1 {
+                value * 17
+            } else {
+                value
+            }
+        };
+
+        let (width, has_alpha) = match digits.len() {
+            3 => (1, false),
+            4 => (1, true),
+            6 => (2, false),
+            8 => (2, true),
+            _ => return Err(error()),
+        };
+
+        let alpha = if has_alpha {
+            channel(3, width)
+        } else {
+            u8::MAX
+        };
+
+        Ok(Self::rgba_u8(
+            channel(0, width),
+            channel(1, width),
+            channel(2, width),
+            alpha,
+        ))
+    }
+}
+
+/// A smooth transition between multiple colors. The gradient is sampled at a position, usually
+/// between 0.0 and 1.0, and interpolates between the two color stops around that position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    /// Color stops sorted by their position.
+    stops: Vec<(f32, Color)>,
+}
+
+impl Gradient {
+    /// A gradient that starts with the given color at position 0.0.
+    #[must_use]
+    pub fn new(start: Color) -> Self {
+        Self {
+            stops: vec![(0.0, start)],
+        }
+    }
+
+    /// Add a color stop at the given position. Stops can be added in any order.
+    #[must_use]
+    pub fn with_stop(mut self, position: f32, color: Color) -> Self {
+        let index = self
+            .stops
+            .partition_point(|&(stop_position, _)| stop_position <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    #[must_use]
+    pub fn stops(&self) -> &[(f32, Color)] {
+        &self.stops
+    }
+
+    /// The color at the given position. Positions before the first or after the last stop have
+    /// the color of that stop.
+    #[must_use]
+    pub fn sample(&self, position: f32) -> Color {
+        let index = self
+            .stops
+            .partition_point(|&(stop_position, _)| stop_position <= position);
+
+        match (
+            index.checked_sub(1).map(|index| self.stops[index]),
+            self.stops.get(index),
+        ) {
+            (Some((start, from)), Some(&(end, to))) => {
+                from.lerp(to, (position - start) / (end - start))
+            }
+            (Some((_, color)), None) | (None, Some(&(_, color))) => color,
+            (None, None) => Color::TRANSPARENT,
+        }
+    }
+}
+
+fn from_hue_chroma(hue: f32, chroma: f32, offset: f32) -> Color {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue.rem_euclid(2.0) - 1.0).abs());
+
+    let (red, green, blue) = match hue {
+        hue if hue < 1.0 => (chroma, x, 0.0),
+        hue if hue < 2.0 => (x, chroma, 0.0),
+        hue if hue < 3.0 => (0.0, chroma, x),
+        hue if hue < 4.0 => (0.0, x, chroma),
+        hue if hue < 5.0 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    Color::rgb(red + offset, green + offset, blue + offset)
+}
+
+fn srgb_to_linear(channel: f32) -> f32 {
+    if channel <= 0.040_45 {
+        channel / 12.92
+    } else {
+        ((channel + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(channel: f32) -> f32 {
+    if channel <= 0.003_130_8 {
+        channel * 12.92
+    } else {
+        1.055f32.mul_add(channel.powf(1.0 / 2.4), -0.055)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color_eq(actual: Color, expected: Color) {
+        let close = actual
+            .to_array()
+            .iter()
+            .zip(expected.to_array())
+            .all(|(actual, expected)| (actual - expected).abs() < 1e-4);
+
+        assert!(close, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!("#ff0000".parse(), Ok(Color::RED));
+        assert_eq!("00ff00ff".parse(), Ok(Color::GREEN));
+        assert_eq!("#00f".parse(), Ok(Color::BLUE));
+        assert_eq!(
+            "#ff880080".parse::<Color>().unwrap().to_u8_array(),
+            [255, 136, 0, 128]
+        );
+        assert_eq!(
+            "#f808".parse::<Color>().unwrap().to_u8_array(),
+            [255, 136, 0, 136]
+        );
+
+        assert!("#ff00".parse::<Color>().is_ok());
+        assert!("#ff000".parse::<Color>().is_err());
+        assert!("#gg0000".parse::<Color>().is_err());
+        assert!("#ff00é".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn converts_between_srgb_and_linear() {
+        let color = Color::rgba(0.5, 0.02, 1.0, 0.5);
+        let [red, green, blue, alpha] = color.to_linear();
+
+        assert!((red - 0.214).abs() < 1e-3);
+        assert!((green - 0.001_548).abs() < 1e-5);
+        assert_eq!(blue, 1.0);
+        assert_eq!(alpha, 0.5);
+        assert_color_eq(Color::linear_rgba(red, green, blue, alpha), color);
+    }
+
+    #[test]
+    fn converts_hsl_and_hsv() {
+        assert_color_eq(Color::hsl(0.0, 1.0, 0.5), Color::RED);
+        assert_color_eq(Color::hsl(120.0, 1.0, 0.5), Color::GREEN);
+        assert_color_eq(Color::hsv(240.0, 1.0, 1.0), Color::BLUE);
+        assert_color_eq(Color::hsv(60.0, 1.0, 1.0), Color::YELLOW);
+        assert_color_eq(Color::hsl(-180.0, 1.0, 0.5), Color::CYAN);
+
+        let color = Color::rgb(0.2, 0.4, 0.8);
+        let [hue, saturation, lightness] = color.to_hsl();
+        assert_color_eq(Color::hsl(hue, saturation, lightness), color);
+        let [hue, saturation, value] = color.to_hsv();
+        assert_color_eq(Color::hsv(hue, saturation, value), color);
+
+        assert_eq!(Color::WHITE.to_hsl(), [0.0, 0.0, 1.0]);
+        assert_eq!(Color::BLACK.to_hsv(), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn lerp_interpolates_in_linear_space() {
+        let middle = Color::BLACK.lerp(Color::WHITE, 0.5);
+
+        assert!((middle.red - 0.735).abs() < 1e-3);
+        assert_color_eq(Color::RED.lerp(Color::BLUE, 0.0), Color::RED);
+        assert_color_eq(Color::RED.lerp(Color::BLUE, 1.0), Color::BLUE);
+    }
+
+    #[test]
+    fn gradient_samples_between_stops() {
+        let gradient = Gradient::new(Color::RED)
+            .with_stop(1.0, Color::GREEN)
+            .with_stop(0.5, Color::BLUE);
+
+        assert_eq!(gradient.stops().len(), 3);
+        assert_color_eq(gradient.sample(-1.0), Color::RED);
+        assert_color_eq(gradient.sample(0.5), Color::BLUE);
+        assert_color_eq(gradient.sample(2.0), Color::GREEN);
+        assert_color_eq(gradient.sample(0.75), Color::BLUE.lerp(Color::GREEN, 0.5));
+    }
+}
@@ -0,0 +1,550 @@
+//! # Console
+//! The [`Console`] resource is a registry of named debug commands with typed arguments and help
+//! texts. Commands are executed from code with [`Storage::run_command`], or typed into the
+//! terminal when the [`StdinConsoleSystem`] runs, e.g. in headless mode:
+//!
+//! ```
+//! use game_engine::console::{Argument, Console};
+//! use game_engine::ecs::World;
+//!
+//! struct Enemy(String);
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! let mut console = Console::new();
+//! console.register(
+//!     "spawn",
+//!     "Spawn enemies of a kind",
+//!     &[Argument::text("kind"), Argument::int("count").optional()],
+//!     |storage, arguments| {
+//!         let kind = arguments.text("kind").unwrap_or_default();
+//!         let count = arguments.int("count").unwrap_or(1);
+//!         for entity in 100..100 + count as usize {
+//!             storage.add_component_to_entity(entity, Enemy(kind.to_owned()));
+//!         }
+//!         Ok(format!("spawned {count} {kind}"))
+//!     },
+//! );
+//! world.storage.insert_resource(console);
+//!
+//! let output = world.storage.run_command("spawn goblin 5");
+//! assert_eq!(output.unwrap(), "spawned 5 goblin");
+//! ```
+//!
+//! The built-in `help` command lists all commands, `help <command>` shows the usage of one.
+use crate::ecs::{Storage, System};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+type CommandFn = Box<dyn FnMut(&mut Storage, &Arguments) -> Result<String, String>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConsoleError {
+    UnknownCommand(String),
+    MissingArgument {
+        command: String,
+        argument: &'static str,
+    },
+    InvalidArgument {
+        command: String,
+        argument: &'static str,
+        value: String,
+    },
+    TooManyArguments(String),
+    /// The command was executed and reported an error.
+    Failed(String),
+}
+
+impl Display for ConsoleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCommand(command) => write!(f, "unknown command {command}"),
+            Self::MissingArgument { command, argument } => {
+                write!(f, "{command}: missing argument {argument}")
+            }
+            Self::InvalidArgument {
+                command,
+                argument,
+                value,
+            } => write!(
+                f,
+                "{command}: invalid value {value} for argument {argument}"
+            ),
+            Self::TooManyArguments(command) => write!(f, "{command}: too many arguments"),
+            Self::Failed(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ConsoleError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentType {
+    Int,
+    Float,
+    Bool,
+    Text,
+}
+
+/// The declaration of a command argument. Optional arguments have to come last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argument {
+    pub name: &'static str,
+    pub kind: ArgumentType,
+    pub optional: bool,
+}
+
+impl Argument {
+    #[must_use]
+    pub const fn int(name: &'static str) -> Self {
+        Self::new(name, ArgumentType::Int)
+    }
+
+    #[must_use]
+    pub const fn float(name: &'static str) -> Self {
+        Self::new(name, ArgumentType::Float)
+    }
+
+    #[must_use]
+    pub const fn bool(name: &'static str) -> Self {
+        Self::new(name, ArgumentType::Bool)
+    }
+
+    #[must_use]
+    pub const fn text(name: &'static str) -> Self {
+        Self::new(name, ArgumentType::Text)
+    }
+
+    #[must_use]
+    pub const fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    const fn new(name: &'static str, kind: ArgumentType) -> Self {
+        Self {
+            name,
+            kind,
+            optional: false,
+        }
+    }
+
+    fn parse(self, value: &str) -> Option<Value> {
+        match self.kind {
+            ArgumentType::Int => value.parse().ok().map(Value::Int),
+            ArgumentType::Float => value.parse().ok().map(Value::Float),
+            ArgumentType::Bool => match value {
+                "true" | "on" | "1" => Some(Value::Bool(true)),
+                "false" | "off" | "0" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ArgumentType::Text => Some(Value::Text(value.to_owned())),
+        }
+    }
+}
+
+impl Display for Argument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ArgumentType::Int => "int",
+            ArgumentType::Float => "float",
+            ArgumentType::Bool => "bool",
+            ArgumentType::Text => "text",
+        };
+
+        if self.optional {
+            write!(f, "[{}: {kind}]", self.name)
+        } else {
+            write!(f, "<{}: {kind}>", self.name)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+}
+
+/// The parsed arguments of a command invocation. Optional arguments that were not given are None.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Arguments {
+    values: Vec<(&'static str, Value)>,
+}
+
+impl Arguments {
+    fn get(&self, name: &str) -> Option<&Value> {
+        self.values
+            .iter()
+            .find(|(argument, _)| *argument == name)
+            .map(|(_, value)| value)
+    }
+
+    #[must_use]
+    pub fn int(&self, name: &str) -> Option<i64> {
+        match self.get(name)? {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a float argument. Int arguments are converted.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn float(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            Value::Float(value) => Some(*value),
+            Value::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)? {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            Value::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+struct Command {
+    help: String,
+    arguments: Vec<Argument>,
+    run: CommandFn,
+}
+
+/// The registered debug commands, see the [module documentation](self).
+#[derive(Default)]
+pub struct Console {
+    commands: BTreeMap<String, Command>,
+}
+
+impl Console {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a command. A command with the same name is replaced. The command returns the text
+    /// that is shown to the user, or an error message.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a required argument follows an optional one.
+    pub fn register(
+        &mut self,
+        name: &str,
+        help: &str,
+        arguments: &[Argument],
+        run: impl FnMut(&mut Storage, &Arguments) -> Result<String, String> + 'static,
+    ) {
+        assert!(
+            arguments
+                .windows(2)
+                .all(|pair| !pair[0].optional || pair[1].optional),
+            "Optional command arguments have to come last"
+        );
+
+        self.commands.insert(
+            name.to_owned(),
+            Command {
+                help: help.to_owned(),
+                arguments: arguments.to_vec(),
+                run: Box::new(run),
+            },
+        );
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.commands.remove(name);
+    }
+
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// The names of all commands in alphabetical order.
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.commands.keys().map(String::as_str)
+    }
+
+    /// The usage and help text of a command, e.g. `spawn <kind: text> [count: int] - Spawn
+    /// enemies`.
+    #[must_use]
+    pub fn usage(&self, name: &str) -> Option<String> {
+        let command = self.commands.get(name)?;
+        let mut usage = name.to_owned();
+
+        for argument in &command.arguments {
+            usage.push_str(&format!(" {argument}"));
+        }
+        Some(format!("{usage} - {}", command.help))
+    }
+
+    fn help(&self, name: Option<&str>) -> Result<String, ConsoleError> {
+        match name {
+            Some(name) => self
+                .usage(name)
+                .ok_or_else(|| ConsoleError::UnknownCommand(name.to_owned())),
+            None => Ok(self
+                .commands()
+                .filter_map(|name| self.usage(name))
+                .collect::<Vec<_>>()
+                .join("\n")),
+        }
+    }
+}
+
+/// Split a command line into words. Words containing whitespace can be quoted with `"`.
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut in_word = false;
+
+    for character in line.chars() {
+        match character {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            character if character.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            character => {
+                word.push(character);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+impl Storage {
+    /// Parse and execute a command line of the [`Console`] resource, see the
+    /// [module documentation](crate::console). Returns the output of the command. Empty lines do
+    /// nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command does not exist, the arguments do not match or the command
+    /// failed.
+    pub fn run_command(&mut self, line: &str) -> Result<String, ConsoleError> {
+        let words = split_words(line);
+        let Some((name, values)) = words.split_first() else {
+            return Ok(String::new());
+        };
+
+        let mut console = self.remove_resource::<Console>().unwrap_or_default();
+        let result = match console.commands.get_mut(name) {
+            Some(command) => {
+                parse_arguments(name, &command.arguments, values).and_then(|arguments| {
+                    (command.run)(self, &arguments).map_err(ConsoleError::Failed)
+                })
+            }
+            None if name == "help" => console.help(values.first().map(String::as_str)),
+            None => Err(ConsoleError::UnknownCommand(name.clone())),
+        };
+
+        // keep the commands that were registered while the command was running
+        if let Some(registered) = self.remove_resource::<Console>() {
+            console.commands.extend(registered.commands);
+        }
+        self.insert_resource(console);
+
+        result
+    }
+}
+
+fn parse_arguments(
+    command: &str,
+    arguments: &[Argument],
+    values: &[String],
+) -> Result<Arguments, ConsoleError> {
+    if values.len() > arguments.len() {
+        return Err(ConsoleError::TooManyArguments(command.to_owned()));
+    }
+
+    let mut parsed = Arguments::default();
+    for (index, argument) in arguments.iter().enumerate() {
+        let Some(value) = values.get(index) else {
+            if argument.optional {
+                continue;
+            }
+            return Err(ConsoleError::MissingArgument {
+                command: command.to_owned(),
+                argument: argument.name,
+            });
+        };
+
+        let value = argument
+            .parse(value)
+            .ok_or_else(|| ConsoleError::InvalidArgument {
+                command: command.to_owned(),
+                argument: argument.name,
+                value: value.clone(),
+            })?;
+        parsed.values.push((argument.name, value));
+    }
+    Ok(parsed)
+}
+
+/// Reads command lines from the standard input and runs them with [`Storage::run_command`] once
+/// per update. The output and errors are printed to the standard output and error streams.
+pub struct StdinConsoleSystem {
+    lines: Receiver<String>,
+}
+
+impl System for StdinConsoleSystem {
+    fn new() -> Self {
+        let (sender, lines) = mpsc::channel();
+
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    return;
+                };
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Self { lines }
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        while let Ok(line) = self.lines.try_recv() {
+            match storage.run_command(&line) {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => {
+                    let _ = writeln!(io::stdout(), "{output}");
+                }
+                Err(error) => {
+                    let _ = writeln!(io::stderr(), "{error}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TimeScale(f64);
+
+    fn storage() -> Storage {
+        let mut console = Console::new();
+        console.register(
+            "timescale",
+            "Set the time scale",
+            &[Argument::float("scale"), Argument::bool("log").optional()],
+            |storage, arguments| {
+                let scale = arguments.float("scale").unwrap();
+                if scale < 0.0 {
+                    return Err("scale must not be negative".to_owned());
+                }
+                storage.insert_resource(TimeScale(scale));
+                Ok(if arguments.bool("log") == Some(true) {
+                    format!("time scale is {scale}")
+                } else {
+                    String::new()
+                })
+            },
+        );
+        console.register(
+            "echo",
+            "Print a text",
+            &[Argument::text("text")],
+            |_, arguments| Ok(arguments.text("text").unwrap().to_owned()),
+        );
+
+        let mut storage = Storage::new();
+        storage.insert_resource(console);
+        storage
+    }
+
+    #[test]
+    fn commands_parse_typed_arguments() {
+        let mut storage = storage();
+
+        assert_eq!(storage.run_command("timescale 2"), Ok(String::new()));
+        assert_eq!(storage.get_resource::<TimeScale>().unwrap().0, 2.0);
+        assert_eq!(
+            storage.run_command("timescale 0.5 on"),
+            Ok("time scale is 0.5".to_owned())
+        );
+        assert_eq!(
+            storage.run_command(r#"echo "hello world""#),
+            Ok("hello world".to_owned())
+        );
+        assert_eq!(storage.run_command("  "), Ok(String::new()));
+    }
+
+    #[test]
+    fn invalid_invocations_are_reported() {
+        let mut storage = storage();
+
+        assert_eq!(
+            storage.run_command("spawn"),
+            Err(ConsoleError::UnknownCommand("spawn".to_owned()))
+        );
+        assert_eq!(
+            storage.run_command("timescale"),
+            Err(ConsoleError::MissingArgument {
+                command: "timescale".to_owned(),
+                argument: "scale"
+            })
+        );
+        assert_eq!(
+            storage.run_command("timescale fast"),
+            Err(ConsoleError::InvalidArgument {
+                command: "timescale".to_owned(),
+                argument: "scale",
+                value: "fast".to_owned()
+            })
+        );
+        assert_eq!(
+            storage.run_command("echo a b"),
+            Err(ConsoleError::TooManyArguments("echo".to_owned()))
+        );
+        assert_eq!(
+            storage.run_command("timescale -1"),
+            Err(ConsoleError::Failed(
+                "scale must not be negative".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn help_lists_the_usage_of_commands() {
+        let mut storage = storage();
+
+        assert_eq!(
+            storage.run_command("help timescale"),
+            Ok("timescale <scale: float> [log: bool] - Set the time scale".to_owned())
+        );
+        assert_eq!(storage.run_command("help").unwrap().lines().count(), 2);
+    }
+}
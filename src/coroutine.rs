@@ -0,0 +1,313 @@
+//! # Coroutines
+//! Coroutines are logic that spans multiple frames, like cutscenes or spawn sequences. They are
+//! written as async blocks that wait for time, events or conditions and are resumed once per
+//! frame by the [`CoroutineSystem`]:
+//!
+//! ```
+//! use game_engine::coroutine::CoroutineSystem;
+//! use game_engine::ecs::{System, World};
+//! use std::time::Duration;
+//!
+//! struct Enemy;
+//! #[derive(Clone)]
+//! struct BossDefeated;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.add_system(CoroutineSystem::new());
+//!
+//! world.storage.start_coroutine(|cx| async move {
+//!     for _ in 0..3 {
+//!         cx.wait_seconds(2.0).await;
+//!         cx.with_storage(|storage| storage.add_component_to_entity(0, Enemy));
+//!     }
+//!
+//!     cx.wait_for_event::<BossDefeated>().await;
+//!     // play the outro
+//! });
+//!
+//! world.tick(Duration::from_millis(16));
+//! ```
+use crate::ecs::{Storage, System};
+use crate::time::Time;
+use std::cell::RefCell;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+type LocalBoxFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+thread_local! {
+    /// The storage of the world whose coroutines are currently resumed. The storage is moved here
+    /// while the coroutines run, so that they can access it between their waits.
+    static CURRENT_STORAGE: RefCell<Option<Storage>> = const { RefCell::new(None) };
+}
+
+/// The running coroutines of a world. The resource is created by [`Storage::start_coroutine`].
+#[derive(Default)]
+pub struct Coroutines {
+    running: Vec<LocalBoxFuture>,
+}
+
+impl Coroutines {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+}
+
+impl Storage {
+    /// Start a coroutine. It runs for the first time in the next update of the
+    /// [`CoroutineSystem`].
+    pub fn start_coroutine<F, Fut>(&mut self, coroutine: F)
+    where
+        F: FnOnce(CoroutineContext) -> Fut,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let coroutine = Box::pin(coroutine(CoroutineContext { _private: () }));
+
+        if let Some(coroutines) = self.get_resource_mut::<Coroutines>() {
+            coroutines.running.push(coroutine);
+        } else {
+            self.insert_resource(Coroutines {
+                running: vec![coroutine],
+            });
+        }
+    }
+}
+
+/// The handle a coroutine uses to access the world and to wait. All waits are measured in frames
+/// of the [`CoroutineSystem`]: a coroutine that waits is resumed in the next frame at the
+/// earliest.
+pub struct CoroutineContext {
+    _private: (),
+}
+
+impl CoroutineContext {
+    /// Access the storage of the world.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is called inside another `with_storage` call.
+    pub fn with_storage<R>(&self, f: impl FnOnce(&mut Storage) -> R) -> R {
+        CURRENT_STORAGE.with_borrow_mut(|storage| {
+            f(storage
+                .as_mut()
+                .expect("Coroutines can only access the storage while they are resumed"))
+        })
+    }
+
+    /// Continue in the next frame.
+    pub async fn next_frame(&self) {
+        self.wait_frames(1).await;
+    }
+
+    /// Continue after the given number of frames.
+    pub async fn wait_frames(&self, frames: u32) {
+        let mut remaining = frames;
+
+        poll_fn(|_| {
+            if remaining == 0 {
+                return Poll::Ready(());
+            }
+
+            remaining -= 1;
+            Poll::Pending
+        })
+        .await;
+    }
+
+    /// Continue after the given game time has passed, see [`Time::delta`]. Without a time
+    /// resource, the coroutine waits forever.
+    pub async fn wait_seconds(&self, seconds: f32) {
+        let mut remaining = seconds;
+        let mut started = false;
+
+        poll_fn(|_| {
+            // the frame in which the wait started does not count
+            if started {
+                remaining -= self.with_storage(|storage| {
+                    storage
+                        .get_resource::<Time>()
+                        .map_or(0.0, Time::delta_seconds)
+                });
+            }
+            started = true;
+
+            if remaining <= 0.0 {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+
+    /// Continue as soon as the condition is true. It is checked once per frame.
+    pub async fn wait_until(&self, mut condition: impl FnMut(&Storage) -> bool) {
+        poll_fn(|_| {
+            if self.with_storage(|storage| condition(storage)) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+
+    /// Continue as soon as an event of the given type is sent and return a copy of it. Events that
+    /// are already pending when the wait starts are ignored. The event is not consumed, so other
+    /// systems still receive it.
+    pub async fn wait_for_event<EventType: Clone + 'static>(&self) -> EventType {
+        let mut seen = None;
+
+        poll_fn(|_| {
+            self.with_storage(|storage| {
+                let pending = storage.read_events::<EventType>().count();
+                // events may have been drained since the last frame
+                let seen = seen.get_or_insert(pending);
+                *seen = (*seen).min(pending);
+
+                match storage.read_events::<EventType>().nth(*seen) {
+                    Some(event) => Poll::Ready(event.clone()),
+                    None => Poll::Pending,
+                }
+            })
+        })
+        .await
+    }
+}
+
+/// Resumes all [coroutines](Storage::start_coroutine) once per update until they are finished.
+pub struct CoroutineSystem;
+
+impl System for CoroutineSystem {
+    fn new() -> Self {
+        Self
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let Some(mut coroutines) = storage.remove_resource::<Coroutines>() else {
+            return;
+        };
+
+        {
+            let _current = CurrentStorage::enter(storage);
+            let mut context = Context::from_waker(Waker::noop());
+
+            coroutines
+                .running
+                .retain_mut(|coroutine| coroutine.as_mut().poll(&mut context).is_pending());
+        }
+
+        // keep the coroutines that were started while the others were running
+        if let Some(started) = storage.remove_resource::<Coroutines>() {
+            coroutines.running.extend(started.running);
+        }
+        storage.insert_resource(coroutines);
+    }
+}
+
+/// Moves a storage into [`CURRENT_STORAGE`] and puts it back when dropped, even if a coroutine
+/// panics.
+struct CurrentStorage<'a> {
+    storage: &'a mut Storage,
+}
+
+impl<'a> CurrentStorage<'a> {
+    fn enter(storage: &'a mut Storage) -> Self {
+        let current = std::mem::replace(storage, Storage::new());
+        CURRENT_STORAGE.set(Some(current));
+
+        Self { storage }
+    }
+}
+
+impl Drop for CurrentStorage<'_> {
+    fn drop(&mut self) {
+        if let Some(current) = CURRENT_STORAGE.take() {
+            *self.storage = current;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Query, World};
+    use std::time::Duration;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Go(usize);
+    struct Spawned(usize);
+
+    fn spawned(world: &World) -> Vec<usize> {
+        let mut spawned: Vec<_> = world
+            .storage
+            .query_one::<Spawned>()
+            .map(|spawned| spawned.0)
+            .collect();
+        spawned.sort_unstable();
+        spawned
+    }
+
+    #[test]
+    fn coroutines_wait_for_time_and_events() {
+        let mut world = World::init().unwrap();
+        world.add_system(CoroutineSystem::new());
+        world.storage.send_event(Go(0));
+
+        world.storage.start_coroutine(|cx| async move {
+            cx.wait_seconds(1.0).await;
+            cx.with_storage(|storage| storage.add_component_to_entity(0, Spawned(0)));
+
+            let Go(id) = cx.wait_for_event::<Go>().await;
+            cx.with_storage(|storage| storage.add_component_to_entity(id, Spawned(id)));
+        });
+
+        world.tick(Duration::from_millis(500));
+        world.tick(Duration::from_millis(500));
+        assert!(spawned(&world).is_empty());
+
+        world.tick(Duration::from_millis(500));
+        assert_eq!(spawned(&world), [0]);
+
+        world.storage.send_event(Go(1));
+        world.tick(Duration::from_millis(500));
+        assert_eq!(spawned(&world), [0, 1]);
+        assert!(world
+            .storage
+            .get_resource::<Coroutines>()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn coroutines_can_start_coroutines() {
+        let mut world = World::init().unwrap();
+        world.add_system(CoroutineSystem::new());
+
+        world.storage.start_coroutine(|cx| async move {
+            cx.wait_until(|storage| storage.has_resource::<Go>()).await;
+            cx.with_storage(|storage| {
+                storage.start_coroutine(|cx| async move {
+                    cx.next_frame().await;
+                    cx.with_storage(|storage| storage.add_component_to_entity(2, Spawned(2)));
+                });
+            });
+        });
+
+        world.tick(Duration::from_millis(16));
+        world.storage.insert_resource(Go(0));
+        world.tick(Duration::from_millis(16));
+        assert_eq!(world.storage.get_resource::<Coroutines>().unwrap().len(), 1);
+
+        world.tick(Duration::from_millis(16));
+        world.tick(Duration::from_millis(16));
+        assert_eq!(spawned(&world), [2]);
+    }
+}
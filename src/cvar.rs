@@ -0,0 +1,712 @@
+//! # Console variables
+//! The [`CVars`] resource holds named, typed values that systems read every frame, like the time
+//! scale or debug toggles. They can be changed live with [`Storage::set_cvar`], from the
+//! [`Console`] once the commands of [`CVars::register_commands`] are registered, or from config
+//! files with [`Storage::load_cvars`]:
+//!
+//! ```
+//! use game_engine::console::Console;
+//! use game_engine::cvar::{CVar, CVars};
+//! use game_engine::ecs::World;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! let mut cvars = CVars::new();
+//! cvars.register(CVar::new("timescale", 1.0).with_range(0.0, 10.0).with_help("Game speed"));
+//! cvars.register(CVar::new("god_mode", false).cheat());
+//!
+//! let mut console = Console::new();
+//! CVars::register_commands(&mut console);
+//! world.storage.insert_resource(cvars);
+//! world.storage.insert_resource(console);
+//!
+//! world.storage.run_command("set timescale 0.5").unwrap();
+//! assert!(world.storage.run_command("set god_mode true").is_err());
+//!
+//! let cvars = world.storage.get_resource::<CVars>().unwrap();
+//! assert_eq!(cvars.float("timescale"), Some(0.5));
+//! ```
+use crate::console::{Argument, Console};
+use crate::ecs::Storage;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::{fs, io};
+
+#[derive(Debug)]
+pub enum CVarError {
+    Unknown(String),
+    /// The value does not have the type of the variable.
+    InvalidValue {
+        name: String,
+        value: String,
+    },
+    OutOfRange {
+        name: String,
+        min: f64,
+        max: f64,
+    },
+    /// The variable is a cheat and [`CVars::cheats_enabled`] is false.
+    CheatsDisabled(String),
+    ReadOnly(String),
+    Io(io::Error),
+    /// The config file is not valid TOML.
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl Display for CVarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "unknown variable {name}"),
+            Self::InvalidValue { name, value } => write!(f, "invalid value {value} for {name}"),
+            Self::OutOfRange { name, min, max } => {
+                write!(f, "{name} has to be between {min} and {max}")
+            }
+            Self::CheatsDisabled(name) => write!(f, "{name} can only be changed with cheats"),
+            Self::ReadOnly(name) => write!(f, "{name} is read only"),
+            Self::Io(error) => write!(f, "cvar io error: {error}"),
+            Self::Parse(error) => write!(f, "invalid cvar file: {error}"),
+            Self::Serialize(error) => write!(f, "cvars could not be serialized: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for CVarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+            Self::Serialize(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CVarError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CVarValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl CVarValue {
+    /// Parse a value of the same type as `self`, e.g. the value of a console command.
+    fn parse_like(&self, value: &str) -> Option<Self> {
+        match self {
+            Self::Bool(_) => match value {
+                "true" | "on" | "1" => Some(Self::Bool(true)),
+                "false" | "off" | "0" => Some(Self::Bool(false)),
+                _ => None,
+            },
+            Self::Int(_) => value.parse().ok().map(Self::Int),
+            Self::Float(_) => value.parse().ok().map(Self::Float),
+            Self::Text(_) => Some(Self::Text(value.to_owned())),
+        }
+    }
+
+    /// Convert `value` to the type of `self`. Ints are converted to floats.
+    #[allow(clippy::cast_precision_loss)]
+    fn convert_like(&self, value: Self) -> Option<Self> {
+        match (self, value) {
+            (Self::Float(_), Self::Int(value)) => Some(Self::Float(value as f64)),
+            (current, value)
+                if std::mem::discriminant(current) == std::mem::discriminant(&value) =>
+            {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    fn from_toml(value: toml::Value) -> Option<Self> {
+        match value {
+            toml::Value::Boolean(value) => Some(Self::Bool(value)),
+            toml::Value::Integer(value) => Some(Self::Int(value)),
+            toml::Value::Float(value) => Some(Self::Float(value)),
+            toml::Value::String(value) => Some(Self::Text(value)),
+            _ => None,
+        }
+    }
+
+    fn to_toml(&self) -> toml::Value {
+        match self {
+            Self::Bool(value) => toml::Value::Boolean(*value),
+            Self::Int(value) => toml::Value::Integer(*value),
+            Self::Float(value) => toml::Value::Float(*value),
+            Self::Text(value) => toml::Value::String(value.clone()),
+        }
+    }
+}
+
+impl Display for CVarValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Text(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<bool> for CVarValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i32> for CVarValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<i64> for CVarValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<f64> for CVarValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<&str> for CVarValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<String> for CVarValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+/// A console variable. The type of its default value is the type of the variable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CVar {
+    name: String,
+    value: CVarValue,
+    default: CVarValue,
+    help: String,
+    range: Option<(f64, f64)>,
+    /// Can only be changed when cheats are enabled.
+    pub is_cheat: bool,
+    /// Is saved to config files by [`CVars::save`].
+    pub is_persistent: bool,
+    /// Can only be changed from code with [`CVar::set_value`].
+    pub is_read_only: bool,
+}
+
+impl CVar {
+    #[must_use]
+    pub fn new(name: &str, default: impl Into<CVarValue>) -> Self {
+        let default = default.into();
+
+        Self {
+            name: name.to_owned(),
+            value: default.clone(),
+            default,
+            help: String::new(),
+            range: None,
+            is_cheat: false,
+            is_persistent: false,
+            is_read_only: false,
+        }
+    }
+
+    #[must_use]
+    pub fn with_help(mut self, help: &str) -> Self {
+        help.clone_into(&mut self.help);
+        self
+    }
+
+    /// Limit the values of an int or float variable to `min..=max`.
+    #[must_use]
+    pub const fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    #[must_use]
+    pub const fn cheat(mut self) -> Self {
+        self.is_cheat = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn persistent(mut self) -> Self {
+        self.is_persistent = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn read_only(mut self) -> Self {
+        self.is_read_only = true;
+        self
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[must_use]
+    pub const fn value(&self) -> &CVarValue {
+        &self.value
+    }
+
+    #[must_use]
+    pub const fn default_value(&self) -> &CVarValue {
+        &self.default
+    }
+
+    #[must_use]
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    /// Set the value, ignoring the cheat and read only flags. Returns true if the value changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value has a different type or is out of range.
+    pub fn set_value(&mut self, value: impl Into<CVarValue>) -> Result<bool, CVarError> {
+        let value = value.into();
+        let invalid = || CVarError::InvalidValue {
+            name: self.name.clone(),
+            value: value.to_string(),
+        };
+        let value = self
+            .default
+            .convert_like(value.clone())
+            .ok_or_else(invalid)?;
+
+        if let (Some((min, max)), Some(number)) = (self.range, value.as_number()) {
+            if !(min..=max).contains(&number) {
+                return Err(CVarError::OutOfRange {
+                    name: self.name.clone(),
+                    min,
+                    max,
+                });
+            }
+        }
+
+        let changed = self.value != value;
+        self.value = value;
+        Ok(changed)
+    }
+}
+
+/// Sent as ECS event by [`Storage::set_cvar`] when the value of a variable changed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CVarChanged(pub String);
+
+/// The registered console variables, see the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct CVars {
+    vars: BTreeMap<String, CVar>,
+    /// Whether variables flagged as cheat can be changed with [`CVars::set`].
+    pub cheats_enabled: bool,
+}
+
+impl CVars {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a variable. A variable with the same name is replaced.
+    pub fn register(&mut self, cvar: CVar) {
+        self.vars.insert(cvar.name.clone(), cvar);
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&CVar> {
+        self.vars.get(name)
+    }
+
+    /// The variable with the given name, e.g. to change it with [`CVar::set_value`].
+    #[must_use]
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut CVar> {
+        self.vars.get_mut(name)
+    }
+
+    /// All variables in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = &CVar> {
+        self.vars.values()
+    }
+
+    #[must_use]
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.get(name)?.value {
+            CVarValue::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn int(&self, name: &str) -> Option<i64> {
+        match self.get(name)?.value {
+            CVarValue::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn float(&self, name: &str) -> Option<f64> {
+        match self.get(name)?.value {
+            CVarValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match &self.get(name)?.value {
+            CVarValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Change a variable the way a user would, respecting the cheat and read only flags. Returns
+    /// true if the value changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable does not exist, can not be changed, or the value is
+    /// invalid.
+    pub fn set(&mut self, name: &str, value: impl Into<CVarValue>) -> Result<bool, CVarError> {
+        let cheats_enabled = self.cheats_enabled;
+        let cvar = self
+            .vars
+            .get_mut(name)
+            .ok_or_else(|| CVarError::Unknown(name.to_owned()))?;
+
+        if cvar.is_read_only {
+            return Err(CVarError::ReadOnly(name.to_owned()));
+        }
+        if cvar.is_cheat && !cheats_enabled {
+            return Err(CVarError::CheatsDisabled(name.to_owned()));
+        }
+        cvar.set_value(value)
+    }
+
+    /// Like [`CVars::set`], but parses the value according to the type of the variable.
+    ///
+    /// # Errors
+    ///
+    /// See [`CVars::set`].
+    pub fn set_from_str(&mut self, name: &str, value: &str) -> Result<bool, CVarError> {
+        let parsed = self
+            .get(name)
+            .ok_or_else(|| CVarError::Unknown(name.to_owned()))?
+            .default
+            .parse_like(value)
+            .ok_or_else(|| CVarError::InvalidValue {
+                name: name.to_owned(),
+                value: value.to_owned(),
+            })?;
+
+        self.set(name, parsed)
+    }
+
+    /// Set all variables of a TOML config file, e.g. `timescale = 0.5`. Variables that are not
+    /// registered are ignored, so the file has to be loaded after registering the variables.
+    /// Values that can not be set don't stop the others from being set, their errors are
+    /// returned instead. Use [`Storage::load_cvars`] once the game runs, so that systems are
+    /// informed about the changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or parsed.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<Vec<CVarError>, CVarError> {
+        self.load_values(path).map(|loaded| loaded.errors)
+    }
+
+    fn load_values(&mut self, path: impl AsRef<Path>) -> Result<LoadedValues, CVarError> {
+        let contents = fs::read_to_string(path)?;
+        let values: BTreeMap<String, toml::Value> =
+            toml::from_str(&contents).map_err(CVarError::Parse)?;
+        let mut loaded = LoadedValues::default();
+
+        for (name, value) in values {
+            if !self.vars.contains_key(&name) {
+                continue;
+            }
+            let result = CVarValue::from_toml(value.clone())
+                .ok_or_else(|| CVarError::InvalidValue {
+                    name: name.clone(),
+                    value: value.to_string(),
+                })
+                .and_then(|value| self.set(&name, value));
+
+            match result {
+                Ok(true) => loaded.changed.push(name),
+                Ok(false) => {}
+                Err(error) => loaded.errors.push(error),
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Write all persistent variables to a TOML config file that can be read by [`CVars::load`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CVarError> {
+        let values: BTreeMap<&str, toml::Value> = self
+            .iter()
+            .filter(|cvar| cvar.is_persistent)
+            .map(|cvar| (cvar.name(), cvar.value.to_toml()))
+            .collect();
+        let contents = toml::to_string(&values).map_err(CVarError::Serialize)?;
+
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Register the `set <name> <value>`, `get <name>`, `reset <name>` and `cvars` commands, which
+    /// change the [`CVars`] resource.
+    pub fn register_commands(console: &mut Console) {
+        console.register(
+            "set",
+            "Set a console variable",
+            &[Argument::text("name"), Argument::text("value")],
+            |storage, arguments| {
+                let name = arguments.text("name").unwrap_or_default();
+                let value = arguments.text("value").unwrap_or_default();
+
+                storage
+                    .set_cvar(name, value)
+                    .map(|_| String::new())
+                    .map_err(|error| error.to_string())
+            },
+        );
+        console.register(
+            "get",
+            "Show a console variable",
+            &[Argument::text("name")],
+            |storage, arguments| {
+                let name = arguments.text("name").unwrap_or_default();
+
+                storage
+                    .get_resource::<Self>()
+                    .and_then(|cvars| cvars.get(name))
+                    .map(|cvar| format!("{name} = {}", cvar.value))
+                    .ok_or_else(|| CVarError::Unknown(name.to_owned()).to_string())
+            },
+        );
+        console.register(
+            "reset",
+            "Reset a console variable to its default",
+            &[Argument::text("name")],
+            |storage, arguments| {
+                let name = arguments.text("name").unwrap_or_default();
+                let default = storage
+                    .get_resource::<Self>()
+                    .and_then(|cvars| cvars.get(name))
+                    .map(|cvar| cvar.default.to_string())
+                    .ok_or_else(|| CVarError::Unknown(name.to_owned()).to_string())?;
+
+                storage
+                    .set_cvar(name, &default)
+                    .map(|_| String::new())
+                    .map_err(|error| error.to_string())
+            },
+        );
+        console.register("cvars", "List all console variables", &[], |storage, _| {
+            Ok(storage
+                .get_resource::<Self>()
+                .into_iter()
+                .flat_map(Self::iter)
+                .map(|cvar| format!("{} = {} - {}", cvar.name, cvar.value, cvar.help))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        });
+    }
+}
+
+/// The outcome of loading a config file, see [`CVars::load`].
+#[derive(Default)]
+struct LoadedValues {
+    changed: Vec<String>,
+    errors: Vec<CVarError>,
+}
+
+impl Storage {
+    /// Change a variable of the [`CVars`] resource like [`CVars::set_from_str`] and send a
+    /// [`CVarChanged`] event if the value changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such variable or the value can not be set.
+    pub fn set_cvar(&mut self, name: &str, value: &str) -> Result<(), CVarError> {
+        let changed = self
+            .get_resource_mut::<CVars>()
+            .ok_or_else(|| CVarError::Unknown(name.to_owned()))?
+            .set_from_str(name, value)?;
+
+        if changed {
+            self.send_event(CVarChanged(name.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Load a config file into the [`CVars`] resource like [`CVars::load`] and send a
+    /// [`CVarChanged`] event for every variable whose value changed. Returns the errors of the
+    /// values that could not be set. Without a [`CVars`] resource no variables are registered, so
+    /// nothing is loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or parsed.
+    pub fn load_cvars(&mut self, path: impl AsRef<Path>) -> Result<Vec<CVarError>, CVarError> {
+        let Some(cvars) = self.get_resource_mut::<CVars>() else {
+            return Ok(Vec::new());
+        };
+        let loaded = cvars.load_values(path)?;
+
+        for name in loaded.changed {
+            self.send_event(CVarChanged(name));
+        }
+        Ok(loaded.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn cvars() -> CVars {
+        let mut cvars = CVars::new();
+        cvars.register(CVar::new("timescale", 1.0).with_range(0.0, 10.0));
+        cvars.register(CVar::new("max_enemies", 20).persistent());
+        cvars.register(CVar::new("god_mode", false).cheat());
+        cvars.register(CVar::new("version", "1.0").read_only());
+        cvars
+    }
+
+    #[test]
+    fn values_are_checked_against_type_range_and_flags() {
+        let mut cvars = cvars();
+
+        assert!(cvars.set("timescale", 2).unwrap());
+        assert_eq!(cvars.float("timescale"), Some(2.0));
+        assert!(!cvars.set_from_str("timescale", "2").unwrap());
+        assert!(matches!(
+            cvars.set_from_str("timescale", "11"),
+            Err(CVarError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            cvars.set("max_enemies", "many"),
+            Err(CVarError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            cvars.set("version", "2.0"),
+            Err(CVarError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            cvars.set("god_mode", true),
+            Err(CVarError::CheatsDisabled(_))
+        ));
+
+        cvars.cheats_enabled = true;
+        cvars.set_from_str("god_mode", "on").unwrap();
+        assert_eq!(cvars.bool("god_mode"), Some(true));
+        assert!(matches!(cvars.set("fov", 90), Err(CVarError::Unknown(_))));
+    }
+
+    #[test]
+    fn persistent_values_are_saved_and_loaded() {
+        let path = env::temp_dir().join(format!("game-engine-cvars-{}.toml", std::process::id()));
+        let mut cvars = cvars();
+        cvars.set("max_enemies", 50).unwrap();
+        cvars.set("timescale", 0.5).unwrap();
+        cvars.save(&path).unwrap();
+
+        let mut loaded = self::cvars();
+        assert!(loaded.load(&path).unwrap().is_empty());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.int("max_enemies"), Some(50));
+        assert_eq!(loaded.float("timescale"), Some(1.0));
+    }
+
+    #[test]
+    fn loading_applies_all_valid_values() {
+        let path = env::temp_dir().join(format!(
+            "game-engine-cvars-partial-{}.toml",
+            std::process::id()
+        ));
+        fs::write(
+            &path,
+            "god_mode = true\nmax_enemies = 5\ntimescale = \"fast\"\nversion = \"1.0\"\n",
+        )
+        .unwrap();
+        let mut storage = Storage::new();
+        storage.insert_resource(cvars());
+
+        let errors = storage.load_cvars(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], CVarError::CheatsDisabled(_)));
+        assert!(matches!(errors[1], CVarError::InvalidValue { .. }));
+        assert!(matches!(errors[2], CVarError::ReadOnly(_)));
+        let cvars = storage.get_resource::<CVars>().unwrap();
+        assert_eq!(cvars.int("max_enemies"), Some(5));
+        assert_eq!(cvars.bool("god_mode"), Some(false));
+        assert_eq!(
+            storage.drain_events::<CVarChanged>().collect::<Vec<_>>(),
+            [CVarChanged("max_enemies".to_owned())]
+        );
+    }
+
+    #[test]
+    fn console_commands_change_the_resource() {
+        let mut storage = Storage::new();
+        let mut console = Console::new();
+        CVars::register_commands(&mut console);
+        storage.insert_resource(console);
+        storage.insert_resource(cvars());
+
+        storage.run_command("set max_enemies 5").unwrap();
+        assert_eq!(
+            storage.run_command("get max_enemies").unwrap(),
+            "max_enemies = 5"
+        );
+        storage.run_command("reset max_enemies").unwrap();
+        assert!(storage.run_command("set timescale fast").is_err());
+
+        assert_eq!(
+            storage.drain_events::<CVarChanged>().collect::<Vec<_>>(),
+            [
+                CVarChanged("max_enemies".to_owned()),
+                CVarChanged("max_enemies".to_owned())
+            ]
+        );
+        assert_eq!(storage.run_command("cvars").unwrap().lines().count(), 4);
+    }
+}
@@ -1,13 +1,20 @@
-use std::any::TypeId;
+use crate::ecs::clone::CloneFn;
 use crate::ecs::storage::{ComponentVec, EntityRow};
+use crate::ecs::EntityId;
+use std::any::TypeId;
+use std::collections::HashMap;
 
 #[allow(clippy::module_name_repetitions)]
 pub type ArchetypeId = usize;
 
+/// A table of all entities that have exactly the same set of component types. Each component type
+/// is stored in its own column, the components of one entity share the same row in all columns.
 pub struct Archetype {
     pub(crate) id: ArchetypeId,
     pub(crate) component_types: Vec<Box<dyn ComponentVec>>,
     pub(crate) types: Vec<TypeId>,
+    /// The entity of each row.
+    pub(crate) entities: Vec<EntityId>,
 }
 
 impl Archetype {
@@ -43,6 +50,7 @@ impl Archetype {
             id,
             component_types,
             types,
+            entities: Vec::new(),
         }
     }
 
@@ -71,11 +79,43 @@ impl Archetype {
             id,
             component_types,
             types,
+            entities: Vec::new(),
         }
     }
 
-    #[cfg(test)]
-    pub(crate) fn get_components<ComponentType: 'static>(&self) -> Option<&[ComponentType]> {
+    #[must_use]
+    pub const fn id(&self) -> ArchetypeId {
+        self.id
+    }
+
+    /// The number of entities in the archetype.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.component_types
+            .first()
+            .map_or(0, |column| column.len())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The entities in the archetype, in row order.
+    #[must_use]
+    pub fn entities(&self) -> &[EntityId] {
+        &self.entities
+    }
+
+    #[must_use]
+    pub fn has_column<ComponentType: 'static>(&self) -> bool {
+        self.types.contains(&TypeId::of::<ComponentType>())
+    }
+
+    /// All components of the given type in this archetype, in row order. Returns None if the
+    /// archetype does not store the component type.
+    #[must_use]
+    pub fn column<ComponentType: 'static>(&self) -> Option<&[ComponentType]> {
         self.component_types.iter().find_map(|column| {
             column
                 .as_any()
@@ -84,7 +124,18 @@ impl Archetype {
         })
     }
 
-    fn get_components_mut<ComponentType: 'static>(&mut self) -> Option<&mut Vec<ComponentType>> {
+    /// Mutable access to all components of the given type in this archetype, see
+    /// [`Archetype::column`].
+    ///
+    /// The rows of all columns belong together. Reordering the components of a single column
+    /// (e.g. by sorting it) therefore mixes up the components of different entities.
+    pub fn column_mut<ComponentType: 'static>(&mut self) -> Option<&mut [ComponentType]> {
+        self.get_components_mut().map(Vec::as_mut_slice)
+    }
+
+    pub(crate) fn get_components_mut<ComponentType: 'static>(
+        &mut self,
+    ) -> Option<&mut Vec<ComponentType>> {
         self.component_types
             .iter_mut()
             .find_map(|column| column.as_any_mut().downcast_mut::<Vec<ComponentType>>())
@@ -97,6 +148,28 @@ impl Archetype {
 
         column.push(component);
     }
+
+    /// Append a copy of a row to the archetype, using the clone function of each column. Nothing
+    /// is changed and the name of the first column without a clone function is returned if not
+    /// all columns can be cloned.
+    pub(crate) fn clone_row(
+        &mut self,
+        row: EntityRow,
+        clone_fns: &HashMap<TypeId, CloneFn>,
+    ) -> Result<(), &'static str> {
+        if let Some(column) = self
+            .component_types
+            .iter()
+            .find(|column| !clone_fns.contains_key(&column.element_type_id()))
+        {
+            return Err(column.element_type_name());
+        }
+
+        for column in &mut self.component_types {
+            clone_fns[&column.element_type_id()](column.as_mut(), row);
+        }
+        Ok(())
+    }
 }
 
 /// Aligns two archetypes and migrates the components of the source archetype to the target
@@ -139,6 +212,7 @@ mod tests {
             id: 0,
             component_types: vec![Box::<Vec<i32>>::default()],
             types: vec![TypeId::of::<i32>()],
+            entities: Vec::new(),
         };
 
         let new_archetype = Archetype::new_from_add::<f32>(&archetype, 1);
@@ -167,6 +241,7 @@ mod tests {
             id: 0,
             component_types: vec![Box::<Vec<i32>>::default(), Box::<Vec<f32>>::default()],
             types: vec![TypeId::of::<i32>(), TypeId::of::<f32>()],
+            entities: Vec::new(),
         };
 
         let new_archetype = Archetype::new_from_remove::<f32>(&archetype, 1);
@@ -188,6 +263,7 @@ mod tests {
             id: 0,
             component_types: vec![Box::new(vec![1, 2, 3])],
             types: vec![TypeId::of::<i32>()],
+            entities: Vec::new(),
         };
 
         let mut target = Archetype {
@@ -197,6 +273,7 @@ mod tests {
                 Box::new(vec![1, 2, 3]),
             ],
             types: vec![TypeId::of::<f32>(), TypeId::of::<i32>()],
+            entities: Vec::new(),
         };
 
         target.types.sort();
@@ -237,12 +314,17 @@ mod tests {
                 Box::new(vec![1, 2, 3]),
             ],
             types: vec![TypeId::of::<f32>(), TypeId::of::<i32>()],
+            entities: Vec::new(),
         };
 
+        source.types.sort();
+        source.component_types.sort_by_key(|a| a.element_type_id());
+
         let mut target = Archetype {
             id: 1,
             component_types: vec![Box::new(Vec::<i32>::new())],
             types: vec![TypeId::of::<i32>()],
+            entities: Vec::new(),
         };
 
         align_and_migrate_archetypes(&mut source, &mut target, 1);
@@ -272,4 +354,24 @@ mod tests {
         assert_eq!(source_f32_components, &vec![1.0_f32, 2.0_f32, 3.0_f32]);
         assert_eq!(target_i32_components, &vec![2]);
     }
+
+    #[test]
+    fn column_returns_typed_slices() {
+        let mut archetype = Archetype {
+            id: 3,
+            component_types: vec![Box::new(vec![3, 1, 2]), Box::new(vec![0.5f32; 3])],
+            types: vec![TypeId::of::<i32>(), TypeId::of::<f32>()],
+            entities: Vec::new(),
+        };
+
+        assert_eq!(archetype.id(), 3);
+        assert_eq!(archetype.len(), 3);
+        assert!(archetype.has_column::<f32>());
+        assert!(!archetype.has_column::<u8>());
+        assert_eq!(archetype.column::<i32>(), Some([3, 1, 2].as_slice()));
+        assert_eq!(archetype.column::<u8>(), None);
+
+        archetype.column_mut::<f32>().unwrap()[1] = 2.0;
+        assert_eq!(archetype.column::<f32>(), Some([0.5, 2.0, 0.5].as_slice()));
+    }
 }
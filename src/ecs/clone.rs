@@ -0,0 +1,228 @@
+use crate::ecs::storage::{ComponentVec, EntityRow};
+use crate::ecs::{Disabled, EntityId, EntitySpawned, PersistentId, Storage, World};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// Appends a copy of a row to a component column.
+pub(crate) type CloneFn = fn(&mut dyn ComponentVec, EntityRow);
+
+fn clone_row<ComponentType: Clone + 'static>(column: &mut dyn ComponentVec, row: EntityRow) {
+    let column = column
+        .as_any_mut()
+        .downcast_mut::<Vec<ComponentType>>()
+        .expect("Internal storage error. Clone function registered for the wrong type.");
+
+    column.push(column[row].clone());
+}
+
+/// A copy of an entity gets its own id, the same persistent id must not be used twice.
+fn clone_persistent_id(column: &mut dyn ComponentVec, _row: EntityRow) {
+    column
+        .as_any_mut()
+        .downcast_mut::<Vec<PersistentId>>()
+        .expect("Internal storage error. Clone function registered for the wrong type.")
+        .push(PersistentId::new());
+}
+
+/// The clone functions of the component types of the engine.
+pub(crate) fn default_clone_fns() -> HashMap<TypeId, CloneFn> {
+    HashMap::from([
+        (TypeId::of::<PersistentId>(), clone_persistent_id as CloneFn),
+        (TypeId::of::<Disabled>(), clone_row::<Disabled>),
+    ])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloneEntityError {
+    NoSuchEntity,
+    /// The entity has a component type that was not registered with
+    /// [`Storage::register_cloneable`].
+    NotCloneable(&'static str),
+}
+
+impl Display for CloneEntityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSuchEntity => write!(f, "the entity does not exist"),
+            Self::NotCloneable(name) => write!(f, "component {name} is not cloneable"),
+        }
+    }
+}
+
+impl std::error::Error for CloneEntityError {}
+
+impl Storage {
+    /// Allow entities with components of this type to be cloned with [`World::clone_entity`].
+    /// Components are type-erased in the storage, so the clone function has to be registered once
+    /// per type.
+    pub fn register_cloneable<ComponentType: Clone + 'static>(&mut self) {
+        self.clone_fns
+            .insert(TypeId::of::<ComponentType>(), clone_row::<ComponentType>);
+    }
+
+    /// Copy the components and tags of an entity to a new entity in the same archetype.
+    fn clone_entity_to(
+        &mut self,
+        entity: EntityId,
+        clone: EntityId,
+    ) -> Result<(), CloneEntityError> {
+        let record = self
+            .entity_index
+            .get(&entity)
+            .ok_or(CloneEntityError::NoSuchEntity)?;
+        let archetype_id = record.archetype_id;
+        let archetype = self
+            .archetypes
+            .get_mut(&archetype_id)
+            .expect("Internal storage error. Entity index points to invalid archetype id.");
+
+        archetype
+            .clone_row(record.entity_row, &self.clone_fns)
+            .map_err(CloneEntityError::NotCloneable)?;
+        self.push_entity_record(clone, archetype_id);
+
+        for entities in self.tags.values_mut() {
+            if entities.contains(&entity) {
+                entities.insert(clone);
+            }
+        }
+        if let Some(&id) = self.get_entity_component::<PersistentId>(clone) {
+            self.persistent_ids.insert(id, clone);
+        }
+
+        #[cfg(feature = "debug-validate")]
+        self.validate();
+
+        Ok(())
+    }
+}
+
+impl World {
+    /// Create a new entity with copies of all components and tags of an existing entity, e.g. to
+    /// spawn many copies of a configured template. The copy is added to the same archetype without
+    /// building it component by component. An [`EntitySpawned`] event is sent. A
+    /// [`PersistentId`] is not copied, the copy gets a new one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entity does not exist or has a component type that was not
+    /// registered with [`Storage::register_cloneable`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// #[derive(Clone)]
+    /// struct Health(u32);
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// world.storage.register_cloneable::<Health>();
+    /// let template = world.build_entity().with_component(Health(50)).build();
+    ///
+    /// let copy = world.clone_entity(template).expect("Failed to clone the template");
+    /// assert_ne!(copy, template);
+    /// assert!(world.storage.has::<Health>(copy));
+    /// ```
+    pub fn clone_entity(&mut self, entity: EntityId) -> Result<EntityId, CloneEntityError> {
+        if !self.contains(entity) {
+            return Err(CloneEntityError::NoSuchEntity);
+        }
+
+        // the id is only taken once the clone succeeded, so that failed clones don't use up ids
+        let clone = self.entities_count;
+        self.storage.clone_entity_to(entity, clone)?;
+        self.entities_count += 1;
+        self.storage.send_event(EntitySpawned(clone));
+
+        Ok(clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Query;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Name(String);
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(u32);
+    struct Elite;
+    struct Unique;
+
+    #[test]
+    fn clones_are_independent_copies() {
+        let mut world = World::init().unwrap();
+        world.storage.register_cloneable::<Name>();
+        world.storage.register_cloneable::<Health>();
+        let id = PersistentId::new();
+        let template = world
+            .build_entity()
+            .with_component(Name("orc".to_owned()))
+            .with_component(Health(30))
+            .with_component(id)
+            .build();
+        world.storage.add_tag::<Elite>(template);
+        let archetypes = world.storage.archetypes.len();
+
+        let clone = world.clone_entity(template).unwrap();
+        world
+            .storage
+            .get_entity_component_mut::<Health>(clone)
+            .unwrap()
+            .0 = 10;
+
+        assert_eq!(world.storage.archetypes.len(), archetypes);
+        assert_eq!(
+            world.storage.get_entity_component::<Name>(clone),
+            Some(&Name("orc".to_owned()))
+        );
+        assert_eq!(
+            world.storage.get_entity_component::<Health>(template),
+            Some(&Health(30))
+        );
+        assert!(world.storage.has_tag::<Elite>(clone));
+
+        let clone_id = *world
+            .storage
+            .get_entity_component::<PersistentId>(clone)
+            .unwrap();
+        assert_ne!(clone_id, id);
+        assert_eq!(world.storage.entity_by_persistent_id(clone_id), Some(clone));
+        assert_eq!(world.storage.entity_by_persistent_id(id), Some(template));
+
+        assert!(world.despawn(template));
+        assert_eq!(
+            world.storage.get_entity_component::<Health>(clone),
+            Some(&Health(10))
+        );
+    }
+
+    #[test]
+    fn entities_with_unregistered_components_are_not_cloned() {
+        let mut world = World::init().unwrap();
+        world.storage.register_cloneable::<Health>();
+        let entity = world
+            .build_entity()
+            .with_component(Health(1))
+            .with_component(Unique)
+            .build();
+
+        assert!(matches!(
+            world.clone_entity(entity),
+            Err(CloneEntityError::NotCloneable(name)) if name.ends_with("Unique")
+        ));
+        assert_eq!(world.storage.entity_count(), 1);
+        assert_eq!(world.storage.query::<(&Health,)>().count(), 1);
+        assert_eq!(
+            world.build_entity().with_component(Health(2)).build(),
+            entity + 1
+        );
+        assert_eq!(
+            world.clone_entity(entity + 100),
+            Err(CloneEntityError::NoSuchEntity)
+        );
+    }
+}
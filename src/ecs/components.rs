@@ -0,0 +1,274 @@
+use crate::ecs::{EntityId, EntitySpawned, Storage, World};
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+
+/// A single type-erased component that remembers how to add itself to an entity again.
+pub(crate) struct BoxedComponent {
+    value: Box<dyn Any>,
+    type_id: TypeId,
+    insert: fn(Box<dyn Any>, &mut Storage, EntityId),
+}
+
+impl BoxedComponent {
+    pub(crate) fn new<ComponentType: 'static>(component: ComponentType) -> Self {
+        Self {
+            value: Box::new(component),
+            type_id: TypeId::of::<ComponentType>(),
+            insert: insert_boxed::<ComponentType>,
+        }
+    }
+}
+
+fn insert_boxed<ComponentType: 'static>(
+    component: Box<dyn Any>,
+    storage: &mut Storage,
+    entity: EntityId,
+) {
+    let component = component
+        .downcast::<ComponentType>()
+        .expect("Internal storage error. Boxed component has an unexpected type.");
+
+    storage.add_component_to_entity(entity, *component);
+}
+
+/// The components and tags of an entity that was [taken](World::take_entity) out of a world. The
+/// bundle owns the components, so they can be inspected, changed and
+/// [spawned](World::spawn_components) into the same or another world.
+#[derive(Default)]
+pub struct EntityComponents {
+    components: HashMap<TypeId, BoxedComponent>,
+    pub(crate) tags: HashSet<TypeId>,
+}
+
+impl EntityComponents {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a component to the bundle. Returns the previous component of the same type.
+    pub fn insert<ComponentType: 'static>(
+        &mut self,
+        component: ComponentType,
+    ) -> Option<ComponentType> {
+        self.components
+            .insert(
+                TypeId::of::<ComponentType>(),
+                BoxedComponent::new(component),
+            )
+            .map(|previous| downcast_component(previous))
+    }
+
+    #[must_use]
+    pub fn get<ComponentType: 'static>(&self) -> Option<&ComponentType> {
+        self.components
+            .get(&TypeId::of::<ComponentType>())
+            .and_then(|component| component.value.downcast_ref())
+    }
+
+    pub fn get_mut<ComponentType: 'static>(&mut self) -> Option<&mut ComponentType> {
+        self.components
+            .get_mut(&TypeId::of::<ComponentType>())
+            .and_then(|component| component.value.downcast_mut())
+    }
+
+    /// Remove a component from the bundle and return it.
+    pub fn take<ComponentType: 'static>(&mut self) -> Option<ComponentType> {
+        self.components
+            .remove(&TypeId::of::<ComponentType>())
+            .map(|component| downcast_component(component))
+    }
+
+    #[must_use]
+    pub fn contains<ComponentType: 'static>(&self) -> bool {
+        self.components.contains_key(&TypeId::of::<ComponentType>())
+    }
+
+    /// Add a [tag](Storage::add_tag) to the bundle.
+    pub fn add_tag<TagType: 'static>(&mut self) {
+        self.tags.insert(TypeId::of::<TagType>());
+    }
+
+    /// Remove a tag from the bundle. Returns true if the bundle had the tag.
+    pub fn remove_tag<TagType: 'static>(&mut self) -> bool {
+        self.tags.remove(&TypeId::of::<TagType>())
+    }
+
+    #[must_use]
+    pub fn has_tag<TagType: 'static>(&self) -> bool {
+        self.tags.contains(&TypeId::of::<TagType>())
+    }
+
+    /// The number of components in the bundle, tags are not counted.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.components.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Add all components and tags of the bundle to an entity.
+    pub(crate) fn insert_into(self, storage: &mut Storage, entity: EntityId) {
+        for component in self.components.into_values() {
+            (component.insert)(component.value, storage, entity);
+        }
+        storage.add_tag_types(entity, self.tags);
+    }
+}
+
+impl FromIterator<BoxedComponent> for EntityComponents {
+    fn from_iter<I: IntoIterator<Item = BoxedComponent>>(iter: I) -> Self {
+        Self {
+            components: iter
+                .into_iter()
+                .map(|component| (component.type_id, component))
+                .collect(),
+            tags: HashSet::new(),
+        }
+    }
+}
+
+fn downcast_component<ComponentType: 'static>(component: BoxedComponent) -> ComponentType {
+    *component
+        .value
+        .downcast::<ComponentType>()
+        .expect("Internal storage error. Boxed component has an unexpected type.")
+}
+
+impl World {
+    /// Remove an entity and return its components and tags, e.g. to move an item into an inventory
+    /// or into another world. An [`EntityDespawned`](crate::ecs::EntityDespawned) event is sent.
+    /// Returns None if the entity does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Sword { damage: u32 }
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let mut other = World::init().expect("Failed to initialize world");
+    /// let sword = world.build_entity().with_component(Sword { damage: 7 }).build();
+    ///
+    /// let components = world.take_entity(sword).unwrap();
+    /// assert_eq!(components.get::<Sword>(), Some(&Sword { damage: 7 }));
+    ///
+    /// assert!(other.spawn_components(components).is_some());
+    /// ```
+    pub fn take_entity(&mut self, entity: EntityId) -> Option<EntityComponents> {
+        self.storage.take_entity(entity)
+    }
+
+    /// Create a new entity from a bundle of components and tags and send an [`EntitySpawned`]
+    /// event. Returns None without creating an entity if the bundle has no components, since
+    /// entities without components don't exist.
+    pub fn spawn_components(&mut self, components: EntityComponents) -> Option<EntityId> {
+        if components.is_empty() {
+            return None;
+        }
+
+        let entity = self.new_entity();
+        components.insert_into(&mut self.storage, entity);
+        self.storage.send_event(EntitySpawned(entity));
+
+        Some(entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{EntityDespawned, Query};
+
+    #[derive(Debug, PartialEq)]
+    struct Item(&'static str);
+    #[derive(Debug, PartialEq)]
+    struct Count(u32);
+    struct Cursed;
+    struct Equipped;
+
+    #[test]
+    fn take_entity_returns_components_and_keeps_other_entities() {
+        let mut world = World::init().unwrap();
+        let potion = world
+            .build_entity()
+            .with_component(Item("potion"))
+            .with_component(Count(3))
+            .build();
+        let _ = world
+            .build_entity()
+            .with_component(Item("arrow"))
+            .with_component(Count(20))
+            .build();
+
+        let mut components = world.take_entity(potion).unwrap();
+        assert!(world.take_entity(potion).is_none());
+        assert_eq!(components.len(), 2);
+        assert_eq!(components.take::<Count>(), Some(Count(3)));
+        assert_eq!(components.get::<Item>(), Some(&Item("potion")));
+        assert_eq!(
+            world.storage.query_two::<Item, Count>().collect::<Vec<_>>(),
+            [(&Item("arrow"), &Count(20))]
+        );
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<EntityDespawned>()
+                .collect::<Vec<_>>(),
+            [EntityDespawned(potion)]
+        );
+    }
+
+    #[test]
+    fn spawn_components_moves_entities_between_worlds() {
+        let mut world = World::init().unwrap();
+        let mut other = World::init().unwrap();
+        let entity = world
+            .build_entity()
+            .with_component(Item("shield"))
+            .with_component(Count(1))
+            .build();
+
+        let mut components = world.take_entity(entity).unwrap();
+        components.get_mut::<Count>().unwrap().0 = 2;
+        let entity = other.spawn_components(components).unwrap();
+
+        assert_eq!(world.storage.query_one::<Item>().count(), 0);
+        assert_eq!(
+            other.storage.query_two::<Item, Count>().collect::<Vec<_>>(),
+            [(&Item("shield"), &Count(2))]
+        );
+        assert_eq!(
+            other.storage.read_events::<EntitySpawned>().last(),
+            Some(&EntitySpawned(entity))
+        );
+        assert_eq!(other.spawn_components(EntityComponents::new()), None);
+    }
+
+    #[test]
+    fn tags_are_moved_with_the_components() {
+        let mut world = World::init().unwrap();
+        let mut other = World::init().unwrap();
+        let entity = world.build_entity().with_component(Item("ring")).build();
+        world.storage.add_tag::<Cursed>(entity);
+        world.storage.add_tag::<Equipped>(entity);
+
+        let mut components = world.take_entity(entity).unwrap();
+        assert!(world.storage.tags.is_empty());
+        assert!(components.has_tag::<Cursed>());
+        assert!(components.remove_tag::<Equipped>());
+        let entity = other.spawn_components(components).unwrap();
+
+        assert!(other.storage.has_tag::<Cursed>(entity));
+        assert!(!other.storage.has_tag::<Equipped>(entity));
+
+        let mut tags_only = EntityComponents::new();
+        tags_only.add_tag::<Cursed>();
+        assert_eq!(other.spawn_components(tags_only), None);
+    }
+}
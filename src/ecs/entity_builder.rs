@@ -1,4 +1,4 @@
-use crate::ecs::{EntityId, World};
+use crate::ecs::{EntityId, EntitySpawned, World};
 use std::marker::PhantomData;
 
 #[derive(Default, Clone)]
@@ -65,14 +65,17 @@ impl EntityBuilder<'_, HasComponents> {
         self
     }
 
+    /// Finish the entity and send an [`EntitySpawned`] event for it.
     #[must_use]
-    pub const fn build(self) -> EntityId {
+    pub fn build(self) -> EntityId {
+        self.world.storage.send_event(EntitySpawned(self.entity_id));
+
         self.entity_id
     }
 }
 
 impl World {
-    pub fn build_entity(&mut self) -> EntityBuilder<NoComponents> {
+    pub fn build_entity(&mut self) -> EntityBuilder<'_, NoComponents> {
         EntityBuilder::new(self)
     }
 }
@@ -0,0 +1,324 @@
+use crate::ecs::{EntityId, Storage};
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// A queue of events of a single type. Events are used to communicate between systems (and
+/// between the engine and systems) without coupling them directly: a sender pushes events into
+/// the queue, a receiver consumes them later on.
+///
+/// The queue is stored as a resource, see [`Storage::send_event`] and [`Storage::drain_events`].
+/// The queue is double buffered: [`World::tick`](crate::ecs::World::tick) calls
+/// [`Storage::update_events`] at the start of every frame, which drops the events of the frame
+/// before the last one. Every system therefore sees each event once, no matter if it runs before
+/// or after the sender, and events nobody reads don't pile up. Several systems can read the same
+/// events with their own [`EventReader`].
+pub struct Events<EventType> {
+    previous: Vec<EventType>,
+    current: Vec<EventType>,
+    /// The number of events that were sent before the first event in `previous`.
+    start: usize,
+}
+
+impl<EventType> Events<EventType> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            start: 0,
+        }
+    }
+
+    pub fn send(&mut self, event: EventType) {
+        self.current.push(event);
+    }
+
+    /// Iterate over all pending events in the order they were sent, without consuming them.
+    pub fn iter(&self) -> impl Iterator<Item = &EventType> {
+        self.previous.iter().chain(self.current.iter())
+    }
+
+    /// Remove all pending events and return them in the order they were sent.
+    pub fn drain(&mut self) -> impl Iterator<Item = EventType> + '_ {
+        self.start += self.len();
+        self.previous.drain(..).chain(self.current.drain(..))
+    }
+
+    pub fn clear(&mut self) {
+        self.start += self.len();
+        self.previous.clear();
+        self.current.clear();
+    }
+
+    /// Drop the events sent before the last update and start a new buffer for the events that
+    /// are sent from now on.
+    pub fn update(&mut self) {
+        self.start += self.previous.len();
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The total number of events that were ever sent, used as cursor by [`EventReader`].
+    const fn event_count(&self) -> usize {
+        self.start + self.previous.len() + self.current.len()
+    }
+}
+
+impl<EventType> Default for Events<EventType> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A cursor into the [`Events`] of a type, so that several systems can read the same events
+/// independently. Each call to [`EventReader::read`] returns only the events that were sent since
+/// the previous call. A reader is usually a field of the system that uses it.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::ecs::{EntitySpawned, EventReader, World};
+///
+/// let mut world = World::init().expect("Failed to initialize world");
+/// let mut minimap = EventReader::<EntitySpawned>::new();
+/// let mut analytics = EventReader::<EntitySpawned>::new();
+///
+/// let entity = world.build_entity().with_component(1u32).build();
+///
+/// assert_eq!(minimap.read(&world.storage).collect::<Vec<_>>(), [&EntitySpawned(entity)]);
+/// assert_eq!(analytics.read(&world.storage).count(), 1);
+/// assert_eq!(minimap.read(&world.storage).count(), 0);
+/// ```
+pub struct EventReader<EventType> {
+    next: usize,
+    marker: PhantomData<fn() -> EventType>,
+}
+
+impl<EventType: 'static> EventReader<EventType> {
+    /// A reader that starts with all events that are currently pending.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            next: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// The events sent since the last call, in the order they were sent. Events that were
+    /// dropped by [`Storage::update_events`] before the reader saw them are skipped.
+    pub fn read<'a>(&mut self, storage: &'a Storage) -> impl Iterator<Item = &'a EventType> + 'a {
+        let events = storage.get_resource::<Events<EventType>>();
+        let skip = events.map_or(0, |events| self.next.saturating_sub(events.start));
+        self.next = events.map_or(self.next, Events::event_count);
+
+        events.into_iter().flat_map(Events::iter).skip(skip)
+    }
+}
+
+impl<EventType: 'static> Default for EventReader<EventType> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn update_events<EventType: 'static>(storage: &mut Storage) {
+    if let Some(events) = storage.get_resource_mut::<Events<EventType>>() {
+        events.update();
+    }
+}
+
+/// Sent when an entity was [built](crate::ecs::EntityBuilder::build), after all of its components
+/// were added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntitySpawned(pub EntityId);
+
+/// Sent when an entity was [removed](Storage::remove_entity) together with all of its components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EntityDespawned(pub EntityId);
+
+impl Storage {
+    /// Send an event. The [`Events`] resource for the event type is created if it does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct PlayerDied(u32);
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// world.storage.send_event(PlayerDied(1));
+    ///
+    /// let events: Vec<_> = world.storage.drain_events::<PlayerDied>().collect();
+    /// assert_eq!(events.len(), 1);
+    /// ```
+    pub fn send_event<EventType: 'static>(&mut self, event: EventType) {
+        if let Some(events) = self.get_resource_mut::<Events<EventType>>() {
+            events.send(event);
+        } else {
+            let mut events = Events::new();
+            events.send(event);
+            self.insert_resource(events);
+            self.event_updaters
+                .insert(TypeId::of::<EventType>(), update_events::<EventType>);
+        }
+    }
+
+    /// Advance the [`Events`] buffers of all event types that were sent with
+    /// [`Storage::send_event`], see [`Events::update`]. Called by
+    /// [`World::tick`](crate::ecs::World::tick) at the start of every frame.
+    pub fn update_events(&mut self) {
+        let updaters: Vec<_> = self.event_updaters.values().copied().collect();
+
+        for update in updaters {
+            update(self);
+        }
+    }
+
+    /// Iterate over all pending events of the given type without consuming them.
+    pub fn read_events<EventType: 'static>(&self) -> impl Iterator<Item = &EventType> {
+        self.get_resource::<Events<EventType>>()
+            .into_iter()
+            .flat_map(Events::iter)
+    }
+
+    /// Remove all pending events of the given type and return them in the order they were sent.
+    pub fn drain_events<EventType: 'static>(&mut self) -> impl Iterator<Item = EventType> + '_ {
+        self.get_resource_mut::<Events<EventType>>()
+            .into_iter()
+            .flat_map(Events::drain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_event_creates_events_resource() {
+        let mut storage = Storage::new();
+
+        storage.send_event(5);
+
+        assert!(storage.has_resource::<Events<i32>>());
+        assert_eq!(storage.get_resource::<Events<i32>>().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn read_events_does_not_consume_events() {
+        let mut storage = Storage::new();
+        storage.send_event(1);
+        storage.send_event(2);
+
+        assert_eq!(
+            storage.read_events::<i32>().collect::<Vec<_>>(),
+            vec![&1, &2]
+        );
+        assert_eq!(storage.read_events::<i32>().count(), 2);
+    }
+
+    #[test]
+    fn drain_events_consumes_events_in_order() {
+        let mut storage = Storage::new();
+        storage.send_event(1);
+        storage.send_event(2);
+        storage.send_event(3);
+
+        assert_eq!(
+            storage.drain_events::<i32>().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(storage.drain_events::<i32>().count(), 0);
+    }
+
+    #[test]
+    fn events_without_sender_are_empty() {
+        let mut storage = Storage::new();
+
+        assert_eq!(storage.read_events::<i32>().count(), 0);
+        assert_eq!(storage.drain_events::<i32>().count(), 0);
+    }
+
+    #[test]
+    fn events_are_dropped_after_two_ticks() {
+        let mut world = crate::ecs::World::init().unwrap();
+        let entity = world.build_entity().with_component(1u8).build();
+
+        world.tick(std::time::Duration::ZERO);
+        assert_eq!(
+            world
+                .storage
+                .read_events::<EntitySpawned>()
+                .collect::<Vec<_>>(),
+            [&EntitySpawned(entity)]
+        );
+
+        world.tick(std::time::Duration::ZERO);
+        assert_eq!(world.storage.read_events::<EntitySpawned>().count(), 0);
+        assert!(world
+            .storage
+            .get_resource::<Events<EntitySpawned>>()
+            .unwrap()
+            .previous
+            .is_empty());
+    }
+
+    #[test]
+    fn readers_see_each_event_once() {
+        let mut storage = Storage::new();
+        let mut first = EventReader::<i32>::new();
+        let mut second = EventReader::<i32>::new();
+        storage.send_event(1);
+
+        assert_eq!(first.read(&storage).collect::<Vec<_>>(), [&1]);
+        storage.update_events();
+        storage.send_event(2);
+
+        assert_eq!(first.read(&storage).collect::<Vec<_>>(), [&2]);
+        assert_eq!(second.read(&storage).collect::<Vec<_>>(), [&1, &2]);
+
+        // the reader missed the event that was dropped in between
+        storage.update_events();
+        storage.update_events();
+        storage.send_event(3);
+        assert_eq!(first.read(&storage).collect::<Vec<_>>(), [&3]);
+        assert_eq!(storage.drain_events::<i32>().collect::<Vec<_>>(), [3]);
+        storage.send_event(4);
+        assert_eq!(second.read(&storage).collect::<Vec<_>>(), [&4]);
+    }
+
+    #[test]
+    fn building_and_removing_entities_sends_events() {
+        let mut world = crate::ecs::World::init().unwrap();
+        let first = world.build_entity().with_component(1u8).build();
+        let second = world.build_entity().with_component(2u8).build();
+
+        world.storage.remove_entity(first);
+        world.storage.remove_entity(first);
+
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<EntitySpawned>()
+                .collect::<Vec<_>>(),
+            [EntitySpawned(first), EntitySpawned(second)]
+        );
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<EntityDespawned>()
+                .collect::<Vec<_>>(),
+            [EntityDespawned(first)]
+        );
+    }
+}
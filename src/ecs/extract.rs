@@ -0,0 +1,148 @@
+use crate::ecs::{Storage, World};
+
+/// A system that copies the data the renderer needs (transforms, sprite handles, camera state)
+/// from the simulation into the render storage of the [`World`]. Extract systems only get shared
+/// access to the simulation, so the render path never borrows the simulation storage directly.
+pub trait ExtractSystem {
+    fn new() -> Self
+    where
+        Self: Sized;
+
+    fn extract(&mut self, main: &Storage, render: &mut Storage);
+}
+
+impl World {
+    /// Add a system to the extract stage. Extract systems run in the order they were added at the
+    /// end of each [tick](World::tick), after all regular systems. Events sent to the render
+    /// storage are kept until the extract stage of the next tick has run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::{ExtractSystem, Query, Storage, World};
+    /// use game_engine::math::Transform2D;
+    /// use std::time::Duration;
+    ///
+    /// /// The transforms the renderer draws sprites at.
+    /// struct ExtractedTransforms(Vec<Transform2D>);
+    ///
+    /// struct ExtractTransforms;
+    ///
+    /// impl ExtractSystem for ExtractTransforms {
+    ///     fn new() -> Self {
+    ///         Self
+    ///     }
+    ///
+    ///     fn extract(&mut self, main: &Storage, render: &mut Storage) {
+    ///         let transforms = main.query_one::<Transform2D>().copied().collect();
+    ///         render.insert_resource(ExtractedTransforms(transforms));
+    ///     }
+    /// }
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// world.add_extract_system(ExtractTransforms::new());
+    /// let _ = world
+    ///     .build_entity()
+    ///     .with_component(Transform2D::IDENTITY)
+    ///     .build();
+    ///
+    /// world.tick(Duration::from_millis(16));
+    ///
+    /// let extracted = world.render_storage.get_resource::<ExtractedTransforms>();
+    /// assert_eq!(extracted.unwrap().0.len(), 1);
+    /// ```
+    pub fn add_extract_system<S: ExtractSystem + 'static>(&mut self, system: S) {
+        self.extract_systems.push(Box::new(system));
+    }
+
+    /// Run the extract stage, see [`World::add_extract_system`].
+    pub(crate) fn extract(&mut self) {
+        for system in &mut self.extract_systems {
+            system.extract(&self.storage, &mut self.render_storage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{EntityDespawned, EntityId, Query};
+    use std::time::Duration;
+
+    struct Position(f32);
+    struct ExtractedPositions(Vec<f32>);
+
+    struct ExtractPositions;
+
+    impl ExtractSystem for ExtractPositions {
+        fn new() -> Self {
+            Self
+        }
+
+        fn extract(&mut self, main: &Storage, render: &mut Storage) {
+            let positions = main.query_one::<Position>().map(|p| p.0).collect();
+            render.insert_resource(ExtractedPositions(positions));
+        }
+    }
+
+    #[test]
+    fn extract_stage_runs_after_systems() {
+        let mut world = World::init().unwrap();
+        world.add_extract_system(ExtractPositions::new());
+        let _ = world.build_entity().with_component(Position(1.0)).build();
+
+        world.tick(Duration::from_millis(16));
+        let _ = world.build_entity().with_component(Position(2.0)).build();
+        assert_eq!(
+            world
+                .render_storage
+                .get_resource::<ExtractedPositions>()
+                .unwrap()
+                .0,
+            [1.0]
+        );
+        assert!(!world.storage.has_resource::<ExtractedPositions>());
+
+        world.tick(Duration::from_millis(16));
+        let mut positions = world
+            .render_storage
+            .get_resource::<ExtractedPositions>()
+            .unwrap()
+            .0
+            .clone();
+        positions.sort_by(f32::total_cmp);
+        assert_eq!(positions, [1.0, 2.0]);
+    }
+
+    /// Replaces the render entity of the previous frame with a new one.
+    struct RespawnRenderEntity(EntityId);
+
+    impl ExtractSystem for RespawnRenderEntity {
+        fn new() -> Self {
+            Self(0)
+        }
+
+        fn extract(&mut self, _main: &Storage, render: &mut Storage) {
+            render.remove_entity(self.0);
+            self.0 += 1;
+            render.add_component_to_entity(self.0, Position(0.0));
+        }
+    }
+
+    #[test]
+    fn render_events_are_advanced_every_tick() {
+        let mut world = World::init().unwrap();
+        world.add_extract_system(RespawnRenderEntity::new());
+
+        for _ in 0..10 {
+            world.tick(Duration::from_millis(16));
+        }
+
+        let despawned: Vec<_> = world
+            .render_storage
+            .read_events::<EntityDespawned>()
+            .map(|event| event.0)
+            .collect();
+        assert_eq!(despawned, [8, 9]);
+    }
+}
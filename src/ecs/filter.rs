@@ -0,0 +1,179 @@
+use crate::ecs::{Archetype, EntityId, Storage};
+use std::marker::PhantomData;
+
+/// A condition on the component types of an entity, used by [`Query::query_filtered`]. Filters
+/// only restrict which entities are matched, they don't give access to any component.
+///
+/// Tuples of filters match if all of their filters match, `()` matches every entity.
+///
+/// [`Query::query_filtered`]: crate::ecs::Query::query_filtered
+pub trait QueryFilter {
+    /// Filters on data that is not stored in the archetypes, like tags, have to check every entity.
+    #[doc(hidden)]
+    const PER_ENTITY: bool = false;
+
+    /// Returns true if the entities in the archetype match the filter. Filters with
+    /// [`QueryFilter::PER_ENTITY`] return true if some entities of the archetype may match.
+    fn matches(archetype: &Archetype) -> bool;
+
+    /// Returns true if the entity in the archetype matches the filter. Only called for filters
+    /// with [`QueryFilter::PER_ENTITY`].
+    #[doc(hidden)]
+    fn matches_entity(_storage: &Storage, archetype: &Archetype, _entity: EntityId) -> bool {
+        Self::matches(archetype)
+    }
+}
+
+/// Matches entities that have a component of type `T`.
+pub struct With<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for With<T> {
+    fn matches(archetype: &Archetype) -> bool {
+        archetype.has_column::<T>()
+    }
+}
+
+/// Matches entities that don't have a component of type `T`.
+pub struct Without<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for Without<T> {
+    fn matches(archetype: &Archetype) -> bool {
+        !archetype.has_column::<T>()
+    }
+}
+
+/// Matches entities that have the [tag](Storage::add_tag) `T`. Tags are not stored in the
+/// archetypes, so this checks every entity of the queried archetypes.
+pub struct WithTag<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for WithTag<T> {
+    const PER_ENTITY: bool = true;
+
+    fn matches(_archetype: &Archetype) -> bool {
+        true
+    }
+
+    fn matches_entity(storage: &Storage, _archetype: &Archetype, entity: EntityId) -> bool {
+        storage.has_tag::<T>(entity)
+    }
+}
+
+/// Matches entities that don't have the [tag](Storage::add_tag) `T`.
+pub struct WithoutTag<T>(PhantomData<T>);
+
+impl<T: 'static> QueryFilter for WithoutTag<T> {
+    const PER_ENTITY: bool = true;
+
+    fn matches(_archetype: &Archetype) -> bool {
+        true
+    }
+
+    fn matches_entity(storage: &Storage, _archetype: &Archetype, entity: EntityId) -> bool {
+        !storage.has_tag::<T>(entity)
+    }
+}
+
+/// Matches entities that match at least one of the filters in the tuple `T`, e.g.
+/// `Or<(With<Sprite>, With<Text>)>`.
+pub struct Or<T>(PhantomData<T>);
+
+impl QueryFilter for () {
+    fn matches(_archetype: &Archetype) -> bool {
+        true
+    }
+}
+
+macro_rules! impl_query_filter {
+    ($($filter:ident),*) => {
+        impl<$($filter: QueryFilter),*> QueryFilter for ($($filter,)*) {
+            const PER_ENTITY: bool = $($filter::PER_ENTITY)||*;
+
+            fn matches(archetype: &Archetype) -> bool {
+                $($filter::matches(archetype))&&*
+            }
+
+            fn matches_entity(storage: &Storage, archetype: &Archetype, entity: EntityId) -> bool {
+                $($filter::matches_entity(storage, archetype, entity))&&*
+            }
+        }
+
+        impl<$($filter: QueryFilter),*> QueryFilter for Or<($($filter,)*)> {
+            const PER_ENTITY: bool = $($filter::PER_ENTITY)||*;
+
+            fn matches(archetype: &Archetype) -> bool {
+                $($filter::matches(archetype))||*
+            }
+
+            fn matches_entity(storage: &Storage, archetype: &Archetype, entity: EntityId) -> bool {
+                $($filter::matches_entity(storage, archetype, entity))||*
+            }
+        }
+    };
+}
+
+impl_query_filter!(Filter1);
+impl_query_filter!(Filter1, Filter2);
+impl_query_filter!(Filter1, Filter2, Filter3);
+impl_query_filter!(Filter1, Filter2, Filter3, Filter4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Query, Storage};
+
+    struct Sprite;
+    struct Text;
+    struct Hidden;
+
+    #[test]
+    fn or_filter_matches_any_alternative() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 0u32);
+        storage.add_component_to_entity(0, Sprite);
+        storage.add_component_to_entity(1, 1u32);
+        storage.add_component_to_entity(1, Text);
+        storage.add_component_to_entity(2, 2u32);
+        storage.add_component_to_entity(3, 3u32);
+        storage.add_component_to_entity(3, Text);
+        storage.add_component_to_entity(3, Hidden);
+
+        let mut visible: Vec<_> = storage
+            .query_filtered::<(&u32,), (Or<(With<Sprite>, With<Text>)>, Without<Hidden>)>()
+            .map(|(id,)| *id)
+            .collect();
+        visible.sort_unstable();
+        assert_eq!(visible, [0, 1]);
+
+        let mut all: Vec<_> = storage
+            .query_filtered::<(&u32,), ()>()
+            .map(|(id,)| *id)
+            .collect();
+        all.sort_unstable();
+        assert_eq!(all, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn tag_filters_check_each_entity() {
+        let mut storage = Storage::new();
+        for id in 0..4u32 {
+            storage.add_component_to_entity(id as EntityId, id);
+        }
+        storage.add_component_to_entity(3, Sprite);
+        assert!(storage.add_tag::<Hidden>(1));
+        assert!(storage.add_tag::<Hidden>(3));
+
+        let mut hidden: Vec<_> = storage
+            .query_filtered_ref::<(&u32,), WithTag<Hidden>>()
+            .map(|(id,)| *id)
+            .collect();
+        hidden.sort_unstable();
+        assert_eq!(hidden, [1, 3]);
+
+        let mut shown: Vec<_> = storage
+            .query_filtered::<(&u32,), Or<(WithoutTag<Hidden>, With<Sprite>)>>()
+            .map(|(id,)| *id)
+            .collect();
+        shown.sort_unstable();
+        assert_eq!(shown, [0, 2, 3]);
+    }
+}
@@ -4,28 +4,60 @@
 //!
 //! We use the following terminology:
 //! - `Entity`: An entity is a unique identifier that groups components together. It is a simple
-//!  [number](EntityId).
+//!   [number](EntityId).
 //! - `Component`: A component is a piece of data that is attached to an entity. It is possible to
-//! attach an arbitrary type as a component, as long as the lifetimes of all members of the
-//! component are `'static`. This is possible since the engine uses a dynamic type system
-//! for components.
+//!   attach an arbitrary type as a component, as long as the lifetimes of all members of the
+//!   component are `'static`. This is possible since the engine uses a dynamic type system
+//!   for components.
 //! - [`System`]: A system is something that operates on entities that share a certain set of
-//! components. There are some predefined systems in the engine, but it is also possible to create
-//! custom systems. The methods in the [`Query`] trait are used to filter entities based on their
-//! components.
+//!   components. There are some predefined systems in the engine, but it is also possible to create
+//!   custom systems. The methods in the [`Query`] trait are used to filter entities based on their
+//!   components.
 //! - [`World`]: The world is the main struct that holds all the entities, components and
-//! systems. It is responsible for updating the systems and handling the general game loop. The
-//! actual housekeeping of entities, components and systems is done by the [`Storage`] struct, that
-//! will be accessible from each system.
+//!   systems. It is responsible for updating the systems and handling the general game loop. The
+//!   actual housekeeping of entities, components and systems is done by the [`Storage`] struct, that
+//!   will be accessible from each system.
+//! - `Tag`: A tag is a marker type attached to an entity without changing its archetype (see
+//!   [`Storage::add_tag`]), so that many combinations of markers don't create many archetypes.
+//! - `Resource`: A resource is a single, globally unique value of a type that is not attached to
+//!   any entity (e.g. the clipboard or the frame time). Resources live in the [`Storage`] as well,
+//!   so systems can access them alongside components.
+//! - `Event`: An event is a message that is sent by one system (or the engine) and read by other
+//!   systems. Events of a type are queued in an [`Events`] resource for two frames, and can be
+//!   read by several systems with an [`EventReader`] each. The engine sends
+//!   [`EntitySpawned`] and [`EntityDespawned`] events whenever entities are created or removed.
 mod archetype;
+mod clone;
+mod components;
 mod entity_builder;
+mod event;
+mod extract;
+mod filter;
+mod persistent_id;
+mod pool;
 mod query;
+mod resource;
+mod schedule;
 mod storage;
 mod system;
+mod tag;
+#[cfg(any(test, feature = "debug-validate"))]
+mod validate;
 mod world;
 
+pub use archetype::{Archetype, ArchetypeId};
+pub use clone::CloneEntityError;
+pub use components::EntityComponents;
 pub use entity_builder::EntityBuilder;
-pub use query::Query;
+pub use event::{EntityDespawned, EntitySpawned, EventReader, Events};
+pub use extract::ExtractSystem;
+pub use filter::{Or, QueryFilter, With, WithTag, Without, WithoutTag};
+pub use persistent_id::PersistentId;
+pub use pool::{Disabled, EntityPool};
+pub use query::{
+    Query, QueryData, QueryParam, QuerySingleError, ReadOnlyQuery, ReadOnlyQueryParam,
+};
+pub use schedule::SystemConfig;
 pub use storage::Storage;
 pub use system::System;
 pub use world::*;
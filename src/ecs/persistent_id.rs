@@ -0,0 +1,148 @@
+use crate::ecs::{EntityId, Storage};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use uuid::Uuid;
+
+/// A globally unique id of an entity that stays the same across saves, network peers and world
+/// reloads, unlike the [`EntityId`], which is only valid in one world. Entities with this
+/// component can be found with [`Storage::entity_by_persistent_id`].
+///
+/// # Example
+///
+/// ```
+/// use game_engine::ecs::{PersistentId, World};
+///
+/// struct Door { open: bool }
+///
+/// let mut world = World::init().expect("Failed to initialize world");
+/// let id = PersistentId::new();
+/// let door = world
+///     .build_entity()
+///     .with_component(Door { open: false })
+///     .with_component(id)
+///     .build();
+///
+/// assert_eq!(world.storage.entity_by_persistent_id(id), Some(door));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PersistentId(pub Uuid);
+
+impl PersistentId {
+    /// A new random id.
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for PersistentId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for PersistentId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Storage {
+    /// The entity with the given [`PersistentId`] component, or None if no entity has it.
+    #[must_use]
+    pub fn entity_by_persistent_id(&self, id: PersistentId) -> Option<EntityId> {
+        // the index is only updated when the component is added, so entries are checked against
+        // the component in case it was changed in place
+        self.persistent_ids
+            .get(&id)
+            .copied()
+            .filter(|&entity| self.get_entity_component::<PersistentId>(entity) == Some(&id))
+    }
+
+    /// Add a component to the persistent id index if it is a [`PersistentId`].
+    pub(crate) fn index_persistent_id(&mut self, entity: EntityId, component: &dyn Any) {
+        if let Some(&id) = component.downcast_ref::<PersistentId>() {
+            self.persistent_ids.insert(id, entity);
+        }
+    }
+
+    /// Remove the [`PersistentId`] of an entity from the index, e.g. when it is removed.
+    pub(crate) fn unindex_persistent_id(&mut self, entity: EntityId) {
+        if let Some(id) = self.get_entity_component::<PersistentId>(entity).copied() {
+            self.persistent_ids.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::World;
+
+    #[test]
+    fn index_follows_the_component() {
+        let mut storage = Storage::new();
+        let first = PersistentId::new();
+        let second = PersistentId::new();
+        storage.add_component_to_entity(0, 1u32);
+        storage.add_component_to_entity(0, first);
+        storage.add_component_to_entity(1, second);
+
+        assert_ne!(first, second);
+        assert_eq!(storage.entity_by_persistent_id(first), Some(0));
+        assert_eq!(storage.entity_by_persistent_id(second), Some(1));
+
+        let replaced = PersistentId::new();
+        assert_eq!(storage.insert_component(0, replaced), Some(first));
+        assert_eq!(storage.entity_by_persistent_id(first), None);
+        assert_eq!(storage.entity_by_persistent_id(replaced), Some(0));
+
+        storage.remove_entity_component::<PersistentId>(0);
+        assert_eq!(storage.entity_by_persistent_id(replaced), None);
+        storage.remove_entity(1);
+        assert_eq!(storage.entity_by_persistent_id(second), None);
+        assert!(storage.persistent_ids.is_empty());
+    }
+
+    #[test]
+    fn replacing_other_components_keeps_the_id_indexed() {
+        let mut storage = Storage::new();
+        let id = PersistentId::new();
+        storage.add_component_to_entity(0, 1u32);
+        storage.add_component_to_entity(0, id);
+
+        assert_eq!(storage.insert_component(0, 2u32), Some(1));
+        assert_eq!(storage.entity_by_persistent_id(id), Some(0));
+
+        // adding a second id is ignored and must not leave an index entry behind
+        storage.add_component_to_entity(0, PersistentId::new());
+        assert_eq!(storage.persistent_ids.len(), 1);
+        assert_eq!(storage.entity_by_persistent_id(id), Some(0));
+    }
+
+    #[test]
+    fn ids_survive_moving_entities_between_worlds() {
+        let mut world = World::init().unwrap();
+        let mut other = World::init().unwrap();
+        let _ = other.build_entity().with_component(0u8).build();
+        let id = PersistentId::new();
+        let entity = world.build_entity().with_component(id).build();
+
+        let components = world.take_entity(entity).unwrap();
+        let moved = other.spawn_components(components).unwrap();
+
+        assert_ne!(moved, entity);
+        assert_eq!(world.storage.entity_by_persistent_id(id), None);
+        assert_eq!(other.storage.entity_by_persistent_id(id), Some(moved));
+    }
+
+    #[test]
+    fn ids_are_serializable() {
+        let id = PersistentId::new();
+        let encoded = bincode::serialize(&id).unwrap();
+
+        assert_eq!(bincode::deserialize::<PersistentId>(&encoded).unwrap(), id);
+        assert_eq!(id.to_string().len(), 36);
+    }
+}
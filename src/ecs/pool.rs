@@ -0,0 +1,221 @@
+use crate::ecs::{EntityComponents, EntityId, EntitySpawned, Storage, World};
+use std::collections::HashSet;
+
+type ResetFn = Box<dyn FnMut(&mut Storage, EntityId)>;
+
+/// Marks an entity that is not part of the game right now, e.g. a free entity of an
+/// [`EntityPool`]. Systems skip disabled entities by querying with the
+/// [`Without<Disabled>`](crate::ecs::Without) filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Disabled;
+
+/// A pool of pre-spawned entities for things that are spawned and removed at a high rate, like
+/// bullets or damage numbers. Free entities are [`Disabled`] instead of removed, so handing them
+/// out and reclaiming them only moves them between the archetypes with and without [`Disabled`],
+/// instead of building every entity component by component.
+///
+/// Systems only get the [`Storage`], so a pool is usually kept in a resource and taken out while
+/// it is used.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::ecs::{Disabled, EntityComponents, EntityPool, Query, World, Without};
+///
+/// struct Bullet { traveled: f32 }
+///
+/// let mut world = World::init().expect("Failed to initialize world");
+/// let mut pool = EntityPool::new(
+///     &mut world,
+///     100,
+///     || {
+///         let mut components = EntityComponents::new();
+///         components.insert(Bullet { traveled: 0.0 });
+///         components
+///     },
+///     |storage, bullet| {
+///         storage.insert_component(bullet, Bullet { traveled: 0.0 });
+///     },
+/// );
+///
+/// let bullet = pool.acquire(&mut world.storage).expect("Pool is exhausted");
+/// assert_eq!(world.storage.query_filtered::<(&Bullet,), Without<Disabled>>().count(), 1);
+///
+/// pool.release(&mut world.storage, bullet);
+/// assert_eq!(pool.available(), 100);
+/// ```
+pub struct EntityPool {
+    free: Vec<EntityId>,
+    active: HashSet<EntityId>,
+    prefab: Box<dyn Fn() -> EntityComponents>,
+    reset: ResetFn,
+}
+
+impl EntityPool {
+    /// Spawn `size` disabled entities with the components returned by `prefab`. `reset` is called
+    /// for every entity that is [released](EntityPool::release), to bring its components back
+    /// into the state of the prefab.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefab has no components.
+    pub fn new(
+        world: &mut World,
+        size: usize,
+        prefab: impl Fn() -> EntityComponents + 'static,
+        reset: impl FnMut(&mut Storage, EntityId) + 'static,
+    ) -> Self {
+        let mut pool = Self {
+            free: Vec::with_capacity(size),
+            active: HashSet::with_capacity(size),
+            prefab: Box::new(prefab),
+            reset: Box::new(reset),
+        };
+
+        pool.grow(world, size);
+        pool
+    }
+
+    /// Spawn `additional` disabled entities, e.g. when the pool turned out to be too small.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefab has no components.
+    pub fn grow(&mut self, world: &mut World, additional: usize) {
+        for _ in 0..additional {
+            let components = (self.prefab)();
+            assert!(!components.is_empty(), "Pool prefab has no components");
+
+            let entity = world.new_entity();
+            components.insert_into(&mut world.storage, entity);
+            world.storage.add_component_to_entity(entity, Disabled);
+            world.storage.send_event(EntitySpawned(entity));
+
+            self.free.push(entity);
+        }
+    }
+
+    /// Enable a free entity and return it. Returns None if all entities of the pool are in use.
+    /// Entities of the pool that were removed from the storage in the meantime are dropped from
+    /// the pool.
+    pub fn acquire(&mut self, storage: &mut Storage) -> Option<EntityId> {
+        while let Some(entity) = self.free.pop() {
+            if storage.contains(entity) {
+                storage.remove_entity_component::<Disabled>(entity);
+                self.active.insert(entity);
+                return Some(entity);
+            }
+        }
+        None
+    }
+
+    /// Reset and disable an entity that was [acquired](EntityPool::acquire) from the pool.
+    /// Returns false if the entity is not in use or does not exist anymore.
+    pub fn release(&mut self, storage: &mut Storage, entity: EntityId) -> bool {
+        if !self.active.remove(&entity) || !storage.contains(entity) {
+            return false;
+        }
+
+        (self.reset)(storage, entity);
+        storage.add_component_to_entity(entity, Disabled);
+        self.free.push(entity);
+        true
+    }
+
+    /// The number of free entities.
+    #[must_use]
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// The number of entities that are in use.
+    #[must_use]
+    pub fn active(&self) -> usize {
+        self.active.len()
+    }
+
+    #[must_use]
+    pub fn is_active(&self, entity: EntityId) -> bool {
+        self.active.contains(&entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Query, Without};
+
+    #[derive(Debug, PartialEq)]
+    struct Bullet {
+        traveled: u32,
+    }
+
+    fn pool(world: &mut World, size: usize) -> EntityPool {
+        EntityPool::new(
+            world,
+            size,
+            || {
+                let mut components = EntityComponents::new();
+                components.insert(Bullet { traveled: 0 });
+                components.insert(1u8);
+                components
+            },
+            |storage, bullet| {
+                storage.insert_component(bullet, Bullet { traveled: 0 });
+            },
+        )
+    }
+
+    #[test]
+    fn acquire_and_release_reuse_entities() {
+        let mut world = World::init().unwrap();
+        let mut pool = pool(&mut world, 2);
+        assert_eq!(world.storage.entity_count(), 2);
+
+        let first = pool.acquire(&mut world.storage).unwrap();
+        let second = pool.acquire(&mut world.storage).unwrap();
+        assert_eq!(pool.acquire(&mut world.storage), None);
+        assert_eq!(pool.active(), 2);
+
+        world
+            .storage
+            .get_entity_component_mut::<Bullet>(first)
+            .unwrap()
+            .traveled = 10;
+
+        assert!(pool.release(&mut world.storage, first));
+        assert!(!pool.release(&mut world.storage, first));
+        assert_eq!(
+            world.storage.get_entity_component::<Bullet>(first),
+            Some(&Bullet { traveled: 0 })
+        );
+        assert!(world.storage.has::<Disabled>(first));
+        assert_eq!(
+            world
+                .storage
+                .query_filtered::<(&Bullet,), Without<Disabled>>()
+                .count(),
+            1
+        );
+
+        assert_eq!(pool.acquire(&mut world.storage), Some(first));
+        assert!(pool.is_active(second));
+    }
+
+    #[test]
+    fn removed_entities_are_dropped_from_the_pool() {
+        let mut world = World::init().unwrap();
+        let mut pool = pool(&mut world, 2);
+
+        let first = pool.acquire(&mut world.storage).unwrap();
+        world.storage.remove_entity(first);
+        assert!(!pool.release(&mut world.storage, first));
+
+        let free = pool.free[0];
+        world.storage.remove_entity(free);
+        assert_eq!(pool.acquire(&mut world.storage), None);
+
+        pool.grow(&mut world, 1);
+        assert!(pool.acquire(&mut world.storage).is_some());
+    }
+}
@@ -1,12 +1,32 @@
 use super::archetype::{Archetype, ArchetypeId};
 use crate::ecs::storage::ComponentVec;
-use crate::ecs::Storage;
+use crate::ecs::{QueryFilter, Storage};
 use itertools::{izip, Itertools};
-use std::any::TypeId;
-use std::collections::HashSet;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 
 const MESSAGE_DUPLICATE_COMPONENT_TYPE: &str =
     "Component types must be different when querying more than one component type";
+const MESSAGE_ZERO_CHUNK_SIZE: &str = "Chunk size must be greater than zero";
+
+/// The error of [`Query::single`] and [`Query::single_mut`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySingleError {
+    NoEntities,
+    MultipleEntities,
+}
+
+impl Display for QuerySingleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoEntities => write!(f, "no entity has the queried component"),
+            Self::MultipleEntities => write!(f, "more than one entity has the queried component"),
+        }
+    }
+}
+
+impl std::error::Error for QuerySingleError {}
 
 /// The `Query` trait provides methods to iterate over a collection of components.
 ///
@@ -41,9 +61,61 @@ const MESSAGE_DUPLICATE_COMPONENT_TYPE: &str =
 /// }
 /// ```
 ///
+/// The `_chunks` variants yield contiguous slices of up to `chunk_size` components instead of
+/// single components, so that batch loops can be vectorized. Chunks never span more than one
+/// archetype, so they may be shorter than `chunk_size`:
+///
+/// ```
+/// use game_engine::ecs::{World, Query};
+///
+/// struct Position(f32);
+/// struct Velocity(f32);
+///
+/// let mut world = World::init().unwrap();
+///
+/// for i in 0..10 {
+///     let _ = world.build_entity()
+///         .with_component(Position(0.0))
+///         .with_component(Velocity(i as f32))
+///         .build();
+/// }
+///
+/// for (positions, velocities) in world.storage.query_two_chunks_mut::<Position, Velocity>(4) {
+///     for (position, velocity) in positions.iter_mut().zip(velocities.iter()) {
+///         position.0 += velocity.0;
+///     }
+/// }
+/// ```
+///
+/// [`Query::query`] allows mixing shared and mutable access in one query, so that components that
+/// are only read don't have to be borrowed mutably:
+///
+/// ```
+/// use game_engine::ecs::{World, Query};
+///
+/// struct Position(f32);
+/// struct Velocity(f32);
+/// struct Mass(f32);
+///
+/// let mut world = World::init().unwrap();
+///
+/// let _ = world.build_entity()
+///     .with_component(Position(0.0))
+///     .with_component(Velocity(2.0))
+///     .with_component(Mass(4.0))
+///     .build();
+///
+/// for (velocity, position, mass) in world.storage.query::<(&Velocity, &mut Position, &Mass)>() {
+///     position.0 += velocity.0 / mass.0;
+/// }
+/// ```
+///
+/// Queries that only read components can use [`Query::query_ref`], which only needs a shared
+/// borrow of the storage.
+///
 /// # Panics
 ///
-/// Panics if two component types are the same.
+/// Panics if two component types are the same or if the chunk size is zero.
 pub trait Query {
     fn query_one<ComponentType: 'static>(&self) -> impl Iterator<Item = &ComponentType>;
     fn query_one_mut<ComponentType: 'static>(&mut self)
@@ -96,10 +168,248 @@ pub trait Query {
             &mut ComponentType4,
         ),
     >;
+    fn query_one_chunks<ComponentType: 'static>(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = &[ComponentType]>;
+    fn query_one_chunks_mut<ComponentType: 'static>(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = &mut [ComponentType]>;
+    fn query_two_chunks<ComponentType1: 'static, ComponentType2: 'static>(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = (&[ComponentType1], &[ComponentType2])>;
+    fn query_two_chunks_mut<ComponentType1: 'static, ComponentType2: 'static>(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = (&mut [ComponentType1], &mut [ComponentType2])>;
+    fn query_three_chunks<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+    >(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = (&[ComponentType1], &[ComponentType2], &[ComponentType3])>;
+    fn query_three_chunks_mut<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+    >(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<
+        Item = (
+            &mut [ComponentType1],
+            &mut [ComponentType2],
+            &mut [ComponentType3],
+        ),
+    >;
+    fn query_four_chunks<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+        ComponentType4: 'static,
+    >(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<
+        Item = (
+            &[ComponentType1],
+            &[ComponentType2],
+            &[ComponentType3],
+            &[ComponentType4],
+        ),
+    >;
+    fn query_four_chunks_mut<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+        ComponentType4: 'static,
+    >(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<
+        Item = (
+            &mut [ComponentType1],
+            &mut [ComponentType2],
+            &mut [ComponentType3],
+            &mut [ComponentType4],
+        ),
+    >;
+    fn query<Data: QueryData>(&mut self) -> impl Iterator<Item = Data::Item<'_>>;
+    /// Same as [`Query::query`], but only for entities that match the [filter](QueryFilter), e.g.
+    /// `query_filtered::<(&mut Visibility,), Or<(With<Sprite>, With<Text>)>>()`.
+    fn query_filtered<Data: QueryData, Filter: QueryFilter>(
+        &mut self,
+    ) -> impl Iterator<Item = Data::Item<'_>>;
+    /// Same as [`Query::query`] for queries that only read components, e.g.
+    /// `query_ref::<(&Position, &Velocity)>()`. Only borrows the storage immutably, so readers
+    /// don't block each other.
+    fn query_ref<Data: ReadOnlyQuery>(&self) -> impl Iterator<Item = Data::Item<'_>>;
+    /// Same as [`Query::query_filtered`] for queries that only read components.
+    fn query_filtered_ref<Data: ReadOnlyQuery, Filter: QueryFilter>(
+        &self,
+    ) -> impl Iterator<Item = Data::Item<'_>>;
+    /// The component of the only entity that has one, e.g. the player or the main camera.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no entity or more than one entity has the component.
+    fn single<ComponentType: 'static>(&self) -> Result<&ComponentType, QuerySingleError>;
+    /// Same as [`Query::single`], with mutable access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no entity or more than one entity has the component.
+    fn single_mut<ComponentType: 'static>(
+        &mut self,
+    ) -> Result<&mut ComponentType, QuerySingleError>;
+    /// The number of entities that have the component. Unlike counting the results of
+    /// [`Query::query_one`], this does not iterate over the components.
+    fn count<ComponentType: 'static>(&self) -> usize;
+    /// Returns true if no entity has the component.
+    fn is_empty<ComponentType: 'static>(&self) -> bool;
 }
 
+/// The access to a single component type in [`Query::query`]: `&T` for shared and `&mut T` for
+/// mutable access.
+pub trait QueryParam {
+    type Component: 'static;
+    type Item<'a>;
+
+    /// Iterate over a column of the component type, which has to be a `Vec<Self::Component>`.
+    #[doc(hidden)]
+    fn iter_column(column: &mut dyn Any) -> impl Iterator<Item = Self::Item<'_>>;
+}
+
+impl<ComponentType: 'static> QueryParam for &ComponentType {
+    type Component = ComponentType;
+    type Item<'a> = &'a ComponentType;
+
+    fn iter_column(column: &mut dyn Any) -> impl Iterator<Item = &ComponentType> {
+        column
+            .downcast_ref::<Vec<ComponentType>>()
+            .expect("Component type not found.")
+            .iter()
+    }
+}
+
+impl<ComponentType: 'static> QueryParam for &mut ComponentType {
+    type Component = ComponentType;
+    type Item<'a> = &'a mut ComponentType;
+
+    fn iter_column(column: &mut dyn Any) -> impl Iterator<Item = &mut ComponentType> {
+        column
+            .downcast_mut::<Vec<ComponentType>>()
+            .expect("Component type not found.")
+            .iter_mut()
+    }
+}
+
+/// A tuple of up to four [query parameters](QueryParam), e.g. `(&A, &mut B, &C)`.
+pub trait QueryData {
+    type Item<'a>;
+
+    #[doc(hidden)]
+    fn type_ids() -> Vec<TypeId>;
+
+    /// Iterate over the columns of an archetype, given in the order of [`QueryData::type_ids`].
+    #[doc(hidden)]
+    fn iter_columns<'a>(columns: Vec<&'a mut dyn Any>) -> impl Iterator<Item = Self::Item<'a>>;
+}
+
+impl<Param: QueryParam> QueryData for (Param,) {
+    type Item<'a> = (Param::Item<'a>,);
+
+    fn type_ids() -> Vec<TypeId> {
+        vec![TypeId::of::<Param::Component>()]
+    }
+
+    fn iter_columns<'a>(columns: Vec<&'a mut dyn Any>) -> impl Iterator<Item = Self::Item<'a>> {
+        let column = columns.into_iter().next().expect("Column not found.");
+
+        Param::iter_column(column).map(|item| (item,))
+    }
+}
+
+macro_rules! impl_query_data {
+    ($($param:ident),*) => {
+        impl<$($param: QueryParam),*> QueryData for ($($param,)*) {
+            type Item<'a> = ($($param::Item<'a>,)*);
+
+            fn type_ids() -> Vec<TypeId> {
+                vec![$(TypeId::of::<$param::Component>(),)*]
+            }
+
+            fn iter_columns<'a>(
+                columns: Vec<&'a mut dyn Any>,
+            ) -> impl Iterator<Item = Self::Item<'a>> {
+                let mut columns = columns.into_iter();
+
+                izip!($($param::iter_column(columns.next().expect("Column not found.")),)*)
+            }
+        }
+    };
+}
+
+impl_query_data!(Param1, Param2);
+impl_query_data!(Param1, Param2, Param3);
+impl_query_data!(Param1, Param2, Param3, Param4);
+
+/// A [query parameter](QueryParam) that only reads the component, i.e. `&T`.
+pub trait ReadOnlyQueryParam: QueryParam {
+    /// Same as [`QueryParam::iter_column`] on a shared column.
+    #[doc(hidden)]
+    fn iter_column_ref(column: &dyn Any) -> impl Iterator<Item = Self::Item<'_>>;
+}
+
+impl<ComponentType: 'static> ReadOnlyQueryParam for &ComponentType {
+    fn iter_column_ref(column: &dyn Any) -> impl Iterator<Item = &ComponentType> {
+        column
+            .downcast_ref::<Vec<ComponentType>>()
+            .expect("Component type not found.")
+            .iter()
+    }
+}
+
+/// [Query data](QueryData) that only reads components, e.g. `(&A, &B)`. Used by
+/// [`Query::query_ref`].
+pub trait ReadOnlyQuery: QueryData {
+    /// Same as [`QueryData::iter_columns`] on shared columns.
+    #[doc(hidden)]
+    fn iter_columns_ref<'a>(columns: Vec<&'a dyn Any>) -> impl Iterator<Item = Self::Item<'a>>;
+}
+
+impl<Param: ReadOnlyQueryParam> ReadOnlyQuery for (Param,) {
+    fn iter_columns_ref<'a>(columns: Vec<&'a dyn Any>) -> impl Iterator<Item = Self::Item<'a>> {
+        let column = columns.into_iter().next().expect("Column not found.");
+
+        Param::iter_column_ref(column).map(|item| (item,))
+    }
+}
+
+macro_rules! impl_read_only_query {
+    ($($param:ident),*) => {
+        impl<$($param: ReadOnlyQueryParam),*> ReadOnlyQuery for ($($param,)*) {
+            fn iter_columns_ref<'a>(
+                columns: Vec<&'a dyn Any>,
+            ) -> impl Iterator<Item = Self::Item<'a>> {
+                let mut columns = columns.into_iter();
+
+                izip!($($param::iter_column_ref(columns.next().expect("Column not found.")),)*)
+            }
+        }
+    };
+}
+
+impl_read_only_query!(Param1, Param2);
+impl_read_only_query!(Param1, Param2, Param3);
+impl_read_only_query!(Param1, Param2, Param3, Param4);
+
 macro_rules! iterate_components_base {
-    ($storage:ident, $($component:ty),*; $get_archetypes:ident, $iter_components:ident, $as_any_fn:ident, $downcast_fn:ident) => {{
+    ($storage:ident, $($component:ty),*; $get_archetypes:ident, $iter_components:ident, $as_any_fn:ident, $downcast_fn:ident, $column_fn:ident $column_args:tt) => {{
         use itertools::izip;
         use std::any::TypeId;
         use std::collections::HashSet;
@@ -127,7 +437,8 @@ macro_rules! iterate_components_base {
                         .unwrap()
                         .$as_any_fn()
                         .$downcast_fn::<Vec<$component>>()
-                        .unwrap(),
+                        .unwrap()
+                        .$column_fn $column_args,
                 )*
             )
         })
@@ -136,16 +447,30 @@ macro_rules! iterate_components_base {
 
 macro_rules! iterate_components {
     ($storage:ident, $($component:ty),*) => {
-        iterate_components_base!($storage, $($component),*; get_archetypes_by_ids, iter_archetype_components_by_type_ids, as_any, downcast_ref)
+        iterate_components_base!($storage, $($component),*; get_archetypes_by_ids, iter_archetype_components_by_type_ids, as_any, downcast_ref, iter())
     };
 }
 
 macro_rules! iterate_components_mut {
     ($storage:ident, $($component:ty),*) => {
-        iterate_components_base!($storage, $($component),*; get_archetypes_by_ids_mut, iter_mut_archetype_components_by_type_ids, as_any_mut, downcast_mut)
+        iterate_components_base!($storage, $($component),*; get_archetypes_by_ids_mut, iter_mut_archetype_components_by_type_ids, as_any_mut, downcast_mut, iter_mut())
     };
 }
 
+macro_rules! iterate_chunks {
+    ($storage:ident, $chunk_size:ident, $($component:ty),*) => {{
+        assert!($chunk_size > 0, "{MESSAGE_ZERO_CHUNK_SIZE}");
+        iterate_components_base!($storage, $($component),*; get_archetypes_by_ids, iter_archetype_components_by_type_ids, as_any, downcast_ref, chunks($chunk_size))
+    }};
+}
+
+macro_rules! iterate_chunks_mut {
+    ($storage:ident, $chunk_size:ident, $($component:ty),*) => {{
+        assert!($chunk_size > 0, "{MESSAGE_ZERO_CHUNK_SIZE}");
+        iterate_components_base!($storage, $($component),*; get_archetypes_by_ids_mut, iter_mut_archetype_components_by_type_ids, as_any_mut, downcast_mut, chunks_mut($chunk_size))
+    }};
+}
+
 impl Query for Storage {
     fn query_one<ComponentType: 'static>(&self) -> impl Iterator<Item = &ComponentType> {
         self.get_archetypes_for_component::<ComponentType>()
@@ -241,6 +566,215 @@ impl Query for Storage {
             ComponentType4
         )
     }
+
+    fn query_one_chunks<ComponentType: 'static>(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = &[ComponentType]> {
+        iterate_chunks!(self, chunk_size, ComponentType)
+    }
+
+    fn query_one_chunks_mut<ComponentType: 'static>(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = &mut [ComponentType]> {
+        iterate_chunks_mut!(self, chunk_size, ComponentType)
+    }
+
+    fn query_two_chunks<ComponentType1: 'static, ComponentType2: 'static>(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = (&[ComponentType1], &[ComponentType2])> {
+        iterate_chunks!(self, chunk_size, ComponentType1, ComponentType2)
+    }
+
+    fn query_two_chunks_mut<ComponentType1: 'static, ComponentType2: 'static>(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = (&mut [ComponentType1], &mut [ComponentType2])> {
+        iterate_chunks_mut!(self, chunk_size, ComponentType1, ComponentType2)
+    }
+
+    fn query_three_chunks<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+    >(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = (&[ComponentType1], &[ComponentType2], &[ComponentType3])> {
+        iterate_chunks!(
+            self,
+            chunk_size,
+            ComponentType1,
+            ComponentType2,
+            ComponentType3
+        )
+    }
+
+    fn query_three_chunks_mut<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+    >(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<
+        Item = (
+            &mut [ComponentType1],
+            &mut [ComponentType2],
+            &mut [ComponentType3],
+        ),
+    > {
+        iterate_chunks_mut!(
+            self,
+            chunk_size,
+            ComponentType1,
+            ComponentType2,
+            ComponentType3
+        )
+    }
+
+    fn query_four_chunks<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+        ComponentType4: 'static,
+    >(
+        &self,
+        chunk_size: usize,
+    ) -> impl Iterator<
+        Item = (
+            &[ComponentType1],
+            &[ComponentType2],
+            &[ComponentType3],
+            &[ComponentType4],
+        ),
+    > {
+        iterate_chunks!(
+            self,
+            chunk_size,
+            ComponentType1,
+            ComponentType2,
+            ComponentType3,
+            ComponentType4
+        )
+    }
+
+    fn query_four_chunks_mut<
+        ComponentType1: 'static,
+        ComponentType2: 'static,
+        ComponentType3: 'static,
+        ComponentType4: 'static,
+    >(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<
+        Item = (
+            &mut [ComponentType1],
+            &mut [ComponentType2],
+            &mut [ComponentType3],
+            &mut [ComponentType4],
+        ),
+    > {
+        iterate_chunks_mut!(
+            self,
+            chunk_size,
+            ComponentType1,
+            ComponentType2,
+            ComponentType3,
+            ComponentType4
+        )
+    }
+
+    fn query<Data: QueryData>(&mut self) -> impl Iterator<Item = Data::Item<'_>> {
+        self.query_filtered::<Data, ()>()
+    }
+
+    fn single<ComponentType: 'static>(&self) -> Result<&ComponentType, QuerySingleError> {
+        single(self.query_one::<ComponentType>())
+    }
+
+    fn single_mut<ComponentType: 'static>(
+        &mut self,
+    ) -> Result<&mut ComponentType, QuerySingleError> {
+        single(self.query_one_mut::<ComponentType>())
+    }
+
+    fn count<ComponentType: 'static>(&self) -> usize {
+        self.get_archetypes_for_component::<ComponentType>()
+            .into_iter()
+            .map(Archetype::len)
+            .sum()
+    }
+
+    fn is_empty<ComponentType: 'static>(&self) -> bool {
+        self.count::<ComponentType>() == 0
+    }
+
+    fn query_filtered<Data: QueryData, Filter: QueryFilter>(
+        &mut self,
+    ) -> impl Iterator<Item = Data::Item<'_>> {
+        let type_ids = Data::type_ids();
+
+        assert_eq!(
+            type_ids.iter().collect::<HashSet<_>>().len(),
+            type_ids.len(),
+            "{MESSAGE_DUPLICATE_COMPONENT_TYPE}"
+        );
+
+        let common_archetype_ids = get_archetype_ids_for_types(self, &type_ids);
+        let mut matching_rows = get_matching_rows::<Filter>(self, &common_archetype_ids);
+        let archetypes = get_archetypes_by_ids_mut(self, &common_archetype_ids);
+
+        archetypes
+            .into_iter()
+            .filter(|archetype| Filter::matches(archetype))
+            .flat_map(move |archetype| {
+                let rows = matching_rows
+                    .as_mut()
+                    .map(|rows| rows.remove(&archetype.id));
+                let columns = iter_mut_archetype_components_by_type_ids(archetype, &type_ids)
+                    .map(|column| column.as_any_mut())
+                    .collect();
+
+                filter_rows(Data::iter_columns(columns), rows)
+            })
+    }
+
+    fn query_ref<Data: ReadOnlyQuery>(&self) -> impl Iterator<Item = Data::Item<'_>> {
+        self.query_filtered_ref::<Data, ()>()
+    }
+
+    fn query_filtered_ref<Data: ReadOnlyQuery, Filter: QueryFilter>(
+        &self,
+    ) -> impl Iterator<Item = Data::Item<'_>> {
+        let type_ids = Data::type_ids();
+
+        assert_eq!(
+            type_ids.iter().collect::<HashSet<_>>().len(),
+            type_ids.len(),
+            "{MESSAGE_DUPLICATE_COMPONENT_TYPE}"
+        );
+
+        let common_archetype_ids = get_archetype_ids_for_types(self, &type_ids);
+        let mut matching_rows = get_matching_rows::<Filter>(self, &common_archetype_ids);
+        let archetypes = get_archetypes_by_ids(self, &common_archetype_ids);
+
+        archetypes
+            .into_iter()
+            .filter(|archetype| Filter::matches(archetype))
+            .flat_map(move |archetype| {
+                let rows = matching_rows
+                    .as_mut()
+                    .map(|rows| rows.remove(&archetype.id));
+                let columns = iter_archetype_components_by_type_ids(archetype, &type_ids)
+                    .map(|column| column.as_any())
+                    .collect();
+
+                filter_rows(Data::iter_columns_ref(columns), rows)
+            })
+    }
 }
 
 fn get_archetype_ids_for_types(storage: &Storage, type_ids: &[TypeId]) -> Vec<ArchetypeId> {
@@ -270,6 +804,46 @@ fn get_archetype_ids_for_types(storage: &Storage, type_ids: &[TypeId]) -> Vec<Ar
     smallest_set.iter().copied().collect()
 }
 
+/// For filters with [`QueryFilter::PER_ENTITY`], which rows of the archetypes match the filter.
+fn get_matching_rows<Filter: QueryFilter>(
+    storage: &Storage,
+    archetype_ids: &[ArchetypeId],
+) -> Option<HashMap<ArchetypeId, Vec<bool>>> {
+    if !Filter::PER_ENTITY {
+        return None;
+    }
+
+    let rows = get_archetypes_by_ids(storage, archetype_ids)
+        .into_iter()
+        .filter(|archetype| Filter::matches(archetype))
+        .map(|archetype| {
+            let rows = archetype
+                .entities
+                .iter()
+                .map(|&entity| Filter::matches_entity(storage, archetype, entity))
+                .collect();
+
+            (archetype.id, rows)
+        })
+        .collect();
+
+    Some(rows)
+}
+
+/// Skip the items of the rows that don't match, see [`get_matching_rows`].
+fn filter_rows<Item>(
+    items: impl Iterator<Item = Item>,
+    rows: Option<Option<Vec<bool>>>,
+) -> impl Iterator<Item = Item> {
+    items
+        .enumerate()
+        .filter(move |(row, _)| {
+            rows.as_ref()
+                .is_none_or(|rows| rows.as_ref().is_some_and(|rows| rows[*row]))
+        })
+        .map(|(_, item)| item)
+}
+
 fn get_archetypes_by_ids<'a>(storage: &'a Storage, ids: &[ArchetypeId]) -> Vec<&'a Archetype> {
     ids.iter()
         .map(|id| storage.archetypes.get(id).expect("Archetype not found."))
@@ -340,9 +914,19 @@ fn iter_mut_archetype_components_by_type_ids<'a>(
         })
 }
 
+fn single<Item>(mut results: impl Iterator<Item = Item>) -> Result<Item, QuerySingleError> {
+    let first = results.next().ok_or(QuerySingleError::NoEntities)?;
+
+    if results.next().is_some() {
+        return Err(QuerySingleError::MultipleEntities);
+    }
+    Ok(first)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ecs::Without;
 
     #[test]
     fn query_one_returns_correct_iterator() {
@@ -513,4 +1097,113 @@ mod tests {
         let mut iterator = storage.query_three_mut::<i32, f32, u8>();
         assert!(iterator.next().is_none());
     }
+
+    #[test]
+    fn query_chunks_split_archetypes_into_slices() {
+        let mut storage = Storage::new();
+        for entity in 0..5 {
+            storage.add_component_to_entity(entity, entity as i32);
+            storage.add_component_to_entity(entity, 1.0f32);
+        }
+        storage.add_component_to_entity(5, 5);
+
+        let mut lengths: Vec<_> = storage.query_one_chunks::<i32>(2).map(<[_]>::len).collect();
+        lengths.sort_unstable();
+        assert_eq!(lengths, [1, 1, 2, 2]);
+
+        for (ints, floats) in storage.query_two_chunks_mut::<i32, f32>(3) {
+            assert_eq!(ints.len(), floats.len());
+            for (int, float) in ints.iter().zip(floats.iter_mut()) {
+                *float += *int as f32;
+            }
+        }
+
+        let mut floats: Vec<_> = storage
+            .query_two_chunks::<f32, i32>(8)
+            .flat_map(|(floats, _)| floats.iter().copied())
+            .collect();
+        floats.sort_by(f32::total_cmp);
+        assert_eq!(floats, [1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Chunk size must be greater than zero")]
+    fn query_chunks_panics_on_zero_chunk_size() {
+        let storage = Storage::new();
+        let _ = storage.query_one_chunks::<i32>(0);
+    }
+
+    #[test]
+    fn query_mixes_shared_and_mutable_access() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 2);
+        storage.add_component_to_entity(0, 0.5f32);
+        storage.add_component_to_entity(0, 'a');
+        storage.add_component_to_entity(1, 3);
+        storage.add_component_to_entity(1, 1.0f32);
+
+        for (int, float) in storage.query::<(&i32, &mut f32)>() {
+            *float *= *int as f32;
+        }
+        for (character, int) in storage.query::<(&char, &mut i32)>() {
+            *int += *character as i32;
+        }
+
+        let mut floats: Vec<_> = storage.query::<(&f32,)>().map(|(float,)| *float).collect();
+        floats.sort_by(f32::total_cmp);
+        assert_eq!(floats, [1.0, 3.0]);
+
+        let mut ints: Vec<_> = storage.query_one::<i32>().copied().collect();
+        ints.sort_unstable();
+        assert_eq!(ints, [3, 99]);
+    }
+
+    #[test]
+    fn read_only_queries_borrow_the_storage_immutably() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 2);
+        storage.add_component_to_entity(0, 0.5f32);
+        storage.add_component_to_entity(1, 3);
+        storage.add_component_to_entity(1, 'a');
+
+        let storage = &storage;
+        let ints = storage.query_ref::<(&i32,)>();
+        let pairs = storage.query_filtered_ref::<(&i32, &f32), Without<char>>();
+
+        let mut ints: Vec<_> = ints.map(|(int,)| *int).collect();
+        ints.sort_unstable();
+        assert_eq!(ints, [2, 3]);
+        assert_eq!(
+            pairs.map(|(int, float)| *int as f32 * float).sum::<f32>(),
+            1.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Component types must be different")]
+    fn query_panics_on_duplicate_component_types() {
+        let mut storage = Storage::new();
+        let _ = storage.query::<(&i32, &mut i32)>();
+    }
+
+    #[test]
+    fn single_requires_exactly_one_entity() {
+        let mut storage = Storage::new();
+        assert_eq!(storage.single::<i32>(), Err(QuerySingleError::NoEntities));
+        assert!(storage.is_empty::<i32>());
+
+        storage.add_component_to_entity(0, 1);
+        storage.add_component_to_entity(0, 'a');
+        *storage.single_mut::<i32>().unwrap() += 1;
+        assert_eq!(storage.single::<i32>(), Ok(&2));
+
+        storage.add_component_to_entity(1, 3);
+        assert_eq!(
+            storage.single::<i32>(),
+            Err(QuerySingleError::MultipleEntities)
+        );
+        assert_eq!(storage.count::<i32>(), 2);
+        assert_eq!(storage.count::<char>(), 1);
+        assert!(!storage.is_empty::<i32>());
+    }
 }
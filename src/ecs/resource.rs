@@ -0,0 +1,129 @@
+use crate::ecs::Storage;
+use std::any::{Any, TypeId};
+
+impl Storage {
+    /// Insert a resource into the storage. There can be at most one resource per type, so an
+    /// already existing resource of the same type is replaced and returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct Score(u32);
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// world.storage.insert_resource(Score(0));
+    ///
+    /// if let Some(score) = world.storage.get_resource_mut::<Score>() {
+    ///     score.0 += 10;
+    /// }
+    ///
+    /// assert_eq!(world.storage.get_resource::<Score>().unwrap().0, 10);
+    /// ```
+    pub fn insert_resource<ResourceType: 'static>(
+        &mut self,
+        resource: ResourceType,
+    ) -> Option<ResourceType> {
+        self.resources
+            .insert(TypeId::of::<ResourceType>(), Box::new(resource))
+            .map(|previous| downcast_resource(previous))
+    }
+
+    /// Get a reference to the resource of the given type. Returns None if no such resource exists.
+    #[must_use]
+    pub fn get_resource<ResourceType: 'static>(&self) -> Option<&ResourceType> {
+        self.resources
+            .get(&TypeId::of::<ResourceType>())
+            .and_then(|resource| resource.downcast_ref::<ResourceType>())
+    }
+
+    /// Get a mutable reference to the resource of the given type. Returns None if no such resource
+    /// exists.
+    pub fn get_resource_mut<ResourceType: 'static>(&mut self) -> Option<&mut ResourceType> {
+        self.resources
+            .get_mut(&TypeId::of::<ResourceType>())
+            .and_then(|resource| resource.downcast_mut::<ResourceType>())
+    }
+
+    /// Remove the resource of the given type from the storage and return it.
+    pub fn remove_resource<ResourceType: 'static>(&mut self) -> Option<ResourceType> {
+        self.resources
+            .remove(&TypeId::of::<ResourceType>())
+            .map(|resource| downcast_resource(resource))
+    }
+
+    /// Returns true if a resource of the given type exists.
+    #[must_use]
+    pub fn has_resource<ResourceType: 'static>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<ResourceType>())
+    }
+}
+
+fn downcast_resource<ResourceType: 'static>(resource: Box<dyn Any>) -> ResourceType {
+    // We allow a panic, since resources are always stored under the type id of their own type.
+    *resource
+        .downcast::<ResourceType>()
+        .expect("Internal storage error. Resource stored under wrong type id.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_resource_replaces_and_returns_previous_resource() {
+        let mut storage = Storage::new();
+
+        assert!(storage.insert_resource(5).is_none());
+        assert_eq!(storage.insert_resource(10), Some(5));
+
+        assert_eq!(storage.resources.len(), 1);
+        assert_eq!(storage.get_resource::<i32>(), Some(&10));
+    }
+
+    #[test]
+    fn get_resource_returns_none_for_missing_resource() {
+        let mut storage = Storage::new();
+        storage.insert_resource(5);
+
+        assert!(storage.get_resource::<f32>().is_none());
+        assert!(storage.get_resource_mut::<f32>().is_none());
+        assert!(!storage.has_resource::<f32>());
+    }
+
+    #[test]
+    fn get_resource_mut_modifies_resource() {
+        let mut storage = Storage::new();
+        storage.insert_resource(String::from("foo"));
+
+        storage
+            .get_resource_mut::<String>()
+            .unwrap()
+            .push_str("bar");
+
+        assert_eq!(storage.get_resource::<String>().unwrap(), "foobar");
+    }
+
+    #[test]
+    fn remove_resource_returns_resource_and_removes_it() {
+        let mut storage = Storage::new();
+        storage.insert_resource(5);
+        storage.insert_resource(42.0f32);
+
+        assert_eq!(storage.remove_resource::<i32>(), Some(5));
+        assert!(!storage.has_resource::<i32>());
+        assert!(storage.has_resource::<f32>());
+        assert!(storage.remove_resource::<i32>().is_none());
+    }
+
+    #[test]
+    fn resources_are_independent_of_components() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 5);
+        storage.insert_resource(42);
+
+        assert_eq!(storage.archetypes.len(), 1);
+        assert_eq!(storage.get_resource::<i32>(), Some(&42));
+    }
+}
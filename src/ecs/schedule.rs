@@ -0,0 +1,416 @@
+use crate::ecs::{Storage, System, World};
+use crate::time::Time;
+use std::time::Duration;
+
+/// Decides in which frames a system is updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunCondition {
+    EveryFrame,
+    Interval {
+        interval: Duration,
+        accumulated: Duration,
+    },
+    Frames {
+        frames: u32,
+        counter: u32,
+    },
+}
+
+impl RunCondition {
+    /// Advance the condition by one frame that took `delta` and return true if the system should
+    /// run in this frame.
+    fn advance(&mut self, delta: Duration) -> bool {
+        match self {
+            Self::EveryFrame => true,
+            Self::Interval {
+                interval,
+                accumulated,
+            } => {
+                *accumulated += delta;
+
+                if *accumulated < *interval {
+                    return false;
+                }
+
+                // run at most once per frame, even if the frame took longer than the interval
+                *accumulated -= *interval;
+                if *accumulated >= *interval {
+                    *accumulated = Duration::ZERO;
+                }
+                true
+            }
+            Self::Frames { frames, counter } => {
+                *counter += 1;
+
+                if *counter < *frames {
+                    return false;
+                }
+
+                *counter = 0;
+                true
+            }
+        }
+    }
+}
+
+/// A system together with the information when it is updated.
+pub(crate) struct ScheduledSystem {
+    system: Box<dyn System>,
+    condition: RunCondition,
+    set: Option<&'static str>,
+    run_once: bool,
+    finished: bool,
+}
+
+impl ScheduledSystem {
+    pub(crate) fn new(system: Box<dyn System>) -> Self {
+        Self {
+            system,
+            condition: RunCondition::EveryFrame,
+            set: None,
+            run_once: false,
+            finished: false,
+        }
+    }
+
+    fn run(&mut self, delta: Duration, storage: &mut Storage) {
+        if self.condition.advance(delta) {
+            self.system.update(storage);
+            self.finished = self.run_once && self.system.is_done();
+        }
+    }
+}
+
+/// Configures when a system that was just [added](World::add_system) is updated. By default, a
+/// system is updated once per frame.
+pub struct SystemConfig<'a> {
+    system: &'a mut ScheduledSystem,
+}
+
+impl<'a> SystemConfig<'a> {
+    pub(crate) fn new(system: &'a mut ScheduledSystem) -> Self {
+        Self { system }
+    }
+
+    /// Update the system whenever the interval of game time has passed, e.g. for autosaves or
+    /// spawning waves of enemies. The system is updated at most once per frame and not at all
+    /// while the [time](Time) is paused.
+    pub fn every(self, interval: Duration) -> Self {
+        self.system.condition = RunCondition::Interval {
+            interval,
+            accumulated: Duration::ZERO,
+        };
+        self
+    }
+
+    /// Update the system only in every n-th frame. A value of 0 is treated as 1.
+    pub fn every_frames(self, frames: u32) -> Self {
+        self.system.condition = RunCondition::Frames {
+            frames: frames.max(1),
+            counter: 0,
+        };
+        self
+    }
+
+    /// Remove the system from the world after its first successful update, i.e. as soon as
+    /// [`System::is_done`] returns true after an update. This is meant for one-time setup that has
+    /// to wait for a resource. Combined with [`SystemConfig::every`], the system runs once after
+    /// the interval has passed.
+    pub fn run_once(self) -> Self {
+        self.system.run_once = true;
+        self
+    }
+
+    /// Put the system into the named set, so that it can be [disabled](World::enable_set)
+    /// together with the other systems of the set.
+    pub fn in_set(self, set: &'static str) -> Self {
+        self.system.set = Some(set);
+        self
+    }
+}
+
+impl World {
+    /// Add a system that is updated exactly once before the next frame, e.g. to spawn the camera
+    /// or load the initial scene. Startup systems run in the order they were added, before any
+    /// regular system.
+    pub fn add_startup_system<S: System + 'static>(&mut self, system: S) {
+        self.startup_systems.push(Box::new(system));
+    }
+
+    /// Enable or disable all systems in the named [set](SystemConfig::in_set), e.g. to pause the
+    /// simulation while rendering and UI keep running. Disabled systems are skipped completely,
+    /// so their intervals do not advance either. Sets are enabled by default.
+    pub fn enable_set(&mut self, set: &'static str, enabled: bool) {
+        if enabled {
+            self.disabled_sets.remove(set);
+        } else {
+            self.disabled_sets.insert(set);
+        }
+    }
+
+    #[must_use]
+    pub fn is_set_enabled(&self, set: &str) -> bool {
+        !self.disabled_sets.contains(set)
+    }
+
+    /// Advance the world by one frame that took `delta` of real time: the [event
+    /// buffers](Storage::update_events) are advanced, the [`Time`] resource is inserted if it does
+    /// not exist yet, pending startup systems are run, the [`Time`] is advanced, then all systems
+    /// that are due in this frame are updated in the order they were added. Finally, the event
+    /// buffers of the render storage are advanced and the
+    /// [extract systems](World::add_extract_system) copy the frame's data for the renderer.
+    ///
+    /// This is called by the main loop of the [`Engine`](crate::engine::Engine). Hosts with their
+    /// own event loop call it directly once per frame:
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let mut last_frame = Instant::now();
+    ///
+    /// for _ in 0..3 {
+    ///     let now = Instant::now();
+    ///     world.tick(now - last_frame);
+    ///     last_frame = now;
+    /// }
+    /// ```
+    pub fn tick(&mut self, delta: Duration) {
+        self.storage.update_events();
+
+        // startup systems see the time before the first frame
+        if !self.storage.has_resource::<Time>() {
+            self.storage.insert_resource(Time::new());
+        }
+
+        for mut system in std::mem::take(&mut self.startup_systems) {
+            system.update(&mut self.storage);
+        }
+
+        let time = self
+            .storage
+            .get_resource_mut::<Time>()
+            .expect("Time resource was just inserted");
+        time.advance(delta);
+        let delta = time.delta();
+
+        for system in &mut self.systems {
+            if system
+                .set
+                .is_some_and(|set| self.disabled_sets.contains(set))
+            {
+                continue;
+            }
+
+            system.run(delta, &mut self.storage);
+        }
+
+        self.systems.retain(|system| !system.finished);
+        self.render_storage.update_events();
+        self.extract();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::marker::PhantomData;
+
+    struct Every;
+    struct Interval;
+    struct Frames;
+    struct Startup;
+    struct Once;
+    struct Physics;
+
+    /// The number of updates of the [`CountingSystem<T>`] with the same marker type.
+    struct Count<T>(u32, PhantomData<T>);
+
+    struct CountingSystem<T>(PhantomData<T>);
+
+    impl<T: 'static> System for CountingSystem<T> {
+        fn new() -> Self {
+            Self(PhantomData)
+        }
+
+        fn update(&mut self, storage: &mut Storage) {
+            match storage.get_resource_mut::<Count<T>>() {
+                Some(count) => count.0 += 1,
+                None => {
+                    storage.insert_resource(Count::<T>(1, PhantomData));
+                }
+            }
+        }
+    }
+
+    fn count<T: 'static>(world: &World) -> u32 {
+        world
+            .storage
+            .get_resource::<Count<T>>()
+            .map_or(0, |count| count.0)
+    }
+
+    #[test]
+    fn scheduled_systems_run_at_their_interval() {
+        let mut world = World::init().unwrap();
+        world.add_system(CountingSystem::<Every>::new());
+        world
+            .add_system(CountingSystem::<Interval>::new())
+            .every(Duration::from_millis(250));
+        world
+            .add_system(CountingSystem::<Frames>::new())
+            .every_frames(3);
+
+        for _ in 0..10 {
+            world.tick(Duration::from_millis(100));
+        }
+
+        assert_eq!(count::<Every>(&world), 10);
+        assert_eq!(count::<Interval>(&world), 4);
+        assert_eq!(count::<Frames>(&world), 3);
+        assert_eq!(
+            world.storage.get_resource::<Time>().unwrap().frame_count(),
+            10
+        );
+    }
+
+    #[test]
+    fn interval_systems_follow_game_time() {
+        let mut world = World::init().unwrap();
+        world
+            .add_system(CountingSystem::<Interval>::new())
+            .every(Duration::from_secs(1));
+
+        let mut time = Time::new();
+        time.pause();
+        world.storage.insert_resource(time);
+
+        world.tick(Duration::from_secs(5));
+        assert_eq!(count::<Interval>(&world), 0);
+
+        world.storage.get_resource_mut::<Time>().unwrap().resume();
+        world.tick(Duration::from_secs(5));
+        world.tick(Duration::from_millis(10));
+        assert_eq!(count::<Interval>(&world), 1);
+    }
+
+    #[test]
+    fn startup_and_run_once_systems_run_a_single_time() {
+        let mut world = World::init().unwrap();
+        world.add_startup_system(CountingSystem::<Startup>::new());
+        world
+            .add_system(CountingSystem::<Once>::new())
+            .every_frames(2)
+            .run_once();
+        world.add_system(CountingSystem::<Every>::new());
+
+        for _ in 0..5 {
+            world.tick(Duration::from_millis(16));
+        }
+
+        assert_eq!(count::<Startup>(&world), 1);
+        assert_eq!(count::<Once>(&world), 1);
+        assert_eq!(count::<Every>(&world), 5);
+        assert_eq!(world.systems.len(), 1);
+    }
+
+    /// The frame count of the [`Time`] resource when the system ran.
+    struct FrameCount(Option<u64>);
+
+    struct RecordFrameCount;
+
+    impl System for RecordFrameCount {
+        fn new() -> Self {
+            Self
+        }
+
+        fn update(&mut self, storage: &mut Storage) {
+            let frame_count = storage.get_resource::<Time>().map(Time::frame_count);
+            storage.insert_resource(FrameCount(frame_count));
+        }
+    }
+
+    #[test]
+    fn startup_systems_see_the_time_before_the_first_frame() {
+        let mut world = World::init().unwrap();
+        world.add_startup_system(RecordFrameCount::new());
+
+        world.tick(Duration::from_millis(16));
+
+        assert_eq!(
+            world.storage.get_resource::<FrameCount>().unwrap().0,
+            Some(0)
+        );
+    }
+
+    /// Counts the ticks until the `Level` resource exists.
+    struct WaitForLevel {
+        ticks: u32,
+        done: bool,
+    }
+
+    struct Level;
+
+    impl System for WaitForLevel {
+        fn new() -> Self {
+            Self {
+                ticks: 0,
+                done: false,
+            }
+        }
+
+        fn update(&mut self, storage: &mut Storage) {
+            self.ticks += 1;
+            if storage.has_resource::<Level>() {
+                storage.insert_resource(Count::<Once>(self.ticks, PhantomData));
+                self.done = true;
+            }
+        }
+
+        fn is_done(&self) -> bool {
+            self.done
+        }
+    }
+
+    #[test]
+    fn run_once_systems_wait_until_they_are_done() {
+        let mut world = World::init().unwrap();
+        world.add_system(WaitForLevel::new()).run_once();
+
+        world.tick(Duration::from_millis(16));
+        world.tick(Duration::from_millis(16));
+        assert_eq!(world.systems.len(), 1);
+
+        world.storage.insert_resource(Level);
+        world.tick(Duration::from_millis(16));
+        world.tick(Duration::from_millis(16));
+
+        assert_eq!(count::<Once>(&world), 3);
+        assert!(world.systems.is_empty());
+    }
+
+    #[test]
+    fn disabled_sets_are_skipped() {
+        let mut world = World::init().unwrap();
+        world
+            .add_system(CountingSystem::<Physics>::new())
+            .in_set("physics");
+        world.add_system(CountingSystem::<Every>::new());
+
+        world.tick(Duration::from_millis(16));
+        world.enable_set("physics", false);
+        world.tick(Duration::from_millis(16));
+        world.tick(Duration::from_millis(16));
+
+        assert!(!world.is_set_enabled("physics"));
+        assert_eq!(count::<Physics>(&world), 1);
+        assert_eq!(count::<Every>(&world), 3);
+
+        world.enable_set("physics", true);
+        world.tick(Duration::from_millis(16));
+
+        assert!(world.is_set_enabled("physics"));
+        assert_eq!(count::<Physics>(&world), 2);
+    }
+}
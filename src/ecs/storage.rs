@@ -1,7 +1,9 @@
 use crate::ecs::archetype::{align_and_migrate_archetypes, Archetype, ArchetypeId};
-use crate::ecs::EntityId;
+use crate::ecs::clone::{default_clone_fns, CloneFn};
+use crate::ecs::components::BoxedComponent;
+use crate::ecs::{EntityComponents, EntityDespawned, EntityId, PersistentId};
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub trait ComponentVec: Any {
     fn as_any(&self) -> &dyn Any;
@@ -11,8 +13,11 @@ pub trait ComponentVec: Any {
     #[allow(dead_code)]
     fn is_empty(&self) -> bool;
     fn element_type_id(&self) -> TypeId;
+    fn element_type_name(&self) -> &'static str;
     fn migrate_element(&mut self, index: usize, other: &mut dyn ComponentVec);
     fn swap_remove(&mut self, index: usize);
+    fn take_element(&mut self, index: usize) -> BoxedComponent;
+    fn shrink_to_fit(&mut self);
 }
 
 impl<T: 'static> ComponentVec for Vec<T> {
@@ -40,6 +45,10 @@ impl<T: 'static> ComponentVec for Vec<T> {
         TypeId::of::<T>()
     }
 
+    fn element_type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
     fn migrate_element(&mut self, index: usize, other: &mut dyn ComponentVec) {
         let element = self.swap_remove(index);
         if let Some(other) = other.as_any_mut().downcast_mut::<Self>() {
@@ -55,6 +64,14 @@ impl<T: 'static> ComponentVec for Vec<T> {
     fn swap_remove(&mut self, index: usize) {
         self.swap_remove(index);
     }
+
+    fn take_element(&mut self, index: usize) -> BoxedComponent {
+        BoxedComponent::new(self.swap_remove(index))
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.shrink_to_fit();
+    }
 }
 
 /// An index to the row in an archetype that stores the components of an entity.
@@ -63,7 +80,7 @@ pub type EntityRow = usize;
 /// A record of an entity in an archetype. This is used inside the `entity_index` to keep track of
 ///  a) which archetype an entity belongs to and
 ///  b) which row in the archetype the components of the entity are stored
-struct EntityRecord {
+pub(crate) struct EntityRecord {
     pub(crate) archetype_id: ArchetypeId,
     pub(crate) entity_row: EntityRow,
 }
@@ -75,23 +92,62 @@ pub struct Storage {
     /// Vector of all archetypes in the storage. The index in the vector is the archetype id.
     pub(crate) archetypes: HashMap<ArchetypeId, Archetype>,
     pub(crate) component_index: HashMap<TypeId, Vec<ArchetypeId>>,
-    entity_index: HashMap<EntityId, EntityRecord>,
-    archetype_id_counter: ArchetypeId,
+    pub(crate) entity_index: HashMap<EntityId, EntityRecord>,
+    pub(crate) archetype_id_counter: ArchetypeId,
+    /// Global, unique-per-type data that is not attached to an entity, see [`Storage::insert_resource`].
+    pub(crate) resources: HashMap<TypeId, Box<dyn Any>>,
+    /// The entities of each tag type, see [`Storage::add_tag`].
+    pub(crate) tags: HashMap<TypeId, HashSet<EntityId>>,
+    /// The entities with a [`PersistentId`] component, see [`Storage::entity_by_persistent_id`].
+    pub(crate) persistent_ids: HashMap<PersistentId, EntityId>,
+    /// The component types that can be cloned, see [`Storage::register_cloneable`].
+    pub(crate) clone_fns: HashMap<TypeId, CloneFn>,
+    /// Advance the [`Events`](crate::ecs::Events) of each event type, see
+    /// [`Storage::update_events`].
+    pub(crate) event_updaters: HashMap<TypeId, fn(&mut Self)>,
 }
 
 impl Storage {
     /// Remove an entity from the Storage. This updates the entities archetype by removing the
     /// swap removing the entity row. Removes the archetype if this is the only entity for this
-    /// archetype.
+    /// archetype. An [`EntityDespawned`] event is sent if the entity existed.
     ///
     /// # Panics
     ///
     /// Panics if the `EntityId` points to an invalid archetype id.
     pub fn remove_entity(&mut self, entity: EntityId) {
         // TODO: check if error handling or feedback is necessary
-        let Some(record) = self.entity_index.remove(&entity) else {
-            return;
-        };
+        self.remove_entity_row(entity, |column, row| column.swap_remove(row));
+    }
+
+    /// Remove an entity like [`Storage::remove_entity`], but return its components and tags
+    /// instead of dropping them. Returns None if the entity does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `EntityId` points to an invalid archetype id.
+    pub fn take_entity(&mut self, entity: EntityId) -> Option<EntityComponents> {
+        let tags = self.tag_types_of(entity);
+
+        self.remove_entity_row(entity, |column, row| column.take_element(row))
+            .map(|components| {
+                let mut components: EntityComponents = components.into_iter().collect();
+                components.tags = tags;
+                components
+            })
+    }
+
+    /// Remove the row of an entity from its archetype. `remove` is called once per column of the
+    /// archetype and has to swap remove the row from it.
+    fn remove_entity_row<R>(
+        &mut self,
+        entity: EntityId,
+        mut remove: impl FnMut(&mut dyn ComponentVec, EntityRow) -> R,
+    ) -> Option<Vec<R>> {
+        self.remove_all_tags(entity);
+        self.unindex_persistent_id(entity);
+        let record = self.entity_index.remove(&entity)?;
+        self.send_event(EntityDespawned(entity));
 
         let archetype = self
             .archetypes
@@ -100,28 +156,25 @@ impl Storage {
 
         let archetype_size = archetype.component_types[0].len();
 
-        // remove archetype if it only contains the current entity
+        // remove current entity
+        let removed = archetype
+            .component_types
+            .iter_mut()
+            .map(|column| remove(column.as_mut(), record.entity_row))
+            .collect();
+        archetype.entities.swap_remove(record.entity_row);
+
+        // remove archetype if it only contained the current entity
         if archetype_size == 1 {
             self.remove_archetype(record.archetype_id);
-            return;
+        } else {
+            self.update_swapped_entity_record(&record);
         }
 
-        // remove current entity
-        archetype.component_types.iter_mut().for_each(|column| {
-            column.swap_remove(record.entity_row);
-        });
-
-        // we swap_remove the entity row, so all components in the last row are moved to the removed
-        // row, meaning we have to update the entity index for the moved entity
-        if record.entity_row < archetype_size - 1 {
-            let moved_entity = self
-                .entity_index
-                .iter_mut()
-                .find(|(_, r)| r.archetype_id == record.archetype_id)
-                .expect("Entity not found.");
+        #[cfg(feature = "debug-validate")]
+        self.validate();
 
-            moved_entity.1.entity_row = record.entity_row;
-        }
+        Some(removed)
     }
 
     /// Adds a component to an entity. This will create a new archetype if none exists for the
@@ -137,24 +190,27 @@ impl Storage {
         entity: EntityId,
         component: ComponentType,
     ) {
-        // If a new entity with a missing component is added, we create a new archetype for it
-        if !self.has_component::<ComponentType>() && !self.entity_index.contains_key(&entity) {
-            let archetype = self.add_archetype_for_new_component_type(component);
-            let record = EntityRecord {
-                archetype_id: archetype.id,
-                entity_row: 0,
-            };
-            self.entity_index.insert(entity, record);
+        // If a new entity is added and there is no archetype for just this component, we create a
+        // new archetype for it
+        if !self.entity_index.contains_key(&entity)
+            && self
+                .find_archetype_id_by_type_ids::<ComponentType>(&[TypeId::of::<ComponentType>()])
+                .is_none()
+        {
+            self.index_persistent_id(entity, &component);
+            let archetype_id = self.add_archetype_for_new_component_type(component).id;
+            self.push_entity_record(entity, archetype_id);
+            #[cfg(feature = "debug-validate")]
+            self.validate();
             return;
         }
 
         // If the entity already has a component of the same type, we don't need to do anything
         // TODO: maybe we should return an error here? Or simply swap the component?
-        if self.entity_index.contains_key(&entity)
-            && self.has_entity_component::<ComponentType>(entity)
-        {
+        if self.entity_index.contains_key(&entity) && self.has::<ComponentType>(entity) {
             return;
         }
+        self.index_persistent_id(entity, &component);
 
         let new_archetype_id = {
             let current_archetype = self.get_archetype_for_entity(entity);
@@ -189,13 +245,50 @@ impl Storage {
             .get_mut(&new_archetype_id)
             .expect("Internal storage error. Invalid Archetype ID.");
         new_archetype.push_component(component);
+        self.push_entity_record(entity, new_archetype_id);
 
-        // update the entity index
-        let new_record = EntityRecord {
-            archetype_id: new_archetype.id,
-            entity_row: new_archetype.component_types[0].len() - 1,
-        };
-        self.entity_index.insert(entity, new_record);
+        #[cfg(feature = "debug-validate")]
+        self.validate();
+    }
+
+    /// Adds a component to an entity or replaces the existing component of the same type. Returns
+    /// the replaced component, e.g. to restore it when a temporary buff runs out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Weapon { Sword, Bow }
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let player = world.build_entity().with_component(Weapon::Sword).build();
+    ///
+    /// let previous = world.storage.insert_component(player, Weapon::Bow);
+    /// assert_eq!(previous, Some(Weapon::Sword));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `EntityId` points to an invalid archetype id.
+    pub fn insert_component<ComponentType: 'static>(
+        &mut self,
+        entity: EntityId,
+        component: ComponentType,
+    ) -> Option<ComponentType> {
+        if TypeId::of::<ComponentType>() == TypeId::of::<PersistentId>()
+            && self.has::<ComponentType>(entity)
+        {
+            self.unindex_persistent_id(entity);
+            self.index_persistent_id(entity, &component);
+        }
+        if let Some(existing) = self.get_entity_component_mut::<ComponentType>(entity) {
+            return Some(std::mem::replace(existing, component));
+        }
+
+        self.add_component_to_entity(entity, component);
+        None
     }
 
     /// Removes a component from an entity. This will create a new archetype if none exists for the
@@ -212,9 +305,29 @@ impl Storage {
         entity: EntityId,
         _component: &ComponentType,
     ) {
-        if !self.entity_index.contains_key(&entity)
-            || !self.has_entity_component::<ComponentType>(entity)
-        {
+        self.remove_entity_component::<ComponentType>(entity);
+    }
+
+    /// Remove the component of the given type from all entities. Entities without other components
+    /// are removed.
+    pub fn remove_component_from_all<ComponentType: 'static>(&mut self) {
+        for entity in self.entities_with_component::<ComponentType>() {
+            self.remove_entity_component::<ComponentType>(entity);
+        }
+    }
+
+    /// Same as [`Storage::remove_component_from_entity`], without a reference to the component.
+    pub(crate) fn remove_entity_component<ComponentType: 'static>(&mut self, entity: EntityId) {
+        if !self.entity_index.contains_key(&entity) || !self.has::<ComponentType>(entity) {
+            return;
+        }
+        if TypeId::of::<ComponentType>() == TypeId::of::<PersistentId>() {
+            self.unindex_persistent_id(entity);
+        }
+
+        // an entity without components does not exist anymore
+        if self.component_types_of(entity).len() == 1 {
+            self.remove_entity(entity);
             return;
         }
 
@@ -247,18 +360,170 @@ impl Storage {
         };
 
         self.move_entity_to_new_archetype(entity, new_archetype_id);
+        self.push_entity_record(entity, new_archetype_id);
 
-        let new_archetype = self
+        #[cfg(feature = "debug-validate")]
+        self.validate();
+    }
+
+    /// Iterate over all archetypes, e.g. to process the [columns](Archetype::column) of a component
+    /// type as slices.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let _ = world.build_entity().with_component(1u32).build();
+    /// let _ = world.build_entity().with_component(2u32).with_component(0.5f32).build();
+    ///
+    /// let sum: u32 = world
+    ///     .storage
+    ///     .archetypes()
+    ///     .filter_map(|archetype| archetype.column::<u32>())
+    ///     .map(|column| column.iter().sum::<u32>())
+    ///     .sum();
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn archetypes(&self) -> impl Iterator<Item = &Archetype> {
+        self.archetypes.values()
+    }
+
+    /// Iterate mutably over all archetypes, see [`Storage::archetypes`] and
+    /// [`Archetype::column_mut`].
+    pub fn archetypes_mut(&mut self) -> impl Iterator<Item = &mut Archetype> {
+        self.archetypes.values_mut()
+    }
+
+    /// Returns true if the entity exists, i.e. it has at least one component.
+    #[must_use]
+    pub fn contains(&self, entity: EntityId) -> bool {
+        self.entity_index.contains_key(&entity)
+    }
+
+    /// Returns true if the entity exists and has a component of the given type.
+    #[must_use]
+    pub fn has<ComponentType: 'static>(&self, entity: EntityId) -> bool {
+        self.get_archetype_for_entity(entity)
+            .is_some_and(|archetype| archetype.types.contains(&TypeId::of::<ComponentType>()))
+    }
+
+    /// The number of entities in the storage.
+    #[must_use]
+    pub fn entity_count(&self) -> usize {
+        self.entity_index.len()
+    }
+
+    /// Release memory that is no longer needed after heavy entity churn: archetypes without
+    /// entities are removed and the unused capacity of all component columns is freed. This moves
+    /// components around in memory, so it is best called at loading screens.
+    pub fn shrink_to_fit(&mut self) {
+        let empty: Vec<_> = self
             .archetypes
-            .get_mut(&new_archetype_id)
-            .expect("Internal storage error. Invalid Archetype ID.");
+            .values()
+            .filter(|archetype| archetype.is_empty())
+            .map(|archetype| archetype.id)
+            .collect();
 
-        // update the entity index
-        let new_record = EntityRecord {
-            archetype_id: new_archetype.id,
-            entity_row: new_archetype.component_types[0].len() - 1,
-        };
-        self.entity_index.insert(entity, new_record);
+        for archetype_id in empty {
+            self.remove_archetype(archetype_id);
+        }
+
+        for archetype in self.archetypes.values_mut() {
+            for column in &mut archetype.component_types {
+                column.shrink_to_fit();
+            }
+        }
+        self.archetypes.shrink_to_fit();
+        self.component_index.shrink_to_fit();
+        self.entity_index.shrink_to_fit();
+
+        #[cfg(feature = "debug-validate")]
+        self.validate();
+    }
+
+    /// Iterate over the ids of all entities in no particular order.
+    pub fn entities(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.entity_index.keys().copied()
+    }
+
+    /// The types of all components of an entity, e.g. for debuggers and serializers. Returns an
+    /// empty vector if the entity does not exist.
+    #[must_use]
+    pub fn component_types_of(&self, entity: EntityId) -> Vec<TypeId> {
+        self.get_archetype_for_entity(entity)
+            .map(|archetype| archetype.types.clone())
+            .unwrap_or_default()
+    }
+
+    /// The type names of all components of an entity, in the same order as
+    /// [`Storage::component_types_of`]. The names are meant for debugging only, see
+    /// [`std::any::type_name`].
+    #[must_use]
+    pub fn component_names_of(&self, entity: EntityId) -> Vec<&'static str> {
+        self.get_archetype_for_entity(entity)
+            .map(|archetype| {
+                archetype
+                    .component_types
+                    .iter()
+                    .map(|column| column.element_type_name())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The ids of all entities that have a component of the given type.
+    pub(crate) fn entities_with_component<ComponentType: 'static>(&self) -> Vec<EntityId> {
+        self.get_archetypes_for_component::<ComponentType>()
+            .into_iter()
+            .flat_map(|archetype| archetype.entities.iter().copied())
+            .collect()
+    }
+
+    /// Get the component of the given type of an entity, e.g. the target of a camera. Returns None
+    /// if the entity does not exist or does not have the component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct Health(u32);
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let player = world.build_entity().with_component(Health(100)).build();
+    ///
+    /// world.storage.get_entity_component_mut::<Health>(player).unwrap().0 -= 30;
+    ///
+    /// let health = world.storage.get_entity_component::<Health>(player);
+    /// assert_eq!(health.map(|health| health.0), Some(70));
+    /// assert!(world.storage.get_entity_component::<u32>(player).is_none());
+    /// ```
+    #[must_use]
+    pub fn get_entity_component<ComponentType: 'static>(
+        &self,
+        entity: EntityId,
+    ) -> Option<&ComponentType> {
+        let record = self.entity_index.get(&entity)?;
+
+        self.archetypes
+            .get(&record.archetype_id)?
+            .column::<ComponentType>()?
+            .get(record.entity_row)
+    }
+
+    /// Same as [`Storage::get_entity_component`], with mutable access.
+    pub fn get_entity_component_mut<ComponentType: 'static>(
+        &mut self,
+        entity: EntityId,
+    ) -> Option<&mut ComponentType> {
+        let record = self.entity_index.get(&entity)?;
+
+        self.archetypes
+            .get_mut(&record.archetype_id)?
+            .get_components_mut::<ComponentType>()?
+            .get_mut(record.entity_row)
     }
 
     pub(crate) fn get_archetype_ids_for_component<ComponentType: 'static>(
@@ -324,6 +589,7 @@ impl Storage {
             id: archetype_id,
             component_types: vec![component_vec],
             types: vec![TypeId::of::<ComponentType>()],
+            entities: Vec::new(),
         };
 
         self.register_archetype(archetype);
@@ -346,15 +612,66 @@ impl Storage {
             .remove(&new_archetype_id)
             .expect("Internal storage error. Invalid Archetype ID.");
 
+        let current_archetype_size = current_archetype.component_types[0].len();
+
         align_and_migrate_archetypes(
             &mut current_archetype,
             &mut new_archetype,
             current_record.entity_row,
         );
 
+        // components that are not part of the new archetype were not migrated, so they are dropped
+        // to keep all columns of the current archetype at the same length
+        current_archetype
+            .component_types
+            .iter_mut()
+            .filter(|column| column.len() == current_archetype_size)
+            .for_each(|column| column.swap_remove(current_record.entity_row));
+        current_archetype
+            .entities
+            .swap_remove(current_record.entity_row);
+
         self.archetypes
             .insert(current_archetype.id, current_archetype);
         self.archetypes.insert(new_archetype.id, new_archetype);
+
+        self.update_swapped_entity_record(&current_record);
+    }
+
+    /// Rows are removed with `swap_remove`, so the entity in the last row of the archetype is moved
+    /// into the removed row. This updates the entity index for that moved entity.
+    fn update_swapped_entity_record(&mut self, removed: &EntityRecord) {
+        // nothing was moved if the removed row was the last one
+        let Some(&moved) = self.archetypes[&removed.archetype_id]
+            .entities
+            .get(removed.entity_row)
+        else {
+            return;
+        };
+
+        self.entity_index
+            .get_mut(&moved)
+            .expect("Internal storage error. No entity found for the last archetype row.")
+            .entity_row = removed.entity_row;
+    }
+
+    /// Add an entity to the entity index and the last row of the archetype, after its components
+    /// were pushed to the archetype.
+    pub(crate) fn push_entity_record(&mut self, entity: EntityId, archetype_id: ArchetypeId) {
+        let archetype = self
+            .archetypes
+            .get_mut(&archetype_id)
+            .expect("Internal storage error. Invalid Archetype ID.");
+
+        archetype.entities.push(entity);
+        let entity_row = archetype.entities.len() - 1;
+        self.entity_index.insert(
+            entity,
+            EntityRecord {
+                archetype_id,
+                entity_row,
+            },
+        );
     }
 
     fn register_archetype(&mut self, archetype: Archetype) {
@@ -392,23 +709,12 @@ impl Storage {
         });
     }
 
+    #[cfg(test)]
     fn has_component<ComponentType: 'static>(&self) -> bool {
         self.component_index
             .contains_key(&TypeId::of::<ComponentType>())
     }
 
-    fn has_entity_component<ComponentType: 'static>(&self, entity: EntityId) -> bool {
-        self.get_archetype_for_entity(entity)
-            .map_or(false, |archetype| {
-                archetype.component_types.iter().any(|column| {
-                    column
-                        .as_any()
-                        .downcast_ref::<Vec<ComponentType>>()
-                        .is_some_and(|vec| vec.get(entity).is_some())
-                })
-            })
-    }
-
     /// Get the archetype for an entity. Returns None if the entity does not exist.
     fn get_archetype_for_entity(&self, entity: EntityId) -> Option<&Archetype> {
         let archetype_id = self.entity_index.get(&entity)?.archetype_id;
@@ -422,6 +728,11 @@ impl Storage {
             component_index: HashMap::new(),
             entity_index: HashMap::new(),
             archetype_id_counter: 0,
+            resources: HashMap::new(),
+            tags: HashMap::new(),
+            persistent_ids: HashMap::new(),
+            clone_fns: default_clone_fns(),
+            event_updaters: HashMap::new(),
         }
     }
 }
@@ -606,7 +917,7 @@ mod tests {
         assert_eq!(storage.archetypes.len(), 3);
 
         let archetype = &storage.entity_index.get(&entity).unwrap().archetype_id;
-        let archetype = &storage.archetypes[&archetype];
+        let archetype = &storage.archetypes[archetype];
         assert_eq!(archetype.types.len(), 1);
         assert_eq!(archetype.component_types.len(), 1);
         assert_eq!(archetype.component_types[0].len(), 1);
@@ -639,12 +950,12 @@ mod tests {
         assert_eq!(storage.archetypes.len(), 2);
 
         let archetype = &storage.entity_index.get(&entity).unwrap().archetype_id;
-        let archetype = &storage.archetypes[&archetype];
+        let archetype = &storage.archetypes[archetype];
         assert_eq!(archetype.types.len(), 1);
         assert_eq!(archetype.component_types.len(), 1);
         assert_eq!(archetype.component_types[0].len(), 1);
 
-        let component = archetype.get_components::<i32>();
+        let component = archetype.column::<i32>();
         assert!(component.is_some());
         assert_eq!(component.unwrap().len(), 1);
     }
@@ -655,13 +966,14 @@ mod tests {
             id: 0,
             component_types: vec![Box::<Vec<i32>>::default()],
             types: vec![TypeId::of::<i32>()],
+            entities: Vec::new(),
         };
 
-        let component_vec = archetype.get_components::<i32>();
+        let component_vec = archetype.column::<i32>();
         assert!(component_vec.is_some());
         assert_eq!(component_vec.unwrap().len(), 0);
 
-        let component_vec = archetype.get_components::<f32>();
+        let component_vec = archetype.column::<f32>();
         assert!(component_vec.is_none());
     }
 
@@ -759,7 +1071,7 @@ mod tests {
                 .component_index
                 .get(&TypeId::of::<i32>())
                 .unwrap()
-                .get(0),
+                .first(),
             Some(1).as_ref()
         );
         assert_eq!(storage.get_archetypes_for_component::<i32>().len(), 1);
@@ -771,4 +1083,101 @@ mod tests {
         assert_eq!(storage.get_archetypes_for_component::<i32>().len(), 0);
         assert_eq!(storage.get_archetypes_for_component::<f32>().len(), 0);
     }
+
+    #[test]
+    fn component_types_of_lists_the_components_of_an_entity() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 1i32);
+        storage.add_component_to_entity(0, 1.0f32);
+        storage.add_component_to_entity(1, 2i32);
+
+        let mut entities: Vec<_> = storage.entities().collect();
+        entities.sort_unstable();
+        assert_eq!(entities, [0, 1]);
+
+        let types = storage.component_types_of(0);
+        assert_eq!(types.len(), 2);
+        assert!(types.contains(&TypeId::of::<i32>()));
+        assert!(types.contains(&TypeId::of::<f32>()));
+
+        let names = storage.component_names_of(0);
+        let position = |type_id| types.iter().position(|&t| t == type_id).unwrap();
+        assert_eq!(names[position(TypeId::of::<i32>())], "i32");
+        assert_eq!(names[position(TypeId::of::<f32>())], "f32");
+        assert!(storage.component_types_of(2).is_empty());
+    }
+
+    #[test]
+    fn contains_and_has_check_entities_and_components() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(3, 1i32);
+        storage.add_component_to_entity(5, 1i32);
+        storage.add_component_to_entity(5, 1.0f32);
+
+        assert!(storage.contains(3));
+        assert!(!storage.contains(0));
+        assert!(storage.has::<f32>(5));
+        assert!(!storage.has::<f32>(3));
+        assert!(!storage.has::<i32>(0));
+        assert_eq!(storage.entity_count(), 2);
+
+        storage.remove_entity(3);
+        assert!(!storage.contains(3));
+        assert!(!storage.has::<i32>(3));
+        assert_eq!(storage.entity_count(), 1);
+    }
+
+    #[test]
+    fn remove_component_from_all_removes_entities_without_other_components() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 1i32);
+        storage.add_component_to_entity(0, 1.0f32);
+        storage.add_component_to_entity(1, 2i32);
+        storage.add_component_to_entity(2, 2.0f32);
+
+        storage.remove_component_from_all::<i32>();
+
+        assert!(!storage.has::<i32>(0));
+        assert!(storage.has::<f32>(0));
+        assert!(!storage.contains(1));
+        assert!(storage.contains(2));
+        assert!(storage.entities_with_component::<i32>().is_empty());
+    }
+
+    #[test]
+    fn insert_component_adds_or_replaces_components() {
+        let mut storage = Storage::new();
+
+        assert_eq!(storage.insert_component(0, 1i32), None);
+        assert_eq!(storage.insert_component(0, 1.0f32), None);
+        assert_eq!(storage.insert_component(0, 2i32), Some(1));
+
+        assert_eq!(storage.get_entity_component_mut::<i32>(0), Some(&mut 2));
+        assert_eq!(storage.component_types_of(0).len(), 2);
+    }
+
+    #[test]
+    fn shrink_to_fit_removes_empty_archetypes() {
+        let mut storage = Storage::new();
+        for entity in 0..100 {
+            storage.add_component_to_entity(entity, 1i32);
+            storage.add_component_to_entity(entity, 1.0f32);
+        }
+        for entity in 1..100 {
+            storage.remove_entity(entity);
+        }
+
+        storage.shrink_to_fit();
+
+        assert_eq!(storage.archetypes.len(), 1);
+        assert_eq!(storage.get_archetypes_for_component::<i32>().len(), 1);
+        assert!(storage.has::<f32>(0));
+
+        let archetype = storage.archetypes().next().unwrap();
+        assert_eq!(archetype.column::<i32>(), Some([1].as_slice()));
+        assert!(archetype.component_types.iter().all(|column| column
+            .as_any()
+            .downcast_ref::<Vec<i32>>()
+            .is_none_or(|column| column.capacity() == 1)));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::ecs::schedule::{ScheduledSystem, SystemConfig};
 use crate::ecs::{Storage, World};
 
 /// Base trait for a subsystem of the engine. Systems are things that operate on entities and are periodically
@@ -10,15 +11,26 @@ pub trait System {
         Self: Sized;
 
     fn update(&mut self, storage: &mut Storage);
+
+    /// For systems added with [`SystemConfig::run_once`]: returns true once the system has done
+    /// its work, e.g. after a resource it waits for appeared. Checked after every update, the
+    /// system is removed from the world as soon as it returns true. By default, a run-once system
+    /// is removed after its first update.
+    fn is_done(&self) -> bool {
+        true
+    }
 }
 
 impl World {
     /// Add a new system statically. The world starts with no default systems for full flexibility.
+    /// Systems are updated once per [tick](World::tick) in the order they were added, unless the
+    /// returned [`SystemConfig`] schedules them differently.
     ///
     /// # Example
     ///
     /// ```
     /// use game_engine::ecs::{System, Storage, World};
+    /// use std::time::Duration;
     ///
     /// struct MySystem;
     ///
@@ -34,8 +46,14 @@ impl World {
     ///
     /// let mut world = World::init().expect("Failed to initialize world");
     /// world.add_system(MySystem::new());
+    /// world
+    ///     .add_system(MySystem::new())
+    ///     .every(Duration::from_secs(60));
     /// ```
-    pub fn add_system<S: System + 'static>(&mut self, system: S) {
-        self.systems.push(Box::new(system));
+    pub fn add_system<S: System + 'static>(&mut self, system: S) -> SystemConfig<'_> {
+        self.systems.push(ScheduledSystem::new(Box::new(system)));
+
+        let system = self.systems.last_mut().expect("System was just added");
+        SystemConfig::new(system)
     }
 }
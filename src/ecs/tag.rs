@@ -0,0 +1,151 @@
+use crate::ecs::{EntityId, Storage};
+use std::any::TypeId;
+use std::collections::HashSet;
+
+impl Storage {
+    /// Tag an entity with a marker type. Unlike components, tags are stored in a set per tag type
+    /// instead of the archetypes, so adding and removing them never moves the entity to another
+    /// archetype. Designs that combine many markers (e.g. `Stunned`, `Burning`, `Selected`) would
+    /// otherwise create an archetype for every combination. Queries can be restricted to tagged
+    /// entities with the [`WithTag`](crate::ecs::WithTag) filter.
+    ///
+    /// Returns false if the entity does not exist. An entity without components can't be tagged,
+    /// it does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct Health(u32);
+    /// struct Burning;
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let enemy = world.build_entity().with_component(Health(10)).build();
+    ///
+    /// assert!(world.storage.add_tag::<Burning>(enemy));
+    ///
+    /// assert!(world.storage.has_tag::<Burning>(enemy));
+    /// assert_eq!(world.storage.tagged::<Burning>().collect::<Vec<_>>(), [enemy]);
+    /// ```
+    pub fn add_tag<TagType: 'static>(&mut self, entity: EntityId) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+
+        self.tags
+            .entry(TypeId::of::<TagType>())
+            .or_default()
+            .insert(entity);
+        true
+    }
+
+    /// Remove a tag from an entity. Returns true if the entity had the tag.
+    pub fn remove_tag<TagType: 'static>(&mut self, entity: EntityId) -> bool {
+        let Some(entities) = self.tags.get_mut(&TypeId::of::<TagType>()) else {
+            return false;
+        };
+        let removed = entities.remove(&entity);
+
+        if entities.is_empty() {
+            self.tags.remove(&TypeId::of::<TagType>());
+        }
+        removed
+    }
+
+    #[must_use]
+    pub fn has_tag<TagType: 'static>(&self, entity: EntityId) -> bool {
+        self.tags
+            .get(&TypeId::of::<TagType>())
+            .is_some_and(|entities| entities.contains(&entity))
+    }
+
+    /// Iterate over all entities with the given tag in no particular order.
+    pub fn tagged<TagType: 'static>(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.tags
+            .get(&TypeId::of::<TagType>())
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    /// The types of all tags of an entity.
+    pub(crate) fn tag_types_of(&self, entity: EntityId) -> HashSet<TypeId> {
+        self.tags
+            .iter()
+            .filter(|(_, entities)| entities.contains(&entity))
+            .map(|(&tag, _)| tag)
+            .collect()
+    }
+
+    /// Add tags by their type, e.g. when an entity is spawned from
+    /// [`EntityComponents`](crate::ecs::EntityComponents).
+    pub(crate) fn add_tag_types(&mut self, entity: EntityId, tags: HashSet<TypeId>) {
+        for tag in tags {
+            self.tags.entry(tag).or_default().insert(entity);
+        }
+    }
+
+    /// Remove all tags of an entity, e.g. when it is removed.
+    pub(crate) fn remove_all_tags(&mut self, entity: EntityId) {
+        self.tags.retain(|_, entities| {
+            entities.remove(&entity);
+            !entities.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stunned;
+    struct Selected;
+
+    #[test]
+    fn tags_do_not_change_archetypes() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 1i32);
+        storage.add_component_to_entity(1, 2i32);
+
+        storage.add_tag::<Stunned>(0);
+        storage.add_tag::<Selected>(0);
+        storage.add_tag::<Stunned>(1);
+
+        assert_eq!(storage.archetypes.len(), 1);
+        assert!(storage.has_tag::<Selected>(0));
+        assert!(!storage.has_tag::<Selected>(1));
+
+        let mut stunned: Vec<_> = storage.tagged::<Stunned>().collect();
+        stunned.sort_unstable();
+        assert_eq!(stunned, [0, 1]);
+
+        assert!(storage.remove_tag::<Selected>(0));
+        assert!(!storage.remove_tag::<Selected>(0));
+        assert_eq!(storage.tagged::<Selected>().count(), 0);
+    }
+
+    #[test]
+    fn unknown_entities_are_not_tagged() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 1i32);
+        storage.remove_entity(0);
+
+        assert!(!storage.add_tag::<Stunned>(0));
+        assert!(!storage.add_tag::<Stunned>(1));
+        assert!(!storage.has_tag::<Stunned>(1));
+        assert!(storage.tags.is_empty());
+    }
+
+    #[test]
+    fn removing_an_entity_removes_its_tags() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 1i32);
+        storage.add_tag::<Stunned>(0);
+
+        storage.remove_entity(0);
+
+        assert!(!storage.has_tag::<Stunned>(0));
+        assert!(storage.tags.is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use crate::ecs::Storage;
+use std::collections::{HashMap, HashSet};
+
+impl Storage {
+    /// Check the internal bookkeeping of the storage and collect every violated invariant:
+    ///  a) all columns of an archetype have the same length and match its component types
+    ///  b) the `component_index` lists exactly the archetypes that contain a component type
+    ///  c) every entity record points to an existing row of that entity and no two entities share
+    ///     a row
+    pub(crate) fn check_invariants(&self) -> Result<(), Vec<String>> {
+        let mut violations = Vec::new();
+
+        for (&archetype_id, archetype) in &self.archetypes {
+            if archetype.id != archetype_id {
+                violations.push(format!(
+                    "archetype {archetype_id} is stored with mismatching id {}",
+                    archetype.id
+                ));
+            }
+
+            if archetype_id >= self.archetype_id_counter {
+                violations.push(format!(
+                    "archetype {archetype_id} is not below the id counter {}",
+                    self.archetype_id_counter
+                ));
+            }
+
+            if archetype.types.len() != archetype.component_types.len() {
+                violations.push(format!(
+                    "archetype {archetype_id} has {} types but {} columns",
+                    archetype.types.len(),
+                    archetype.component_types.len()
+                ));
+            }
+
+            for (index, (type_id, column)) in archetype
+                .types
+                .iter()
+                .zip(&archetype.component_types)
+                .enumerate()
+            {
+                if column.element_type_id() != *type_id {
+                    violations.push(format!(
+                        "archetype {archetype_id} column {index} does not match its type id"
+                    ));
+                }
+
+                if !self
+                    .component_index
+                    .get(type_id)
+                    .is_some_and(|ids| ids.contains(&archetype_id))
+                {
+                    violations.push(format!(
+                        "archetype {archetype_id} column {index} is missing in the component index"
+                    ));
+                }
+            }
+
+            let unique_types = archetype.types.iter().collect::<HashSet<_>>();
+            if unique_types.len() != archetype.types.len() {
+                violations.push(format!(
+                    "archetype {archetype_id} contains a component type more than once"
+                ));
+            }
+
+            let lengths = archetype
+                .component_types
+                .iter()
+                .map(|column| column.len())
+                .collect::<Vec<_>>();
+            if lengths.windows(2).any(|pair| pair[0] != pair[1]) {
+                violations.push(format!(
+                    "archetype {archetype_id} has columns of different lengths {lengths:?}"
+                ));
+            }
+        }
+
+        for (type_id, archetype_ids) in &self.component_index {
+            let unique_ids = archetype_ids.iter().collect::<HashSet<_>>();
+            if unique_ids.len() != archetype_ids.len() {
+                violations.push(format!(
+                    "component index lists an archetype more than once: {archetype_ids:?}"
+                ));
+            }
+
+            for archetype_id in archetype_ids {
+                match self.archetypes.get(archetype_id) {
+                    None => violations.push(format!(
+                        "component index points to missing archetype {archetype_id}"
+                    )),
+                    Some(archetype) if !archetype.types.contains(type_id) => {
+                        violations.push(format!(
+                            "component index points to archetype {archetype_id}, which does not \
+                             contain the component type"
+                        ));
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let mut occupied_rows = HashMap::new();
+
+        for (&entity, record) in &self.entity_index {
+            let Some(archetype) = self.archetypes.get(&record.archetype_id) else {
+                violations.push(format!(
+                    "entity {entity} points to missing archetype {}",
+                    record.archetype_id
+                ));
+                continue;
+            };
+
+            let rows = archetype
+                .component_types
+                .first()
+                .map_or(0, |column| column.len());
+            if record.entity_row >= rows {
+                violations.push(format!(
+                    "entity {entity} points to row {} of archetype {}, which has {rows} rows",
+                    record.entity_row, record.archetype_id
+                ));
+            }
+
+            if archetype.entities.get(record.entity_row) != Some(&entity) {
+                violations.push(format!(
+                    "row {} of archetype {} does not belong to entity {entity}",
+                    record.entity_row, record.archetype_id
+                ));
+            }
+
+            if let Some(other) =
+                occupied_rows.insert((record.archetype_id, record.entity_row), entity)
+            {
+                violations.push(format!(
+                    "entities {other} and {entity} share row {} of archetype {}",
+                    record.entity_row, record.archetype_id
+                ));
+            }
+        }
+
+        for (&archetype_id, archetype) in &self.archetypes {
+            let rows = archetype
+                .component_types
+                .first()
+                .map_or(0, |column| column.len());
+            let entities = occupied_rows
+                .keys()
+                .filter(|(id, _)| *id == archetype_id)
+                .count();
+
+            if entities != rows || archetype.entities.len() != rows {
+                violations.push(format!(
+                    "archetype {archetype_id} has {rows} rows but {entities} entities"
+                ));
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Panic with a list of all violated invariants if the storage is in an inconsistent state.
+    /// With the `debug-validate` feature, this is called after every structural mutation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the invariants checked by [`Storage::check_invariants`] is violated.
+    pub(crate) fn validate(&self) {
+        if let Err(violations) = self.check_invariants() {
+            panic!(
+                "Internal storage error. Invariants violated:\n  - {}",
+                violations.join("\n  - ")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_storage_is_valid() {
+        assert_eq!(Storage::new().check_invariants(), Ok(()));
+    }
+
+    #[test]
+    fn structural_mutations_keep_storage_valid() {
+        let mut storage = Storage::new();
+
+        for entity in 0..4 {
+            storage.add_component_to_entity(entity, entity as i32);
+            storage.add_component_to_entity(entity, entity as f32);
+            storage.validate();
+        }
+
+        // moving entities out of the middle of an archetype swaps the last row into their place
+        storage.remove_component_from_entity::<f32>(1, &0.0);
+        storage.validate();
+        storage.remove_entity(0);
+        storage.validate();
+        storage.add_component_to_entity(2, 'x');
+        storage.validate();
+        storage.remove_component_from_entity::<i32>(3, &0);
+        storage.validate();
+        storage.remove_entity(2);
+        storage.validate();
+
+        // a new entity with a component type that only exists in larger archetypes
+        storage.add_component_to_entity(4, 'y');
+        storage.validate();
+    }
+
+    #[test]
+    fn check_invariants_reports_diverging_columns() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 5);
+        storage.add_component_to_entity(0, 42.0f32);
+
+        let archetype_id = storage.entity_index[&0].archetype_id;
+        storage
+            .archetypes
+            .get_mut(&archetype_id)
+            .unwrap()
+            .push_component(7);
+
+        let violations = storage.check_invariants().unwrap_err();
+
+        assert!(violations
+            .iter()
+            .any(|violation| violation.contains("columns of different lengths")));
+    }
+
+    #[test]
+    fn check_invariants_reports_stale_entity_records() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 5);
+        storage.add_component_to_entity(1, 6);
+
+        storage.entity_index.get_mut(&1).unwrap().entity_row = 0;
+
+        let violations = storage.check_invariants().unwrap_err();
+
+        assert!(violations
+            .iter()
+            .any(|violation| violation.contains("share row 0 of archetype 0")));
+        assert!(violations
+            .iter()
+            .any(|violation| violation.contains("has 2 rows but 1 entities")));
+    }
+
+    #[test]
+    #[should_panic(expected = "entity 0 points to missing archetype 7")]
+    fn validate_panics_with_violations() {
+        let mut storage = Storage::new();
+        storage.add_component_to_entity(0, 5);
+
+        storage.entity_index.get_mut(&0).unwrap().archetype_id = 7;
+
+        storage.validate();
+    }
+}
@@ -1,4 +1,7 @@
-use crate::ecs::{Storage, System};
+use crate::ecs::schedule::ScheduledSystem;
+use crate::ecs::{ExtractSystem, Storage, System};
+use std::collections::HashSet;
+use std::convert::Infallible;
 
 /// A unique id for an entity
 pub type EntityId = usize;
@@ -6,8 +9,14 @@ pub type EntityId = usize;
 /// The main struct that holds all the game state. The storage is responsible for managing the
 /// entities and components. The storage is then passed into every system.
 pub struct World {
-    pub(crate) systems: Vec<Box<dyn System>>,
+    pub(crate) systems: Vec<ScheduledSystem>,
+    pub(crate) startup_systems: Vec<Box<dyn System>>,
+    pub(crate) disabled_sets: HashSet<&'static str>,
+    pub(crate) extract_systems: Vec<Box<dyn ExtractSystem>>,
     pub storage: Storage,
+    /// The data of the renderer, filled by the [extract systems](World::add_extract_system) at
+    /// the end of each frame.
+    pub render_storage: Storage,
     pub(crate) entities_count: EntityId,
     // TODO: replace ggez dependencies with winit window loop and custom game loop logic
     // pub(crate) ggez_context: ggez::Context,
@@ -15,6 +24,112 @@ pub struct World {
 }
 
 impl World {
+    /// Create an empty world without any entities or systems.
+    ///
+    /// # Errors
+    ///
+    /// Initialization currently cannot fail, the `Result` is kept so that fallible setup (e.g.
+    /// window or device creation) can be added without breaking callers.
+    pub fn init() -> Result<Self, Infallible> {
+        Ok(Self {
+            systems: Vec::new(),
+            startup_systems: Vec::new(),
+            disabled_sets: HashSet::new(),
+            extract_systems: Vec::new(),
+            storage: Storage::new(),
+            render_storage: Storage::new(),
+            entities_count: 0,
+        })
+    }
+
+    /// Iterate over the ids of all entities that have at least one component, in no particular
+    /// order. Use [`Storage::component_types_of`] to inspect their components.
+    pub fn iter_entities(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.storage.entities()
+    }
+
+    /// Returns true if the entity exists, i.e. it was spawned and not removed yet. Use this to
+    /// check entities that are stored in components or resources before operating on them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct Target;
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let target = world.build_entity().with_component(Target).build();
+    /// assert!(world.contains(target));
+    ///
+    /// world.despawn(target);
+    /// assert!(!world.contains(target));
+    /// ```
+    #[must_use]
+    pub fn contains(&self, entity: EntityId) -> bool {
+        self.storage.contains(entity)
+    }
+
+    /// Remove all entities for which the predicate returns false, e.g. everything except
+    /// persistent entities at a level transition. An [`EntityDespawned`](crate::ecs::EntityDespawned)
+    /// event is sent for each removed entity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct Enemy;
+    /// struct Persistent;
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let player = world.build_entity().with_component(Persistent).build();
+    /// let _ = world.build_entity().with_component(Enemy).build();
+    ///
+    /// world.retain_entities(|entity, storage| storage.has::<Persistent>(entity));
+    ///
+    /// assert_eq!(world.iter_entities().collect::<Vec<_>>(), [player]);
+    /// ```
+    pub fn retain_entities(&mut self, mut predicate: impl FnMut(EntityId, &Storage) -> bool) {
+        let removed: Vec<_> = self
+            .storage
+            .entities()
+            .filter(|&entity| !predicate(entity, &self.storage))
+            .collect();
+
+        for entity in removed {
+            self.storage.remove_entity(entity);
+        }
+    }
+
+    /// Remove an entity with all its components and tags and send an
+    /// [`EntityDespawned`](crate::ecs::EntityDespawned) event. Entity ids are never reused, so the
+    /// id stays invalid afterwards and later lookups return None. Returns false if the entity did
+    /// not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use game_engine::ecs::World;
+    ///
+    /// struct Enemy;
+    ///
+    /// let mut world = World::init().expect("Failed to initialize world");
+    /// let enemy = world.build_entity().with_component(Enemy).build();
+    ///
+    /// assert!(world.despawn(enemy));
+    /// assert!(!world.storage.has::<Enemy>(enemy));
+    /// assert!(!world.despawn(enemy));
+    /// ```
+    pub fn despawn(&mut self, entity: EntityId) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+
+        self.storage.remove_entity(entity);
+        true
+    }
+
     /// Create a new entity and return its ID
     pub(crate) fn new_entity(&mut self) -> EntityId {
         let entity_id = self.entities_count;
@@ -23,3 +138,47 @@ impl World {
         entity_id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::EntityDespawned;
+
+    #[test]
+    fn retain_entities_removes_rejected_entities() {
+        let mut world = World::init().unwrap();
+        let kept = world.build_entity().with_component(1u32).build();
+        let removed = world.build_entity().with_component(2u32).build();
+
+        world.retain_entities(|entity, storage| storage.has::<u32>(entity) && entity == kept);
+
+        assert!(world.storage.contains(kept));
+        assert!(!world.storage.contains(removed));
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<EntityDespawned>()
+                .collect::<Vec<_>>(),
+            [EntityDespawned(removed)]
+        );
+    }
+
+    #[test]
+    fn despawned_ids_are_not_reused() {
+        struct Marker;
+
+        let mut world = World::init().unwrap();
+        let entity = world.build_entity().with_component(1u32).build();
+        world.storage.add_tag::<Marker>(entity);
+
+        assert!(world.despawn(entity));
+        assert!(!world.contains(entity));
+        assert!(!world.storage.has_tag::<Marker>(entity));
+        assert_eq!(world.storage.entity_count(), 0);
+
+        let next = world.build_entity().with_component(2u32).build();
+        assert_ne!(next, entity);
+        assert!(!world.despawn(entity));
+        assert!(world.contains(next));
+    }
+}
@@ -0,0 +1,215 @@
+//! # Engine
+//! The [`Engine`] is the entry point for games. It creates the [`World`], registers plugins and
+//! systems and owns the main loop:
+//!
+//! ```no_run
+//! use game_engine::ecs::{Storage, System};
+//! use game_engine::engine::{DefaultPlugins, Engine, WindowSettings};
+//!
+//! struct SpawnCamera;
+//!
+//! impl System for SpawnCamera {
+//!     fn new() -> Self {
+//!         Self
+//!     }
+//!
+//!     fn update(&mut self, storage: &mut Storage) {
+//!         // spawn the camera
+//!     }
+//! }
+//!
+//! Engine::new()
+//!     .with_window(WindowSettings::new("My Game", 1280, 720))
+//!     .add_plugin(DefaultPlugins)
+//!     .add_startup_system(SpawnCamera::new())
+//!     .run();
+//! ```
+use crate::clipboard::Clipboard;
+use crate::ecs::{System, World};
+use crate::game_loop;
+use crate::tasks::ComputePool;
+use crate::time::Time;
+
+/// The initial configuration of the game window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowSettings {
+    pub title: String,
+    /// The width of the drawable area in logical pixels.
+    pub width: u32,
+    /// The height of the drawable area in logical pixels.
+    pub height: u32,
+    pub resizable: bool,
+}
+
+impl WindowSettings {
+    #[must_use]
+    pub fn new(title: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            height,
+            resizable: true,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self::new("Game", 1280, 720)
+    }
+}
+
+/// A reusable bundle of systems and resources, e.g. everything a physics engine or an audio
+/// backend needs.
+pub trait Plugin {
+    fn build(&self, world: &mut World);
+}
+
+/// The resources most games need: the [`Time`], the [`Clipboard`] and the [`ComputePool`].
+pub struct DefaultPlugins;
+
+impl Plugin for DefaultPlugins {
+    fn build(&self, world: &mut World) {
+        if !world.storage.has_resource::<Time>() {
+            world.storage.insert_resource(Time::new());
+        }
+        if !world.storage.has_resource::<Clipboard>() {
+            world.storage.insert_resource(Clipboard::new());
+        }
+        if !world.storage.has_resource::<ComputePool>() {
+            world.storage.insert_resource(ComputePool::default());
+        }
+    }
+}
+
+/// Builds a [`World`] and runs it in a window, see the [module documentation](self).
+pub struct Engine {
+    world: World,
+    window: WindowSettings,
+}
+
+impl Engine {
+    #[must_use]
+    pub fn new() -> Self {
+        let Ok(world) = World::init();
+
+        Self {
+            world,
+            window: WindowSettings::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_window(mut self, settings: WindowSettings) -> Self {
+        self.window = settings;
+        self
+    }
+
+    #[must_use]
+    pub fn add_plugin(mut self, plugin: impl Plugin) -> Self {
+        plugin.build(&mut self.world);
+        self
+    }
+
+    /// Add a system that is updated every frame. Use [`Engine::world_mut`] and
+    /// [`World::add_system`] to schedule it differently.
+    #[must_use]
+    pub fn add_system<S: System + 'static>(mut self, system: S) -> Self {
+        self.world.add_system(system);
+        self
+    }
+
+    /// Add a system that is updated once before the first frame, see
+    /// [`World::add_startup_system`].
+    #[must_use]
+    pub fn add_startup_system<S: System + 'static>(mut self, system: S) -> Self {
+        self.world.add_startup_system(system);
+        self
+    }
+
+    #[must_use]
+    pub const fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// Open the window and [tick](World::tick) the world once per frame until the window is
+    /// closed.
+    pub fn run(self) {
+        game_loop::run_world(self.world, &self.window);
+    }
+
+    /// Hand the world to a custom main loop instead of opening a window, e.g. when the engine is
+    /// embedded into an editor or driven frame by frame in tests. The loop is responsible for
+    /// calling [`World::tick`].
+    ///
+    /// ```
+    /// use game_engine::engine::{DefaultPlugins, Engine};
+    /// use game_engine::time::Time;
+    /// use std::time::Duration;
+    ///
+    /// let frames = Engine::new()
+    ///     .add_plugin(DefaultPlugins)
+    ///     .run_with(|mut world| {
+    ///         for _ in 0..3 {
+    ///             world.tick(Duration::from_millis(16));
+    ///         }
+    ///         world.storage.get_resource::<Time>().unwrap().frame_count()
+    ///     });
+    ///
+    /// assert_eq!(frames, 3);
+    /// ```
+    pub fn run_with<R>(self, main_loop: impl FnOnce(World) -> R) -> R {
+        main_loop(self.world)
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Storage;
+    use std::time::Duration;
+
+    struct Spawner;
+
+    impl System for Spawner {
+        fn new() -> Self {
+            Self
+        }
+
+        fn update(&mut self, storage: &mut Storage) {
+            storage.insert_resource(Spawner);
+        }
+    }
+
+    #[test]
+    fn builder_registers_plugins_and_systems() {
+        let mut engine = Engine::new()
+            .with_window(WindowSettings::new("Test", 640, 480).with_resizable(false))
+            .add_plugin(DefaultPlugins)
+            .add_startup_system(Spawner::new());
+
+        assert_eq!(engine.window.title, "Test");
+        assert!(!engine.window.resizable);
+        assert!(engine.world().storage.has_resource::<Time>());
+        assert!(engine.world().storage.has_resource::<Clipboard>());
+
+        engine.world_mut().tick(Duration::from_millis(16));
+        assert!(engine.world().storage.has_resource::<Spawner>());
+    }
+}
@@ -1,6 +1,11 @@
+use crate::ecs::World;
+use crate::engine::WindowSettings;
+use crate::window::{self, WindowClose};
+use std::time::Instant;
 use winit::{
+    dpi::LogicalSize,
     event::*,
-    event_loop::EventLoop,
+    event_loop::{ControlFlow, EventLoop},
     keyboard::{KeyCode, PhysicalKey},
     window::WindowBuilder,
 };
@@ -8,6 +13,7 @@ use winit::{
 // lib.rs
 use winit::window::Window;
 
+#[allow(dead_code)]
 struct State<'a> {
     surface: wgpu::Surface<'a>,
     device: wgpu::Device,
@@ -20,21 +26,22 @@ struct State<'a> {
     window: &'a Window,
 }
 
+#[allow(dead_code)]
 impl<'a> State<'a> {
     // Creating some of the wgpu types requires async code
-    async fn new(window: &'a Window) -> State<'a> {
+    async fn new(_window: &'a Window) -> State<'a> {
         todo!()
     }
 
     pub fn window(&self) -> &Window {
-        &self.window
+        self.window
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+    fn resize(&mut self, _new_size: winit::dpi::PhysicalSize<u32>) {
         todo!()
     }
 
-    fn input(&mut self, event: &WindowEvent) -> bool {
+    fn input(&mut self, _event: &WindowEvent) -> bool {
         todo!()
     }
 
@@ -48,26 +55,58 @@ impl<'a> State<'a> {
 }
 
 pub fn run() {
+    let Ok(world) = World::init();
+
+    run_world(world, &WindowSettings::default());
+}
+
+/// Open a window and tick the world once per frame until the window is closed. Window events are
+/// forwarded into the world, see the [`window`](crate::window) module.
+pub(crate) fn run_world(mut world: World, settings: &WindowSettings) {
     let event_loop = EventLoop::new().unwrap();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let window = WindowBuilder::new()
+        .with_title(settings.title.as_str())
+        .with_inner_size(LogicalSize::new(settings.width, settings.height))
+        .with_resizable(settings.resizable)
+        .build(&event_loop)
+        .unwrap();
+    let mut last_frame = Instant::now();
 
+    if !world.storage.has_resource::<WindowClose>() {
+        world.storage.insert_resource(WindowClose::default());
+    }
+
+    event_loop.set_control_flow(ControlFlow::Poll);
     let _ = event_loop.run(move |event, control_flow| match event {
         Event::WindowEvent {
-            ref event,
+            event:
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            state: ElementState::Pressed,
+                            physical_key: PhysicalKey::Code(KeyCode::Escape),
+                            ..
+                        },
+                    ..
+                },
             window_id,
-        } if window_id == window.id() => match event {
-            WindowEvent::CloseRequested
-            | WindowEvent::KeyboardInput {
-                event:
-                    KeyEvent {
-                        state: ElementState::Pressed,
-                        physical_key: PhysicalKey::Code(KeyCode::Escape),
-                        ..
-                    },
-                ..
-            } => control_flow.exit(),
-            _ => {}
-        },
+        } if window_id == window.id() => window::request_close(&mut world.storage),
+        Event::WindowEvent { event, window_id } if window_id == window.id() => {
+            window::send_window_event(&mut world.storage, &event);
+        }
+        Event::AboutToWait => {
+            let now = Instant::now();
+            world.tick(now - last_frame);
+            last_frame = now;
+
+            if world
+                .storage
+                .get_resource_mut::<WindowClose>()
+                .is_some_and(WindowClose::finish_frame)
+            {
+                control_flow.exit();
+            }
+        }
         _ => {}
     });
 }
@@ -0,0 +1,393 @@
+//! # Jobs
+//! A [`JobGraph`] splits the work of a frame into named jobs with explicit dependencies, which are
+//! executed on the workers of the [`ComputePool`]. A job starts as soon as all of its dependencies
+//! have finished, so independent chains run in parallel:
+//!
+//! ```
+//! use game_engine::jobs::JobGraph;
+//! use game_engine::tasks::ComputePool;
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//!
+//! let pool = ComputePool::default();
+//! let visible = AtomicUsize::new(0);
+//! let mut jobs = JobGraph::new();
+//!
+//! jobs.add_job("animation", &[], || { /* advance animations */ })
+//!     .unwrap();
+//! jobs.add_job("skinning", &["animation"], || { /* compute skinned meshes */ })
+//!     .unwrap();
+//! jobs.add_job("bounds", &["skinning"], || { /* update bounding boxes */ })
+//!     .unwrap();
+//! jobs.add_job("culling", &["bounds"], || {
+//!     visible.store(42, Ordering::Relaxed);
+//! })
+//! .unwrap();
+//!
+//! jobs.run(&pool);
+//! assert_eq!(visible.load(Ordering::Relaxed), 42);
+//! ```
+//!
+//! Jobs may borrow data from the surrounding scope, e.g. from the [`Storage`](crate::ecs::Storage)
+//! inside a system, since [`JobGraph::run`] only returns after all jobs have finished.
+use crate::tasks::ComputePool;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+type JobFn<'a> = Box<dyn FnOnce() + Send + 'a>;
+type ProfilerFn<'a> = Box<dyn Fn(&JobProfile) + Send + Sync + 'a>;
+
+/// Timing information of a finished job, passed to the [profiler](JobGraph::with_profiler).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobProfile {
+    pub name: &'static str,
+    /// The index of the thread that executed the job, 0 for the thread that called
+    /// [`JobGraph::run`].
+    pub worker: usize,
+    pub start: Instant,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobError {
+    /// A job with this name was already added.
+    DuplicateJob(&'static str),
+    /// A dependency has to be added before the jobs that depend on it.
+    UnknownDependency {
+        job: &'static str,
+        dependency: &'static str,
+    },
+}
+
+impl Display for JobError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateJob(name) => write!(f, "job {name} was already added"),
+            Self::UnknownDependency { job, dependency } => {
+                write!(f, "job {job} depends on unknown job {dependency}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+struct Job<'a> {
+    name: &'static str,
+    run: JobFn<'a>,
+    dependents: Vec<usize>,
+    open_dependencies: usize,
+}
+
+/// A set of jobs and their dependencies, see the [module documentation](self). Since
+/// dependencies have to be added before the jobs that depend on them, the graph never contains
+/// cycles.
+#[derive(Default)]
+pub struct JobGraph<'a> {
+    jobs: Vec<Job<'a>>,
+    ids: HashMap<&'static str, usize>,
+    profiler: Option<ProfilerFn<'a>>,
+    workers: Option<NonZeroUsize>,
+}
+
+impl<'a> JobGraph<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call the profiler after each finished job, e.g. to record the frame timings.
+    #[must_use]
+    pub fn with_profiler(mut self, profiler: impl Fn(&JobProfile) + Send + Sync + 'a) -> Self {
+        self.profiler = Some(Box::new(profiler));
+        self
+    }
+
+    /// Limit the number of threads, including the thread that calls [`JobGraph::run`]. By default,
+    /// as many threads as the pool has workers are used.
+    #[must_use]
+    pub const fn with_workers(mut self, workers: NonZeroUsize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Add a job that runs after all jobs in `dependencies` have finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a job with the same name was already added or if a dependency was not
+    /// added yet.
+    pub fn add_job(
+        &mut self,
+        name: &'static str,
+        dependencies: &[&'static str],
+        job: impl FnOnce() + Send + 'a,
+    ) -> Result<(), JobError> {
+        if self.ids.contains_key(name) {
+            return Err(JobError::DuplicateJob(name));
+        }
+
+        let dependency_ids = dependencies
+            .iter()
+            .map(|&dependency| {
+                self.ids
+                    .get(dependency)
+                    .copied()
+                    .ok_or(JobError::UnknownDependency {
+                        job: name,
+                        dependency,
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let id = self.jobs.len();
+        for &dependency in &dependency_ids {
+            self.jobs[dependency].dependents.push(id);
+        }
+
+        self.ids.insert(name, id);
+        self.jobs.push(Job {
+            name,
+            run: Box::new(job),
+            dependents: Vec::new(),
+            open_dependencies: dependency_ids.len(),
+        });
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Execute all jobs on the calling thread and the workers of the pool and wait until they are
+    /// finished. No threads are started, so the graph can be run every frame. Workers that are
+    /// busy with other tasks join in once they are free.
+    ///
+    /// # Panics
+    ///
+    /// If a job panics, no further jobs are started and the panic is resumed on the calling thread
+    /// once the running jobs have finished.
+    pub fn run(self, pool: &ComputePool) {
+        if self.jobs.is_empty() {
+            return;
+        }
+
+        let workers = self
+            .workers
+            .map_or(pool.workers(), NonZeroUsize::get)
+            .min(self.jobs.len());
+
+        let mut state = SchedulerState {
+            ready: VecDeque::new(),
+            dependents: Vec::with_capacity(self.jobs.len()),
+            open_dependencies: Vec::with_capacity(self.jobs.len()),
+            jobs: Vec::with_capacity(self.jobs.len()),
+            remaining: self.jobs.len(),
+            panic: None,
+        };
+        for (id, job) in self.jobs.into_iter().enumerate() {
+            if job.open_dependencies == 0 {
+                state.ready.push_back(id);
+            }
+            state.dependents.push(job.dependents);
+            state.open_dependencies.push(job.open_dependencies);
+            state.jobs.push(Some((job.name, job.run)));
+        }
+
+        let scheduler = Scheduler {
+            state: Mutex::new(state),
+            changed: Condvar::new(),
+            profiler: self.profiler,
+        };
+
+        pool.scope(workers.saturating_sub(1), &|worker| scheduler.work(worker));
+
+        let state = scheduler
+            .state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(payload) = state.panic {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+struct SchedulerState<'a> {
+    ready: VecDeque<usize>,
+    dependents: Vec<Vec<usize>>,
+    open_dependencies: Vec<usize>,
+    jobs: Vec<Option<(&'static str, JobFn<'a>)>>,
+    remaining: usize,
+    panic: Option<Box<dyn std::any::Any + Send>>,
+}
+
+struct Scheduler<'a> {
+    state: Mutex<SchedulerState<'a>>,
+    changed: Condvar,
+    profiler: Option<ProfilerFn<'a>>,
+}
+
+impl<'a> Scheduler<'a> {
+    fn work(&self, worker: usize) {
+        loop {
+            let (id, name, job) = {
+                let mut state = self.lock();
+
+                loop {
+                    if state.remaining == 0 || state.panic.is_some() {
+                        return;
+                    }
+                    if let Some(id) = state.ready.pop_front() {
+                        let (name, job) = state.jobs[id].take().expect("Job was already started");
+                        break (id, name, job);
+                    }
+                    state = self
+                        .changed
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            };
+
+            let start = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            let duration = start.elapsed();
+
+            if let (Ok(()), Some(profiler)) = (&result, &self.profiler) {
+                profiler(&JobProfile {
+                    name,
+                    worker,
+                    start,
+                    duration,
+                });
+            }
+
+            let mut state = self.lock();
+            match result {
+                Ok(()) => {
+                    state.remaining -= 1;
+                    for dependent in std::mem::take(&mut state.dependents[id]) {
+                        state.open_dependencies[dependent] -= 1;
+                        if state.open_dependencies[dependent] == 0 {
+                            state.ready.push_back(dependent);
+                        }
+                    }
+                }
+                Err(payload) => {
+                    state.panic.get_or_insert(payload);
+                }
+            }
+            drop(state);
+            self.changed.notify_all();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SchedulerState<'a>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::thread;
+
+    #[test]
+    fn jobs_run_after_their_dependencies() {
+        let order = Mutex::new(Vec::new());
+        let record = |name| {
+            let order = &order;
+            move || order.lock().unwrap().push(name)
+        };
+
+        let mut jobs = JobGraph::new();
+        jobs.add_job("a", &[], record("a")).unwrap();
+        jobs.add_job("b", &["a"], record("b")).unwrap();
+        jobs.add_job("c", &["a"], record("c")).unwrap();
+        jobs.add_job("d", &["b", "c"], record("d")).unwrap();
+        jobs.run(&ComputePool::default());
+
+        let order = order.into_inner().unwrap();
+        let position = |name| order.iter().position(|&n| n == name).unwrap();
+        assert_eq!(order.len(), 4);
+        assert!(position("a") < position("b"));
+        assert!(position("a") < position("c"));
+        assert!(position("b") < position("d"));
+        assert!(position("c") < position("d"));
+    }
+
+    #[test]
+    fn add_job_rejects_invalid_graphs() {
+        let mut jobs = JobGraph::new();
+        jobs.add_job("a", &[], || {}).unwrap();
+
+        assert_eq!(
+            jobs.add_job("a", &[], || {}),
+            Err(JobError::DuplicateJob("a"))
+        );
+        assert_eq!(
+            jobs.add_job("b", &["c"], || {}),
+            Err(JobError::UnknownDependency {
+                job: "b",
+                dependency: "c"
+            })
+        );
+        assert_eq!(jobs.len(), 1);
+    }
+
+    #[test]
+    fn profiler_is_called_for_each_job() {
+        let profiles = Mutex::new(Vec::new());
+        let mut jobs = JobGraph::new()
+            .with_workers(NonZeroUsize::new(2).unwrap())
+            .with_profiler(|profile| profiles.lock().unwrap().push(profile.name));
+
+        for name in ["a", "b", "c"] {
+            jobs.add_job(name, &[], || {}).unwrap();
+        }
+        jobs.run(&ComputePool::default());
+
+        let mut profiles = profiles.into_inner().unwrap();
+        profiles.sort_unstable();
+        assert_eq!(profiles, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn runs_reuse_the_threads_of_the_pool() {
+        let pool = ComputePool::new(NonZeroUsize::new(2).unwrap());
+        let threads = Mutex::new(HashSet::new());
+
+        for _ in 0..10 {
+            let mut jobs = JobGraph::new();
+            for name in ["a", "b", "c", "d"] {
+                jobs.add_job(name, &[], || {
+                    threads.lock().unwrap().insert(thread::current().id());
+                })
+                .unwrap();
+            }
+            jobs.run(&pool);
+        }
+
+        // the calling thread and the two workers of the pool
+        assert!(threads.into_inner().unwrap().len() <= 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "job failed")]
+    fn panics_are_resumed_on_the_caller() {
+        let mut jobs = JobGraph::new();
+        jobs.add_job("a", &[], || panic!("job failed")).unwrap();
+        jobs.add_job("b", &["a"], || {}).unwrap();
+        jobs.run(&ComputePool::default());
+    }
+}
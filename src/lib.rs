@@ -1,2 +1,19 @@
-pub mod game_loop;
+pub mod animation;
+pub mod camera;
+pub mod clipboard;
+pub mod color;
+pub mod console;
+pub mod coroutine;
+pub mod cvar;
 pub mod ecs;
+pub mod engine;
+pub mod game_loop;
+pub mod jobs;
+pub mod math;
+pub mod net;
+pub mod path;
+pub mod settings;
+pub mod tasks;
+pub mod time;
+pub mod tween;
+pub mod window;
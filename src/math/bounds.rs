@@ -0,0 +1,348 @@
+use crate::math::{Affine2, Vec2};
+
+/// An axis-aligned rectangle, given by its minimum and maximum corner. This is used for screen
+/// regions and UI layouts as well as for axis-aligned bounding boxes, see [`Aabb2d`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+/// An axis-aligned bounding box in 2D.
+pub type Aabb2d = Rect;
+
+impl Rect {
+    /// A rectangle that contains nothing. It is the neutral element of [`Rect::union`].
+    pub const EMPTY: Self = Self {
+        min: Vec2::INFINITY,
+        max: Vec2::NEG_INFINITY,
+    };
+
+    /// A rectangle between two opposite corners, in any order.
+    #[must_use]
+    pub fn from_corners(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    #[must_use]
+    pub fn from_center_size(center: Vec2, size: Vec2) -> Self {
+        Self::from_center_half_size(center, size / 2.0)
+    }
+
+    #[must_use]
+    pub fn from_center_half_size(center: Vec2, half_size: Vec2) -> Self {
+        let half_size = half_size.abs();
+
+        Self {
+            min: center - half_size,
+            max: center + half_size,
+        }
+    }
+
+    /// The smallest rectangle containing all points, or [`Rect::EMPTY`] if there are none.
+    #[must_use]
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        points.into_iter().fold(Self::EMPTY, Self::union_point)
+    }
+
+    #[must_use]
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    #[must_use]
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    #[must_use]
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    #[must_use]
+    pub fn half_size(&self) -> Vec2 {
+        self.size() / 2.0
+    }
+
+    #[must_use]
+    pub fn center(&self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Returns true if the rectangle has no area.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.min.cmpge(self.max).any()
+    }
+
+    /// Returns true if the point is inside the rectangle or on its edge.
+    #[must_use]
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmple(self.max).all()
+    }
+
+    /// Returns true if the other rectangle is completely inside this rectangle.
+    #[must_use]
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    /// Returns true if the rectangles overlap. Rectangles that only touch do not intersect.
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The overlapping area of both rectangles, which [is empty](Rect::is_empty) if they do not
+    /// intersect.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.max(other.min),
+            max: self.max.min(other.max),
+        }
+    }
+
+    /// The smallest rectangle containing both rectangles.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// The smallest rectangle containing this rectangle and the point.
+    #[must_use]
+    pub fn union_point(self, point: Vec2) -> Self {
+        Self {
+            min: self.min.min(point),
+            max: self.max.max(point),
+        }
+    }
+
+    /// Grow the rectangle by the margin on all sides. Negative margins shrink it.
+    #[must_use]
+    pub fn inflate(&self, margin: f32) -> Self {
+        Self {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// The point inside the rectangle that is closest to the given point.
+    #[must_use]
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        point.clamp(self.min, self.max)
+    }
+
+    /// The four corners, counterclockwise starting at the minimum.
+    #[must_use]
+    pub fn corners(&self) -> [Vec2; 4] {
+        [
+            self.min,
+            Vec2::new(self.max.x, self.min.y),
+            self.max,
+            Vec2::new(self.min.x, self.max.y),
+        ]
+    }
+
+    /// The axis-aligned bounding box of the transformed rectangle. If the transformation contains
+    /// a rotation, the result is larger than the rectangle itself.
+    #[must_use]
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        Self::from_points(
+            self.corners()
+                .map(|corner| transform.transform_point2(corner)),
+        )
+    }
+}
+
+/// A circle, given by its center and radius.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+impl Circle {
+    #[must_use]
+    pub const fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// Returns true if the point is inside the circle or on its edge.
+    #[must_use]
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    /// Returns true if the other circle is completely inside this circle.
+    #[must_use]
+    pub fn contains_circle(&self, other: &Self) -> bool {
+        self.center.distance(other.center) + other.radius <= self.radius
+    }
+
+    /// Returns true if the circles overlap. Circles that only touch do not intersect.
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        let radii = self.radius + other.radius;
+
+        self.center.distance_squared(other.center) < radii * radii
+    }
+
+    /// Returns true if the circle and the rectangle overlap.
+    #[must_use]
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        rect.closest_point(self.center)
+            .distance_squared(self.center)
+            < self.radius * self.radius
+    }
+
+    /// The smallest circle containing both circles.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        if self.contains_circle(other) {
+            return *self;
+        }
+        if other.contains_circle(self) {
+            return *other;
+        }
+
+        let distance = self.center.distance(other.center);
+        let radius = (distance + self.radius + other.radius) / 2.0;
+        let direction = (other.center - self.center) / distance;
+
+        Self {
+            center: self.center + direction * (radius - self.radius),
+            radius,
+        }
+    }
+
+    /// The axis-aligned bounding box of the circle.
+    #[must_use]
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_center_half_size(self.center, Vec2::splat(self.radius))
+    }
+
+    /// A circle containing the transformed circle. Non-uniform scaling turns a circle into an
+    /// ellipse, in this case the result is the circle around that ellipse.
+    #[must_use]
+    pub fn transform(&self, transform: &Affine2) -> Self {
+        let scale = transform
+            .matrix2
+            .x_axis
+            .length()
+            .max(transform.matrix2.y_axis.length());
+
+        Self {
+            center: transform.transform_point2(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn rect_constructors_normalize_corners() {
+        let rect = Rect::from_corners(Vec2::new(4.0, 1.0), Vec2::new(0.0, 3.0));
+
+        assert_eq!(rect.min, Vec2::new(0.0, 1.0));
+        assert_eq!(rect.max, Vec2::new(4.0, 3.0));
+        assert_eq!(rect.size(), Vec2::new(4.0, 2.0));
+        assert_eq!(rect.center(), Vec2::new(2.0, 2.0));
+        assert_eq!(Rect::from_center_size(rect.center(), rect.size()), rect);
+        assert_eq!(
+            Rect::from_points([
+                Vec2::new(4.0, 3.0),
+                Vec2::new(0.0, 1.0),
+                Vec2::new(1.0, 2.0)
+            ]),
+            rect
+        );
+        assert!(Rect::from_points([]).is_empty());
+    }
+
+    #[test]
+    fn rect_containment_and_intersection() {
+        let rect = Rect::from_corners(Vec2::ZERO, Vec2::splat(2.0));
+        let overlapping = Rect::from_corners(Vec2::ONE, Vec2::splat(3.0));
+        let touching = Rect::from_corners(Vec2::new(2.0, 0.0), Vec2::new(3.0, 2.0));
+
+        assert!(rect.contains(Vec2::splat(2.0)));
+        assert!(!rect.contains(Vec2::new(2.1, 1.0)));
+        assert!(rect.contains_rect(&rect.inflate(-0.5)));
+        assert!(!rect.contains_rect(&overlapping));
+
+        assert!(rect.intersects(&overlapping));
+        assert!(!rect.intersects(&touching));
+        assert_eq!(
+            rect.intersection(&overlapping),
+            Rect::from_corners(Vec2::ONE, Vec2::splat(2.0))
+        );
+        assert_eq!(
+            rect.union(&overlapping),
+            Rect::from_corners(Vec2::ZERO, Vec2::splat(3.0))
+        );
+        assert_eq!(Rect::EMPTY.union(&rect), rect);
+    }
+
+    #[test]
+    fn rect_transform_returns_bounding_box() {
+        let rect = Rect::from_center_size(Vec2::ZERO, Vec2::splat(2.0));
+        let transform =
+            Affine2::from_scale_angle_translation(Vec2::splat(2.0), FRAC_PI_4, Vec2::new(5.0, 0.0));
+
+        let transformed = rect.transform(&transform);
+        let half_diagonal = 2.0 * 2.0f32.sqrt();
+
+        assert!(transformed.center().abs_diff_eq(Vec2::new(5.0, 0.0), 1e-5));
+        assert!(transformed
+            .half_size()
+            .abs_diff_eq(Vec2::splat(half_diagonal), 1e-5));
+    }
+
+    #[test]
+    fn circle_containment_and_intersection() {
+        let circle = Circle::new(Vec2::ZERO, 2.0);
+
+        assert!(circle.contains(Vec2::new(0.0, 2.0)));
+        assert!(!circle.contains(Vec2::new(1.5, 1.5)));
+        assert!(circle.contains_circle(&Circle::new(Vec2::X, 1.0)));
+        assert!(circle.intersects(&Circle::new(Vec2::new(3.0, 0.0), 1.5)));
+        assert!(!circle.intersects(&Circle::new(Vec2::new(3.0, 0.0), 1.0)));
+
+        let rect = Rect::from_corners(Vec2::new(1.5, 1.5), Vec2::splat(3.0));
+        assert!(!circle.intersects_rect(&rect));
+        assert!(circle.intersects_rect(&rect.inflate(0.2)));
+        assert_eq!(
+            circle.bounding_rect(),
+            Rect::from_corners(Vec2::splat(-2.0), Vec2::splat(2.0))
+        );
+    }
+
+    #[test]
+    fn circle_union_and_transform() {
+        let left = Circle::new(Vec2::new(-2.0, 0.0), 1.0);
+        let right = Circle::new(Vec2::new(3.0, 0.0), 2.0);
+
+        let union = left.union(&right);
+        assert!(union.center.abs_diff_eq(Vec2::new(1.0, 0.0), 1e-5));
+        assert!((union.radius - 4.0).abs() < 1e-5);
+        assert_eq!(union.union(&left), union);
+
+        let transform = Affine2::from_scale_angle_translation(Vec2::new(1.0, 3.0), 0.0, Vec2::Y);
+        assert_eq!(
+            left.transform(&transform),
+            Circle::new(Vec2::new(-2.0, 1.0), 3.0)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::math::{Vec2, Vec3};
+use std::ops::{Add, Mul, Sub};
+
+/// A point type that curves can be built from.
+pub trait CurvePoint:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self> + 'static
+{
+    fn distance(self, other: Self) -> f32;
+}
+
+impl CurvePoint for Vec2 {
+    fn distance(self, other: Self) -> f32 {
+        Self::distance(self, other)
+    }
+}
+
+impl CurvePoint for Vec3 {
+    fn distance(self, other: Self) -> f32 {
+        Self::distance(self, other)
+    }
+}
+
+/// A curve made of cubic Bézier segments that are joined end to end. Each segment is given by its
+/// four control points. Bézier, Hermite and Catmull-Rom splines can all be expressed this way, so
+/// they share this type and only differ in how they are constructed.
+///
+/// Curves are parameterized by `t` from 0.0 to the number of segments, with segment `i` covering
+/// `i..=i + 1`. This does not correspond to the distance along the curve, use an
+/// [`ArcLengthCurve`] for that.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubicCurve<P> {
+    segments: Vec<[P; 4]>,
+}
+
+impl<P: CurvePoint> CubicCurve<P> {
+    /// A Bézier spline from the control points of its segments. The curve passes through the
+    /// first and last control point of every segment and is pulled towards the other two.
+    #[must_use]
+    pub fn bezier(segments: impl Into<Vec<[P; 4]>>) -> Self {
+        Self {
+            segments: segments.into(),
+        }
+    }
+
+    /// A Hermite spline through the given points, leaving each point with the given tangent.
+    #[must_use]
+    pub fn hermite(points: &[(P, P)]) -> Self {
+        let segments = points
+            .windows(2)
+            .map(|pair| {
+                let (start, start_tangent) = pair[0];
+                let (end, end_tangent) = pair[1];
+
+                [
+                    start,
+                    start + start_tangent * (1.0 / 3.0),
+                    end - end_tangent * (1.0 / 3.0),
+                    end,
+                ]
+            })
+            .collect();
+
+        Self { segments }
+    }
+
+    /// A Catmull-Rom spline that passes through all given points. The tangent at each point is
+    /// derived from its neighbours, so the curve is smooth without specifying tangents. The first
+    /// and last point are used as their own neighbours.
+    #[must_use]
+    pub fn catmull_rom(points: &[P]) -> Self {
+        if points.len() < 2 {
+            return Self {
+                segments: Vec::new(),
+            };
+        }
+
+        let tangents: Vec<_> = (0..points.len())
+            .map(|index| {
+                let previous = points[index.saturating_sub(1)];
+                let next = points[(index + 1).min(points.len() - 1)];
+
+                (next - previous) * 0.5
+            })
+            .collect();
+
+        let points: Vec<_> = points.iter().copied().zip(tangents).collect();
+
+        Self::hermite(&points)
+    }
+
+    #[must_use]
+    pub fn segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    #[must_use]
+    pub fn segments(&self) -> &[[P; 4]] {
+        &self.segments
+    }
+
+    /// The position on the curve at `t`, which is clamped to the range of the curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the curve has no segments.
+    #[must_use]
+    pub fn position(&self, t: f32) -> P {
+        let ([p0, p1, p2, p3], t) = self.segment_at(t);
+        let u = 1.0 - t;
+
+        p0 * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) + p3 * (t * t * t)
+    }
+
+    /// The derivative of the curve at `t`, which points in the direction of movement along the
+    /// curve. The parameter is clamped to the range of the curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the curve has no segments.
+    #[must_use]
+    pub fn velocity(&self, t: f32) -> P {
+        let ([p0, p1, p2, p3], t) = self.segment_at(t);
+        let u = 1.0 - t;
+
+        (p1 - p0) * (3.0 * u * u) + (p2 - p1) * (6.0 * u * t) + (p3 - p2) * (3.0 * t * t)
+    }
+
+    /// Build a lookup table to sample the curve by distance. More samples per segment make the
+    /// sampling more accurate.
+    #[must_use]
+    pub fn arc_length_parameterize(self, samples_per_segment: usize) -> ArcLengthCurve<P> {
+        ArcLengthCurve::new(self, samples_per_segment)
+    }
+
+    fn segment_at(&self, t: f32) -> ([P; 4], f32) {
+        assert!(!self.segments.is_empty(), "Curve has no segments.");
+
+        let last = self.segments.len() - 1;
+        let t = t.clamp(0.0, self.segments.len() as f32);
+        // truncation is intended, t is non-negative and the index is clamped to the last segment
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let index = (t as usize).min(last);
+
+        (self.segments[index], t - index as f32)
+    }
+}
+
+/// A curve that is sampled by the distance travelled along it instead of the curve parameter, so
+/// that moving along it at a constant rate results in a constant speed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLengthCurve<P> {
+    curve: CubicCurve<P>,
+    /// Pairs of curve parameter and the distance along the curve at that parameter, sorted by both.
+    table: Vec<(f32, f32)>,
+}
+
+impl<P: CurvePoint> ArcLengthCurve<P> {
+    fn new(curve: CubicCurve<P>, samples_per_segment: usize) -> Self {
+        let samples = curve.segment_count() * samples_per_segment.max(1);
+        let mut table = Vec::with_capacity(samples + 1);
+
+        if samples > 0 {
+            let mut previous = curve.position(0.0);
+            let mut length = 0.0;
+            table.push((0.0, 0.0));
+
+            for sample in 1..=samples {
+                let t = sample as f32 / samples as f32 * curve.segment_count() as f32;
+                let position = curve.position(t);
+
+                length += previous.distance(position);
+                previous = position;
+                table.push((t, length));
+            }
+        }
+
+        Self { curve, table }
+    }
+
+    /// The approximate length of the curve.
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        self.table.last().map_or(0.0, |&(_, length)| length)
+    }
+
+    #[must_use]
+    pub const fn curve(&self) -> &CubicCurve<P> {
+        &self.curve
+    }
+
+    /// The curve parameter at the given distance from the start of the curve. The distance is
+    /// clamped to the length of the curve.
+    #[must_use]
+    pub fn parameter_at(&self, distance: f32) -> f32 {
+        let distance = distance.clamp(0.0, self.length());
+        let index = self
+            .table
+            .partition_point(|&(_, length)| length < distance)
+            .clamp(1, self.table.len().max(2) - 1);
+
+        let Some(&(t1, length1)) = self.table.get(index) else {
+            return 0.0;
+        };
+        let (t0, length0) = self.table[index - 1];
+
+        if length1 > length0 {
+            t0 + (t1 - t0) * (distance - length0) / (length1 - length0)
+        } else {
+            t0
+        }
+    }
+
+    /// The position at the given distance from the start of the curve.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the curve has no segments.
+    #[must_use]
+    pub fn position_at(&self, distance: f32) -> P {
+        self.curve.position(self.parameter_at(distance))
+    }
+
+    /// The direction of movement at the given distance from the start of the curve. This is not
+    /// normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the curve has no segments.
+    #[must_use]
+    pub fn velocity_at(&self, distance: f32) -> P {
+        self.curve.velocity(self.parameter_at(distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bezier_passes_through_segment_end_points() {
+        let curve = CubicCurve::bezier([
+            [Vec2::ZERO, Vec2::Y, Vec2::new(1.0, 1.0), Vec2::X],
+            [
+                Vec2::X,
+                Vec2::new(1.0, -1.0),
+                Vec2::new(2.0, -1.0),
+                Vec2::new(2.0, 0.0),
+            ],
+        ]);
+
+        assert_eq!(curve.segment_count(), 2);
+        assert_eq!(curve.position(0.0), Vec2::ZERO);
+        assert_eq!(curve.position(1.0), Vec2::X);
+        assert_eq!(curve.position(2.0), Vec2::new(2.0, 0.0));
+        assert_eq!(curve.position(5.0), Vec2::new(2.0, 0.0));
+        assert!(curve.position(0.5).abs_diff_eq(Vec2::new(0.5, 0.75), 1e-5));
+    }
+
+    #[test]
+    fn hermite_follows_tangents() {
+        let curve = CubicCurve::hermite(&[(Vec2::ZERO, Vec2::X), (Vec2::new(1.0, 1.0), Vec2::Y)]);
+
+        assert!(curve.velocity(0.0).abs_diff_eq(Vec2::X, 1e-5));
+        assert!(curve.velocity(1.0).abs_diff_eq(Vec2::Y, 1e-5));
+        assert!(curve.position(1.0).abs_diff_eq(Vec2::new(1.0, 1.0), 1e-5));
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_all_points() {
+        let points = [
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(2.0, 1.0, 1.0),
+        ];
+        let curve = CubicCurve::catmull_rom(&points);
+
+        assert_eq!(curve.segment_count(), 3);
+        for (index, point) in points.iter().enumerate() {
+            assert!(curve.position(index as f32).abs_diff_eq(*point, 1e-5));
+        }
+
+        assert_eq!(CubicCurve::catmull_rom(&[Vec3::ZERO]).segment_count(), 0);
+    }
+
+    #[test]
+    fn arc_length_curve_samples_by_distance() {
+        // the inner control points are bunched at the start, so the parameter is not uniform
+        let curve = CubicCurve::bezier([[
+            Vec2::ZERO,
+            Vec2::new(0.1, 0.0),
+            Vec2::new(0.2, 0.0),
+            Vec2::new(10.0, 0.0),
+        ]])
+        .arc_length_parameterize(64);
+
+        assert!((curve.length() - 10.0).abs() < 1e-3);
+        assert!(curve.position_at(0.0).abs_diff_eq(Vec2::ZERO, 1e-5));
+        assert!(curve
+            .position_at(2.5)
+            .abs_diff_eq(Vec2::new(2.5, 0.0), 1e-2));
+        assert!(curve
+            .position_at(7.5)
+            .abs_diff_eq(Vec2::new(7.5, 0.0), 1e-2));
+        assert!(curve
+            .position_at(20.0)
+            .abs_diff_eq(Vec2::new(10.0, 0.0), 1e-5));
+        assert!(curve.velocity_at(5.0).x > 0.0);
+    }
+
+    #[test]
+    fn arc_length_of_empty_curve_is_zero() {
+        let curve = CubicCurve::<Vec2>::catmull_rom(&[]).arc_length_parameterize(8);
+
+        assert_eq!(curve.length(), 0.0);
+        assert_eq!(curve.parameter_at(1.0), 0.0);
+    }
+}
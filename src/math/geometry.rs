@@ -0,0 +1,345 @@
+use crate::math::{Circle, Rect, Vec2};
+
+/// Tolerance for treating nearly parallel segments as parallel.
+const EPSILON: f32 = 1e-6;
+
+/// A straight line between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Segment {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Segment {
+    #[must_use]
+    pub const fn new(start: Vec2, end: Vec2) -> Self {
+        Self { start, end }
+    }
+
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    /// The point on the segment that is closest to the given point.
+    #[must_use]
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        let direction = self.end - self.start;
+        let length_squared = direction.length_squared();
+
+        if length_squared == 0.0 {
+            return self.start;
+        }
+
+        let t = ((point - self.start).dot(direction) / length_squared).clamp(0.0, 1.0);
+        self.start + direction * t
+    }
+
+    #[must_use]
+    pub fn distance_to_point(&self, point: Vec2) -> f32 {
+        self.closest_point(point).distance(point)
+    }
+
+    /// The point where both segments meet, including their end points. If the segments are
+    /// collinear and overlap, this is the point of the overlap that is closest to the start of
+    /// this segment.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Vec2> {
+        let direction = self.end - self.start;
+        let other_direction = other.end - other.start;
+        let offset = other.start - self.start;
+        let denominator = direction.perp_dot(other_direction);
+
+        if denominator.abs() > EPSILON {
+            let t = offset.perp_dot(other_direction) / denominator;
+            let u = offset.perp_dot(direction) / denominator;
+
+            return ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u))
+                .then(|| self.start + direction * t);
+        }
+
+        // parallel segments only meet if they are on the same line
+        if offset.perp_dot(direction).abs() > EPSILON
+            || offset.perp_dot(other_direction).abs() > EPSILON
+        {
+            return None;
+        }
+
+        let length_squared = direction.length_squared();
+        if length_squared == 0.0 {
+            return (other.distance_to_point(self.start) <= EPSILON).then_some(self.start);
+        }
+
+        let t0 = offset.dot(direction) / length_squared;
+        let t1 = (other.end - self.start).dot(direction) / length_squared;
+        let start = t0.min(t1).max(0.0);
+        let end = t0.max(t1).min(1.0);
+
+        (start <= end).then(|| self.start + direction * start)
+    }
+
+    /// Returns true if the segments meet in at least one point.
+    #[must_use]
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+}
+
+/// A simple polygon, given by its vertices in order. The last vertex is connected to the first.
+/// Polygons may be concave, but their edges must not cross each other.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Polygon {
+    vertices: Vec<Vec2>,
+}
+
+impl Polygon {
+    #[must_use]
+    pub fn new(vertices: impl Into<Vec<Vec2>>) -> Self {
+        Self {
+            vertices: vertices.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn vertices(&self) -> &[Vec2] {
+        &self.vertices
+    }
+
+    /// The edges of the polygon, including the one from the last back to the first vertex.
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        let next = self.vertices.iter().cycle().skip(1);
+
+        self.vertices
+            .iter()
+            .zip(next)
+            .map(|(&start, &end)| Segment::new(start, end))
+    }
+
+    /// The area of the polygon. It is positive if the vertices are in counterclockwise order and
+    /// negative if they are clockwise.
+    #[must_use]
+    pub fn signed_area(&self) -> f32 {
+        self.edges()
+            .map(|edge| edge.start.perp_dot(edge.end))
+            .sum::<f32>()
+            / 2.0
+    }
+
+    #[must_use]
+    pub fn area(&self) -> f32 {
+        self.signed_area().abs()
+    }
+
+    #[must_use]
+    pub fn bounding_rect(&self) -> Rect {
+        Rect::from_points(self.vertices.iter().copied())
+    }
+
+    /// Returns true if the point is inside the polygon. Points exactly on an edge may be reported
+    /// either way.
+    #[must_use]
+    pub fn contains(&self, point: Vec2) -> bool {
+        // count the edges crossed by a ray from the point in positive x direction
+        self.edges()
+            .filter(|edge| (edge.start.y > point.y) != (edge.end.y > point.y))
+            .filter(|edge| {
+                let t = (point.y - edge.start.y) / (edge.end.y - edge.start.y);
+                point.x < (edge.end.x - edge.start.x).mul_add(t, edge.start.x)
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    /// Returns true if the segment crosses an edge of the polygon or lies inside of it.
+    #[must_use]
+    pub fn intersects_segment(&self, segment: &Segment) -> bool {
+        self.contains(segment.start) || self.edges().any(|edge| edge.intersects(segment))
+    }
+
+    /// Returns true if the circle and the polygon overlap.
+    #[must_use]
+    pub fn intersects_circle(&self, circle: &Circle) -> bool {
+        self.contains(circle.center)
+            || self
+                .edges()
+                .any(|edge| edge.distance_to_point(circle.center) < circle.radius)
+    }
+
+    /// Returns true if the polygons overlap, i.e. their edges cross or one contains the other.
+    #[must_use]
+    pub fn intersects_polygon(&self, other: &Self) -> bool {
+        let contains_first_vertex = |polygon: &Self, other: &Self| {
+            other.vertices.first().is_some_and(|&v| polygon.contains(v))
+        };
+
+        contains_first_vertex(self, other)
+            || contains_first_vertex(other, self)
+            || self
+                .edges()
+                .any(|edge| other.edges().any(|other_edge| edge.intersects(&other_edge)))
+    }
+
+    /// The part of this polygon that lies inside the clip polygon, e.g. the visible part of a shape
+    /// in a viewport. The clip polygon must be convex, this polygon may be concave.
+    #[must_use]
+    pub fn clip(&self, clip: &Self) -> Self {
+        let orientation = clip.signed_area().signum();
+        let mut vertices = self.vertices.clone();
+
+        // Sutherland-Hodgman: clip against the half plane inside of each clip edge in turn
+        for clip_edge in clip.edges() {
+            let inside = |point: Vec2| {
+                (clip_edge.end - clip_edge.start).perp_dot(point - clip_edge.start) * orientation
+                    >= 0.0
+            };
+            let input = std::mem::take(&mut vertices);
+            let previous = input.iter().cycle().skip(input.len().saturating_sub(1));
+
+            for (&previous, &current) in previous.zip(&input) {
+                if inside(current) != inside(previous) {
+                    vertices.extend(line_intersection(
+                        &Segment::new(previous, current),
+                        &clip_edge,
+                    ));
+                }
+                if inside(current) {
+                    vertices.push(current);
+                }
+            }
+        }
+
+        Self { vertices }
+    }
+}
+
+/// The intersection of the infinite lines through both segments.
+fn line_intersection(a: &Segment, b: &Segment) -> Option<Vec2> {
+    let direction = a.end - a.start;
+    let other_direction = b.end - b.start;
+    let denominator = direction.perp_dot(other_direction);
+
+    (denominator.abs() > EPSILON).then(|| {
+        let t = (b.start - a.start).perp_dot(other_direction) / denominator;
+        a.start + direction * t
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: f32, max: f32) -> Polygon {
+        Polygon::new([
+            Vec2::splat(min),
+            Vec2::new(max, min),
+            Vec2::splat(max),
+            Vec2::new(min, max),
+        ])
+    }
+
+    #[test]
+    fn segment_intersections() {
+        let a = Segment::new(Vec2::ZERO, Vec2::splat(2.0));
+
+        assert_eq!(
+            a.intersection(&Segment::new(Vec2::new(0.0, 2.0), Vec2::new(2.0, 0.0))),
+            Some(Vec2::ONE)
+        );
+        assert_eq!(
+            a.intersection(&Segment::new(Vec2::new(2.0, 2.0), Vec2::new(3.0, 0.0))),
+            Some(Vec2::splat(2.0))
+        );
+        assert!(!a.intersects(&Segment::new(Vec2::new(3.0, 0.0), Vec2::new(0.0, -1.0))));
+        assert!(!a.intersects(&Segment::new(Vec2::X, Vec2::new(3.0, 2.0))));
+
+        // collinear segments
+        assert_eq!(
+            a.intersection(&Segment::new(Vec2::splat(3.0), Vec2::ONE)),
+            Some(Vec2::ONE)
+        );
+        assert_eq!(
+            a.intersection(&Segment::new(Vec2::splat(3.0), Vec2::splat(4.0))),
+            None
+        );
+    }
+
+    #[test]
+    fn segment_closest_point() {
+        let segment = Segment::new(Vec2::ZERO, Vec2::new(4.0, 0.0));
+
+        assert_eq!(segment.closest_point(Vec2::new(1.0, 3.0)), Vec2::X);
+        assert_eq!(segment.closest_point(Vec2::new(-2.0, 1.0)), Vec2::ZERO);
+        assert_eq!(segment.distance_to_point(Vec2::new(7.0, 4.0)), 5.0);
+    }
+
+    #[test]
+    fn polygon_contains_points_of_concave_shapes() {
+        // a U shape that is open at the top
+        let polygon = Polygon::new([
+            Vec2::ZERO,
+            Vec2::new(3.0, 0.0),
+            Vec2::new(3.0, 3.0),
+            Vec2::new(2.0, 3.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ]);
+
+        assert!(polygon.contains(Vec2::new(0.5, 2.5)));
+        assert!(polygon.contains(Vec2::new(1.5, 0.5)));
+        assert!(!polygon.contains(Vec2::new(1.5, 2.0)));
+        assert!(!polygon.contains(Vec2::new(4.0, 0.5)));
+        assert_eq!(polygon.area(), 7.0);
+        assert!(polygon.signed_area() > 0.0);
+    }
+
+    #[test]
+    fn polygon_shape_intersections() {
+        let polygon = square(0.0, 2.0);
+
+        assert!(polygon.intersects_circle(&Circle::new(Vec2::ONE, 0.1)));
+        assert!(polygon.intersects_circle(&Circle::new(Vec2::new(3.0, 1.0), 1.5)));
+        assert!(!polygon.intersects_circle(&Circle::new(Vec2::splat(3.0), 1.0)));
+
+        assert!(polygon.intersects_segment(&Segment::new(Vec2::ONE, Vec2::splat(1.5))));
+        assert!(
+            polygon.intersects_segment(&Segment::new(Vec2::new(-1.0, 1.0), Vec2::new(5.0, 1.0)))
+        );
+        assert!(!polygon.intersects_segment(&Segment::new(Vec2::splat(3.0), Vec2::splat(4.0))));
+
+        assert!(polygon.intersects_polygon(&square(1.0, 3.0)));
+        assert!(polygon.intersects_polygon(&square(0.5, 1.5)));
+        assert!(square(0.5, 1.5).intersects_polygon(&polygon));
+        assert!(!polygon.intersects_polygon(&square(3.0, 4.0)));
+    }
+
+    #[test]
+    fn clip_keeps_part_inside_clip_polygon() {
+        let clipped = square(0.0, 2.0).clip(&square(1.0, 3.0));
+
+        assert_eq!(clipped.vertices().len(), 4);
+        assert_eq!(clipped.area(), 1.0);
+        assert_eq!(
+            clipped.bounding_rect(),
+            Rect::from_corners(Vec2::ONE, Vec2::splat(2.0))
+        );
+
+        // clockwise clip polygons work the same
+        let clockwise = Polygon::new(
+            square(1.0, 3.0)
+                .vertices()
+                .iter()
+                .rev()
+                .copied()
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(square(0.0, 2.0).clip(&clockwise).area(), 1.0);
+
+        assert!(square(0.0, 1.0)
+            .clip(&square(2.0, 3.0))
+            .vertices()
+            .is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+//! # Math
+//! The math vocabulary of the engine. Vector, matrix and quaternion types are re-exported from
+//! [glam](https://docs.rs/glam), so that all engine and game code uses the same types:
+//!
+//! ```
+//! use game_engine::math::{Quat, Transform3D, Vec3};
+//!
+//! let transform = Transform3D::from_xyz(1.0, 2.0, 3.0).with_rotation(Quat::from_rotation_y(0.5));
+//!
+//! assert!(transform.transform_point(Vec3::ZERO).abs_diff_eq(Vec3::new(1.0, 2.0, 3.0), 1e-6));
+//! ```
+//!
+//! On top of that, [`Transform2D`] and [`Transform3D`] are the canonical components for the
+//! placement of entities in the world. A [`GlobalTransform`] is the world space counterpart of a
+//! [`Transform3D`] and converts between the local space of an entity and world space.
+//!
+//! Paths through the world are described by [cubic curves](CubicCurve), which can be built as
+//! Bézier, Hermite or Catmull-Rom splines and sampled by distance with an [`ArcLengthCurve`].
+//!
+//! Areas are described by the 2D bounds primitives [`Rect`] (which doubles as [`Aabb2d`]) and
+//! [`Circle`], which are shared by culling, collision, UI hit-testing and cameras. More detailed
+//! shapes are built from [`Segment`]s and [`Polygon`]s, which support intersection tests and
+//! clipping for gameplay code like line of sight checks or area selection.
+mod bounds;
+mod curve;
+mod geometry;
+mod transform;
+
+pub use bounds::{Aabb2d, Circle, Rect};
+pub use curve::{ArcLengthCurve, CubicCurve, CurvePoint};
+pub use geometry::{Polygon, Segment};
+pub use glam::*;
+pub use transform::{GlobalTransform, Transform2D, Transform3D};
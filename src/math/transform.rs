@@ -0,0 +1,492 @@
+use crate::math::{Affine2, Affine3A, Mat3, Mat4, Quat, Vec2, Vec3};
+
+/// Position, rotation and scale of an entity in a 2D world. The rotation is counterclockwise and
+/// given in radians. When applied to a point, the point is scaled first, then rotated and then
+/// translated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    pub translation: Vec2,
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Transform2D {
+    /// A transform that does not change anything.
+    pub const IDENTITY: Self = Self {
+        translation: Vec2::ZERO,
+        rotation: 0.0,
+        scale: Vec2::ONE,
+    };
+
+    #[must_use]
+    pub const fn from_translation(translation: Vec2) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[must_use]
+    pub const fn from_xy(x: f32, y: f32) -> Self {
+        Self::from_translation(Vec2::new(x, y))
+    }
+
+    #[must_use]
+    pub const fn from_rotation(rotation: f32) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[must_use]
+    pub const fn from_scale(scale: Vec2) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[must_use]
+    pub const fn with_translation(mut self, translation: Vec2) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The affine transformation described by this transform.
+    #[must_use]
+    pub fn compute_affine(&self) -> Affine2 {
+        Affine2::from_scale_angle_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// The homogeneous 3x3 matrix described by this transform.
+    #[must_use]
+    pub fn compute_matrix(&self) -> Mat3 {
+        Mat3::from(self.compute_affine())
+    }
+
+    /// Apply the transform to a point, i.e. scale, rotate and translate it.
+    #[must_use]
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        self.compute_affine().transform_point2(point)
+    }
+
+    /// Apply the rotation and scale of the transform to a vector, ignoring the translation.
+    #[must_use]
+    pub fn transform_vector(&self, vector: Vec2) -> Vec2 {
+        Vec2::from_angle(self.rotation).rotate(vector * self.scale)
+    }
+
+    /// The unit vector pointing along the local x-axis.
+    #[must_use]
+    pub fn right(&self) -> Vec2 {
+        Vec2::from_angle(self.rotation)
+    }
+
+    /// The unit vector pointing along the local y-axis.
+    #[must_use]
+    pub fn up(&self) -> Vec2 {
+        self.right().perp()
+    }
+
+    /// Move the transform by the given offset.
+    pub fn translate(&mut self, offset: Vec2) {
+        self.translation += offset;
+    }
+
+    /// Rotate the transform counterclockwise by the given angle in radians.
+    pub fn rotate(&mut self, angle: f32) {
+        self.rotation += angle;
+    }
+
+    /// Rotate the transform counterclockwise around a point, changing both its rotation and
+    /// translation.
+    pub fn rotate_around(&mut self, point: Vec2, angle: f32) {
+        self.translation = point + Vec2::from_angle(angle).rotate(self.translation - point);
+        self.rotation += angle;
+    }
+
+    /// Rotate the transform so that its [right](Self::right) direction points at the target. Does
+    /// nothing if the target is at the translation of the transform.
+    pub fn look_at(&mut self, target: Vec2) {
+        let direction = target - self.translation;
+
+        if direction != Vec2::ZERO {
+            self.rotation = direction.to_angle();
+        }
+    }
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Position, rotation and scale of an entity in a 3D world. When applied to a point, the point is
+/// scaled first, then rotated and then translated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform3D {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Transform3D {
+    /// A transform that does not change anything.
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    #[must_use]
+    pub const fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[must_use]
+    pub const fn from_xyz(x: f32, y: f32, z: f32) -> Self {
+        Self::from_translation(Vec3::new(x, y, z))
+    }
+
+    #[must_use]
+    pub const fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    #[must_use]
+    pub const fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    /// Decompose a matrix into translation, rotation and scale. The matrix must not contain any
+    /// shear or projection.
+    #[must_use]
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// The 4x4 matrix described by this transform.
+    #[must_use]
+    pub fn compute_matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    /// Apply the transform to a point, i.e. scale, rotate and translate it.
+    #[must_use]
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation * (point * self.scale) + self.translation
+    }
+
+    /// Apply the rotation and scale of the transform to a vector, ignoring the translation.
+    #[must_use]
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        self.rotation * (vector * self.scale)
+    }
+
+    /// The unit vector the transform is facing. By convention this is the local negative z-axis.
+    #[must_use]
+    pub fn forward(&self) -> Vec3 {
+        self.rotation * Vec3::NEG_Z
+    }
+
+    /// The unit vector pointing along the local x-axis.
+    #[must_use]
+    pub fn right(&self) -> Vec3 {
+        self.rotation * Vec3::X
+    }
+
+    /// The unit vector pointing along the local y-axis.
+    #[must_use]
+    pub fn up(&self) -> Vec3 {
+        self.rotation * Vec3::Y
+    }
+
+    /// Move the transform by the given offset.
+    pub fn translate(&mut self, offset: Vec3) {
+        self.translation += offset;
+    }
+
+    /// Apply an additional rotation to the transform, on top of its current rotation.
+    pub fn rotate(&mut self, rotation: Quat) {
+        self.rotation = (rotation * self.rotation).normalize();
+    }
+
+    /// Rotate the transform around a point, changing both its rotation and translation.
+    pub fn rotate_around(&mut self, point: Vec3, rotation: Quat) {
+        self.translation = point + rotation * (self.translation - point);
+        self.rotate(rotation);
+    }
+
+    /// Rotate the transform so that it is [facing](Self::forward) the target and its
+    /// [up](Self::up) direction is as close to `up` as possible. Does nothing if the target is at
+    /// the translation of the transform or if `up` is parallel to the direction of the target.
+    pub fn look_at(&mut self, target: Vec3, up: Vec3) {
+        let forward = (target - self.translation).normalize_or_zero();
+        let right = forward.cross(up).normalize_or_zero();
+
+        if forward == Vec3::ZERO || right == Vec3::ZERO {
+            return;
+        }
+
+        let up = right.cross(forward);
+        self.rotation = Quat::from_mat3(&Mat3::from_cols(right, up, -forward));
+    }
+
+    /// Builder variant of [`Transform3D::look_at`].
+    #[must_use]
+    pub fn looking_at(mut self, target: Vec3, up: Vec3) -> Self {
+        self.look_at(target, up);
+        self
+    }
+}
+
+impl Default for Transform3D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// The transform of an entity in world space. While a [`Transform3D`] is relative to the parent
+/// of an entity, the global transform combines it with the transforms of all its ancestors. For
+/// entities without a parent, both are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GlobalTransform(Affine3A);
+
+impl GlobalTransform {
+    pub const IDENTITY: Self = Self(Affine3A::IDENTITY);
+
+    /// The global transform of a child entity with the given local transform.
+    #[must_use]
+    pub fn mul_transform(&self, local: &Transform3D) -> Self {
+        Self(self.0 * Affine3A::from(*local))
+    }
+
+    #[must_use]
+    pub const fn affine(&self) -> Affine3A {
+        self.0
+    }
+
+    #[must_use]
+    pub fn compute_matrix(&self) -> Mat4 {
+        Mat4::from(self.0)
+    }
+
+    #[must_use]
+    pub fn translation(&self) -> Vec3 {
+        self.0.translation.into()
+    }
+
+    /// Decompose the global transform into translation, rotation and scale.
+    #[must_use]
+    pub fn compute_transform(&self) -> Transform3D {
+        Transform3D::from_matrix(self.compute_matrix())
+    }
+
+    /// Convert a point from the local space of the entity to world space.
+    #[must_use]
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.0.transform_point3(point)
+    }
+
+    /// Convert a point from world space to the local space of the entity.
+    #[must_use]
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        self.0.inverse().transform_point3(point)
+    }
+
+    /// The local transform that places an entity at this global transform when it is the child of
+    /// an entity with the given global transform. Useful to keep an entity in place when it is
+    /// attached to a new parent.
+    #[must_use]
+    pub fn reparented_to(&self, parent: &Self) -> Transform3D {
+        Self(parent.0.inverse() * self.0).compute_transform()
+    }
+}
+
+impl From<Transform3D> for GlobalTransform {
+    fn from(transform: Transform3D) -> Self {
+        Self(Affine3A::from(transform))
+    }
+}
+
+impl From<Transform3D> for Affine3A {
+    fn from(transform: Transform3D) -> Self {
+        Self::from_scale_rotation_translation(
+            transform.scale,
+            transform.rotation,
+            transform.translation,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn transform_2d_scales_rotates_and_translates() {
+        let transform = Transform2D::from_xy(10.0, 0.0)
+            .with_rotation(FRAC_PI_2)
+            .with_scale(Vec2::splat(2.0));
+
+        let point = transform.transform_point(Vec2::X);
+
+        assert!(point.abs_diff_eq(Vec2::new(10.0, 2.0), 1e-5));
+        assert!(transform
+            .compute_matrix()
+            .transform_point2(Vec2::X)
+            .abs_diff_eq(point, 1e-5));
+        assert!(transform
+            .transform_vector(Vec2::X)
+            .abs_diff_eq(Vec2::new(0.0, 2.0), 1e-5));
+    }
+
+    #[test]
+    fn transform_3d_scales_rotates_and_translates() {
+        let transform = Transform3D::from_xyz(0.0, 0.0, 5.0)
+            .with_rotation(Quat::from_rotation_z(FRAC_PI_2))
+            .with_scale(Vec3::new(3.0, 1.0, 1.0));
+
+        let point = transform.transform_point(Vec3::X);
+
+        assert!(point.abs_diff_eq(Vec3::new(0.0, 3.0, 5.0), 1e-5));
+        assert!(transform
+            .compute_matrix()
+            .transform_point3(Vec3::X)
+            .abs_diff_eq(point, 1e-5));
+    }
+
+    #[test]
+    fn transform_3d_round_trips_through_matrix() {
+        let transform = Transform3D::from_xyz(1.0, -2.0, 3.0)
+            .with_rotation(Quat::from_rotation_y(0.3))
+            .with_scale(Vec3::new(1.0, 2.0, 3.0));
+
+        let decomposed = Transform3D::from_matrix(transform.compute_matrix());
+
+        assert!(decomposed
+            .translation
+            .abs_diff_eq(transform.translation, 1e-5));
+        assert!(decomposed.rotation.abs_diff_eq(transform.rotation, 1e-5));
+        assert!(decomposed.scale.abs_diff_eq(transform.scale, 1e-5));
+    }
+
+    #[test]
+    fn transform_2d_helpers_move_and_rotate() {
+        let mut transform = Transform2D::from_xy(2.0, 0.0);
+
+        transform.rotate_around(Vec2::ZERO, FRAC_PI_2);
+        assert!(transform.translation.abs_diff_eq(Vec2::new(0.0, 2.0), 1e-5));
+        assert!(transform.right().abs_diff_eq(Vec2::Y, 1e-5));
+        assert!(transform.up().abs_diff_eq(Vec2::NEG_X, 1e-5));
+
+        transform.translate(Vec2::new(1.0, -2.0));
+        transform.look_at(Vec2::new(1.0, -5.0));
+        assert!(transform.right().abs_diff_eq(Vec2::NEG_Y, 1e-5));
+    }
+
+    #[test]
+    fn transform_3d_look_at_faces_target() {
+        let transform =
+            Transform3D::from_xyz(1.0, 0.0, 0.0).looking_at(Vec3::new(1.0, 0.0, 5.0), Vec3::Y);
+
+        assert!(transform.forward().abs_diff_eq(Vec3::Z, 1e-5));
+        assert!(transform.up().abs_diff_eq(Vec3::Y, 1e-5));
+        assert!(transform.right().abs_diff_eq(Vec3::NEG_X, 1e-5));
+
+        let mut unchanged = Transform3D::IDENTITY;
+        unchanged.look_at(Vec3::Y, Vec3::Y);
+        assert_eq!(unchanged, Transform3D::IDENTITY);
+    }
+
+    #[test]
+    fn transform_3d_rotate_around_moves_and_rotates() {
+        let mut transform = Transform3D::from_xyz(0.0, 0.0, -1.0);
+
+        transform.rotate_around(Vec3::new(0.0, 0.0, -2.0), Quat::from_rotation_y(FRAC_PI_2));
+
+        assert!(transform
+            .translation
+            .abs_diff_eq(Vec3::new(1.0, 0.0, -2.0), 1e-5));
+        assert!(transform.forward().abs_diff_eq(Vec3::NEG_X, 1e-5));
+    }
+
+    #[test]
+    fn global_transform_converts_between_local_and_world_space() {
+        let parent = GlobalTransform::from(
+            Transform3D::from_xyz(10.0, 0.0, 0.0).with_scale(Vec3::splat(2.0)),
+        );
+        let child = parent.mul_transform(&Transform3D::from_xyz(1.0, 0.0, 0.0));
+
+        assert!(child
+            .translation()
+            .abs_diff_eq(Vec3::new(12.0, 0.0, 0.0), 1e-5));
+
+        let point = Vec3::new(0.0, 1.0, 0.0);
+        let world = child.transform_point(point);
+        assert!(world.abs_diff_eq(Vec3::new(12.0, 2.0, 0.0), 1e-5));
+        assert!(child
+            .inverse_transform_point(world)
+            .abs_diff_eq(point, 1e-5));
+
+        let local = child.reparented_to(&parent);
+        assert!(local.translation.abs_diff_eq(Vec3::X, 1e-5));
+        assert!(local.scale.abs_diff_eq(Vec3::ONE, 1e-5));
+    }
+
+    #[test]
+    fn default_transforms_are_identity() {
+        assert_eq!(Transform2D::default(), Transform2D::IDENTITY);
+        assert_eq!(Transform3D::default(), Transform3D::IDENTITY);
+        assert_eq!(Transform3D::IDENTITY.compute_matrix(), Mat4::IDENTITY);
+        assert_eq!(GlobalTransform::default(), GlobalTransform::IDENTITY);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::net::{Channel, ConnectionId, NetError, Transport, TransportEvent};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+
+/// A loopback transport has at most one connection, which always uses this id.
+const LOOPBACK_CONNECTION: ConnectionId = 0;
+
+enum LoopbackPacket {
+    Connect,
+    Accept,
+    Disconnect,
+    Message(Channel, Vec<u8>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+}
+
+/// In-memory transport connecting two peers in the same process. Since nothing can get lost in
+/// memory, both channels are reliable and ordered.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::net::{Channel, LoopbackTransport, Transport, TransportEvent};
+///
+/// let (mut server, mut client) = LoopbackTransport::pair();
+/// server.accept_incoming(true);
+///
+/// let connection = client.connect(()).unwrap();
+/// server.poll().unwrap();
+/// client.poll().unwrap();
+///
+/// client.send(connection, Channel::ReliableOrdered, b"hello").unwrap();
+/// server.poll().unwrap();
+///
+/// assert!(server
+///     .drain_events()
+///     .iter()
+///     .any(|event| matches!(event, TransportEvent::Message { payload, .. } if payload == b"hello")));
+/// ```
+pub struct LoopbackTransport {
+    sender: Sender<LoopbackPacket>,
+    receiver: Receiver<LoopbackPacket>,
+    state: ConnectionState,
+    accepting: bool,
+    events: Vec<TransportEvent>,
+}
+
+impl LoopbackTransport {
+    /// Create two transports that can connect to each other.
+    #[must_use]
+    pub fn pair() -> (Self, Self) {
+        let (sender_a, receiver_b) = channel();
+        let (sender_b, receiver_a) = channel();
+
+        (
+            Self::new(sender_a, receiver_a),
+            Self::new(sender_b, receiver_b),
+        )
+    }
+
+    const fn new(sender: Sender<LoopbackPacket>, receiver: Receiver<LoopbackPacket>) -> Self {
+        Self {
+            sender,
+            receiver,
+            state: ConnectionState::Disconnected,
+            accepting: false,
+            events: Vec::new(),
+        }
+    }
+
+    fn send_packet(&self, packet: LoopbackPacket) {
+        // if the other transport was dropped, this is noticed on the next poll
+        let _ = self.sender.send(packet);
+    }
+
+    fn close_connection(&mut self) {
+        if self.state != ConnectionState::Disconnected {
+            self.state = ConnectionState::Disconnected;
+            self.events
+                .push(TransportEvent::Disconnected(LOOPBACK_CONNECTION));
+        }
+    }
+
+    fn handle_packet(&mut self, packet: LoopbackPacket) {
+        match packet {
+            LoopbackPacket::Connect => {
+                if self.accepting && self.state == ConnectionState::Disconnected {
+                    self.state = ConnectionState::Connected;
+                    self.send_packet(LoopbackPacket::Accept);
+                    self.events
+                        .push(TransportEvent::Connected(LOOPBACK_CONNECTION));
+                } else if self.state == ConnectionState::Disconnected {
+                    self.send_packet(LoopbackPacket::Disconnect);
+                }
+            }
+            LoopbackPacket::Accept => {
+                if self.state == ConnectionState::Connecting {
+                    self.state = ConnectionState::Connected;
+                    self.events
+                        .push(TransportEvent::Connected(LOOPBACK_CONNECTION));
+                }
+            }
+            LoopbackPacket::Disconnect => self.close_connection(),
+            LoopbackPacket::Message(channel, payload) => {
+                if self.state == ConnectionState::Connected {
+                    self.events.push(TransportEvent::Message {
+                        connection: LOOPBACK_CONNECTION,
+                        channel,
+                        payload,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Transport for LoopbackTransport {
+    type Address = ();
+
+    fn connect(&mut self, _address: Self::Address) -> Result<ConnectionId, NetError> {
+        if self.state == ConnectionState::Disconnected {
+            self.state = ConnectionState::Connecting;
+            self.send_packet(LoopbackPacket::Connect);
+        }
+
+        Ok(LOOPBACK_CONNECTION)
+    }
+
+    fn accept_incoming(&mut self, accept: bool) {
+        self.accepting = accept;
+    }
+
+    fn disconnect(&mut self, connection: ConnectionId) {
+        if connection == LOOPBACK_CONNECTION && self.state != ConnectionState::Disconnected {
+            self.send_packet(LoopbackPacket::Disconnect);
+            self.close_connection();
+        }
+    }
+
+    fn send(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        payload: &[u8],
+    ) -> Result<(), NetError> {
+        if !self.is_connected(connection) {
+            return Err(NetError::NotConnected(connection));
+        }
+
+        self.send_packet(LoopbackPacket::Message(channel, payload.to_vec()));
+
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Result<(), NetError> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(packet) => self.handle_packet(packet),
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => {
+                    self.close_connection();
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn drain_events(&mut self) -> Vec<TransportEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn is_connected(&self, connection: ConnectionId) -> bool {
+        connection == LOOPBACK_CONNECTION && self.state == ConnectionState::Connected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connected_pair() -> (LoopbackTransport, LoopbackTransport) {
+        let (mut server, mut client) = LoopbackTransport::pair();
+        server.accept_incoming(true);
+
+        client.connect(()).unwrap();
+        server.poll().unwrap();
+        client.poll().unwrap();
+
+        (server, client)
+    }
+
+    #[test]
+    fn connect_reports_connected_on_both_peers() {
+        let (mut server, mut client) = connected_pair();
+
+        assert_eq!(
+            server.drain_events(),
+            vec![TransportEvent::Connected(LOOPBACK_CONNECTION)]
+        );
+        assert_eq!(
+            client.drain_events(),
+            vec![TransportEvent::Connected(LOOPBACK_CONNECTION)]
+        );
+        assert!(server.is_connected(LOOPBACK_CONNECTION));
+        assert!(client.is_connected(LOOPBACK_CONNECTION));
+    }
+
+    #[test]
+    fn connect_is_rejected_if_not_accepting() {
+        let (mut server, mut client) = LoopbackTransport::pair();
+
+        client.connect(()).unwrap();
+        server.poll().unwrap();
+        client.poll().unwrap();
+
+        assert!(server.drain_events().is_empty());
+        assert_eq!(
+            client.drain_events(),
+            vec![TransportEvent::Disconnected(LOOPBACK_CONNECTION)]
+        );
+    }
+
+    #[test]
+    fn send_delivers_messages_in_order() {
+        let (mut server, mut client) = connected_pair();
+        server.drain_events();
+
+        client
+            .send(LOOPBACK_CONNECTION, Channel::ReliableOrdered, &[1])
+            .unwrap();
+        client
+            .send(LOOPBACK_CONNECTION, Channel::Unreliable, &[2])
+            .unwrap();
+        server.poll().unwrap();
+
+        assert_eq!(
+            server.drain_events(),
+            vec![
+                TransportEvent::Message {
+                    connection: LOOPBACK_CONNECTION,
+                    channel: Channel::ReliableOrdered,
+                    payload: vec![1],
+                },
+                TransportEvent::Message {
+                    connection: LOOPBACK_CONNECTION,
+                    channel: Channel::Unreliable,
+                    payload: vec![2],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn send_fails_if_not_connected() {
+        let (_server, mut client) = LoopbackTransport::pair();
+
+        assert!(matches!(
+            client.send(LOOPBACK_CONNECTION, Channel::Unreliable, &[1]),
+            Err(NetError::NotConnected(LOOPBACK_CONNECTION))
+        ));
+    }
+
+    #[test]
+    fn disconnect_reports_disconnected_on_both_peers() {
+        let (mut server, mut client) = connected_pair();
+        server.drain_events();
+        client.drain_events();
+
+        client.disconnect(LOOPBACK_CONNECTION);
+        server.poll().unwrap();
+
+        assert_eq!(
+            client.drain_events(),
+            vec![TransportEvent::Disconnected(LOOPBACK_CONNECTION)]
+        );
+        assert_eq!(
+            server.drain_events(),
+            vec![TransportEvent::Disconnected(LOOPBACK_CONNECTION)]
+        );
+    }
+
+    #[test]
+    fn dropping_peer_disconnects() {
+        let (mut server, client) = connected_pair();
+        server.drain_events();
+
+        drop(client);
+        server.poll().unwrap();
+
+        assert_eq!(
+            server.drain_events(),
+            vec![TransportEvent::Disconnected(LOOPBACK_CONNECTION)]
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use crate::ecs::Storage;
+use crate::net::wire::{WireReader, WireWriter};
+use crate::net::{Channel, ClientId, NetError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::{type_name, TypeId};
+use std::collections::HashMap;
+
+/// A unique id for a registered message type. Ids are assigned in the order in which message types
+/// are registered.
+pub type MessageId = u16;
+
+/// ECS event for a typed message that was received from a peer. On clients, the sender is always
+/// the server ([`SERVER_ID`](crate::net::SERVER_ID)).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageReceived<MessageType> {
+    pub client: ClientId,
+    pub message: MessageType,
+}
+
+/// Decodes a message and sends it as [`MessageReceived`] event. One of these is created for every
+/// registered message type.
+pub type DispatchFn = fn(&mut Storage, ClientId, &[u8]) -> Result<(), NetError>;
+
+struct Registration {
+    channel: Channel,
+    dispatch: DispatchFn,
+}
+
+/// The set of typed messages that peers can exchange through a session. Any type that implements
+/// `serde`'s `Serialize` and `Deserialize` can be registered as message, together with the channel
+/// it is sent on.
+///
+/// Message types are identified by the order in which they are registered, so the server and all
+/// clients have to register the same types in the same order. This is easiest to ensure by
+/// building the registry in a single function that both sides call.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::net::{Channel, MessageRegistry};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct ChatMessage(String);
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct SpawnRequest {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// fn protocol() -> MessageRegistry {
+///     let mut registry = MessageRegistry::new();
+///     registry
+///         .register::<ChatMessage>(Channel::ReliableOrdered)
+///         .register::<SpawnRequest>(Channel::Unreliable);
+///     registry
+/// }
+/// ```
+#[derive(Default)]
+pub struct MessageRegistry {
+    ids: HashMap<TypeId, MessageId>,
+    registrations: Vec<Registration>,
+}
+
+impl MessageRegistry {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a message type that is sent on the given channel. Registering a type again only
+    /// changes its channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `MessageId::MAX + 1` message types are registered.
+    pub fn register<MessageType: Serialize + DeserializeOwned + 'static>(
+        &mut self,
+        channel: Channel,
+    ) -> &mut Self {
+        if let Some(&id) = self.ids.get(&TypeId::of::<MessageType>()) {
+            self.registrations[usize::from(id)].channel = channel;
+            return self;
+        }
+
+        let id = MessageId::try_from(self.registrations.len())
+            .expect("Too many message types registered.");
+
+        self.ids.insert(TypeId::of::<MessageType>(), id);
+        self.registrations.push(Registration {
+            channel,
+            dispatch: dispatch::<MessageType>,
+        });
+
+        self
+    }
+
+    #[must_use]
+    pub fn is_registered<MessageType: 'static>(&self) -> bool {
+        self.ids.contains_key(&TypeId::of::<MessageType>())
+    }
+
+    /// The channel a message type is sent on. Returns None if the type is not registered.
+    #[must_use]
+    pub fn channel<MessageType: 'static>(&self) -> Option<Channel> {
+        let id = self.ids.get(&TypeId::of::<MessageType>())?;
+
+        Some(self.registrations[usize::from(*id)].channel)
+    }
+
+    /// Encode a message together with its id and return it with the channel it has to be sent on.
+    pub(crate) fn encode<MessageType: Serialize + 'static>(
+        &self,
+        message: &MessageType,
+    ) -> Result<(Channel, Vec<u8>), NetError> {
+        let id = *self
+            .ids
+            .get(&TypeId::of::<MessageType>())
+            .ok_or(NetError::UnregisteredMessage(type_name::<MessageType>()))?;
+
+        let mut writer = WireWriter::default();
+        writer
+            .write_u16(id)
+            .write_bytes(&bincode::serialize(message).map_err(NetError::Encoding)?);
+
+        Ok((
+            self.registrations[usize::from(id)].channel,
+            writer.into_bytes(),
+        ))
+    }
+
+    /// Split an encoded message into its id and the encoded message itself.
+    pub(crate) fn split_id(payload: &[u8]) -> Option<(MessageId, &[u8])> {
+        let mut reader = WireReader::new(payload);
+
+        Some((reader.read_u16()?, reader.read_remaining()))
+    }
+
+    pub(crate) fn dispatcher(&self, id: MessageId) -> Option<DispatchFn> {
+        self.registrations
+            .get(usize::from(id))
+            .map(|registration| registration.dispatch)
+    }
+}
+
+fn dispatch<MessageType: DeserializeOwned + 'static>(
+    storage: &mut Storage,
+    client: ClientId,
+    payload: &[u8],
+) -> Result<(), NetError> {
+    let message: MessageType = bincode::deserialize(payload).map_err(NetError::Encoding)?;
+    storage.send_event(MessageReceived { client, message });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct ChatMessage(String);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct SpawnRequest {
+        x: f32,
+        y: f32,
+    }
+
+    fn registry() -> MessageRegistry {
+        let mut registry = MessageRegistry::new();
+        registry
+            .register::<ChatMessage>(Channel::ReliableOrdered)
+            .register::<SpawnRequest>(Channel::Unreliable);
+        registry
+    }
+
+    #[test]
+    fn register_assigns_ids_in_order() {
+        let registry = registry();
+
+        assert!(registry.is_registered::<ChatMessage>());
+        assert!(registry.is_registered::<SpawnRequest>());
+        assert!(!registry.is_registered::<i32>());
+        assert_eq!(registry.ids[&TypeId::of::<ChatMessage>()], 0);
+        assert_eq!(registry.ids[&TypeId::of::<SpawnRequest>()], 1);
+    }
+
+    #[test]
+    fn register_again_changes_channel() {
+        let mut registry = registry();
+
+        registry.register::<ChatMessage>(Channel::Unreliable);
+
+        assert_eq!(registry.registrations.len(), 2);
+        assert_eq!(registry.channel::<ChatMessage>(), Some(Channel::Unreliable));
+    }
+
+    #[test]
+    fn encode_fails_for_unregistered_message() {
+        let registry = registry();
+
+        assert!(matches!(
+            registry.encode(&42),
+            Err(NetError::UnregisteredMessage(_))
+        ));
+    }
+
+    #[test]
+    fn dispatch_sends_decoded_message_as_event() {
+        let registry = registry();
+        let message = SpawnRequest { x: 1.0, y: 2.0 };
+
+        let (channel, payload) = registry.encode(&message).unwrap();
+        assert_eq!(channel, Channel::Unreliable);
+
+        let (id, encoded) = MessageRegistry::split_id(&payload).unwrap();
+        assert_eq!(id, 1);
+
+        let mut storage = Storage::new();
+        registry.dispatcher(id).unwrap()(&mut storage, 7, encoded).unwrap();
+
+        assert_eq!(
+            storage
+                .drain_events::<MessageReceived<SpawnRequest>>()
+                .collect::<Vec<_>>(),
+            vec![MessageReceived { client: 7, message }]
+        );
+    }
+
+    #[test]
+    fn dispatch_fails_for_malformed_message() {
+        let registry = registry();
+        let mut storage = Storage::new();
+
+        let result = registry.dispatcher(1).unwrap()(&mut storage, 7, &[1, 2]);
+
+        assert!(matches!(result, Err(NetError::Encoding(_))));
+        assert_eq!(
+            storage
+                .read_events::<MessageReceived<SpawnRequest>>()
+                .count(),
+            0
+        );
+    }
+}
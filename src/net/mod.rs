@@ -0,0 +1,35 @@
+//! # Networking
+//! This module contains the transport layer that all networking features build upon. A
+//! [`Transport`] moves raw byte messages between peers over [channels](Channel) with different
+//! delivery guarantees and reports connection changes and incoming messages as
+//! [`TransportEvent`]s.
+//!
+//! There are two implementations:
+//! - [`UdpTransport`]: Talks to remote peers over UDP. Reliable-ordered messages are acknowledged
+//!   and resent by the transport itself.
+//! - [`LoopbackTransport`]: Connects two transports in the same process through memory, e.g. for
+//!   a local server in single player or for tests.
+//!
+//! The transport is usually stored as a resource and driven by the [`TransportSystem`], which
+//! forwards all transport events into the ECS event channel.
+//!
+//! On top of the transport, [`ServerSession`] and [`ClientSession`] manage who is playing: clients
+//! join a server with some [metadata](PlayerMetadata), get a player slot assigned, and everyone is
+//! informed about players joining and leaving through [`PlayerConnected`] and
+//! [`PlayerDisconnected`] events. Besides raw bytes, sessions can exchange typed messages that are
+//! registered in a [`MessageRegistry`] and arrive as [`MessageReceived`] events.
+mod loopback;
+mod message;
+mod reliability;
+mod session;
+mod system;
+mod transport;
+mod udp;
+mod wire;
+
+pub use loopback::LoopbackTransport;
+pub use message::{MessageId, MessageReceived, MessageRegistry};
+pub use session::*;
+pub use system::TransportSystem;
+pub use transport::*;
+pub use udp::{UdpTransport, MAX_PAYLOAD_SIZE};
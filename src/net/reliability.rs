@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Sequence number of a reliable message. Sequence numbers do not wrap around, which limits a
+/// connection to `u32::MAX` reliable messages.
+pub type Sequence = u32;
+
+/// The maximum number of out-of-order messages buffered while waiting for a missing message.
+/// Messages beyond this window are dropped without an acknowledgement, so that the other peer
+/// resends them.
+pub(crate) const MAX_BUFFERED_MESSAGES: u32 = 1024;
+
+/// Sending half of a reliable-ordered channel. Keeps all messages until they are acknowledged by
+/// the other peer and decides when they have to be resent.
+#[derive(Default)]
+pub struct ReliableSender {
+    next_sequence: Sequence,
+    unacknowledged: BTreeMap<Sequence, PendingMessage>,
+}
+
+struct PendingMessage {
+    payload: Vec<u8>,
+    last_sent: Instant,
+}
+
+impl ReliableSender {
+    /// Register a message that is sent right now and return its sequence number.
+    pub fn push(&mut self, payload: Vec<u8>, now: Instant) -> Sequence {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        self.unacknowledged.insert(
+            sequence,
+            PendingMessage {
+                payload,
+                last_sent: now,
+            },
+        );
+
+        sequence
+    }
+
+    pub fn acknowledge(&mut self, sequence: Sequence) {
+        self.unacknowledged.remove(&sequence);
+    }
+
+    /// Return all messages that were not acknowledged within the resend interval and mark them
+    /// as sent again.
+    pub fn due_for_resend(
+        &mut self,
+        now: Instant,
+        resend_interval: Duration,
+    ) -> Vec<(Sequence, Vec<u8>)> {
+        self.unacknowledged
+            .iter_mut()
+            .filter(|(_, message)| now.duration_since(message.last_sent) >= resend_interval)
+            .map(|(&sequence, message)| {
+                message.last_sent = now;
+                (sequence, message.payload.clone())
+            })
+            .collect()
+    }
+
+    #[cfg(test)]
+    pub fn unacknowledged_count(&self) -> usize {
+        self.unacknowledged.len()
+    }
+}
+
+/// Receiving half of a reliable-ordered channel. Drops duplicates and holds back messages that
+/// arrive before their predecessors.
+#[derive(Default)]
+pub struct ReliableReceiver {
+    next_expected: Sequence,
+    buffered: BTreeMap<Sequence, Vec<u8>>,
+}
+
+impl ReliableReceiver {
+    /// Receive a message and return all messages that can now be delivered in order. Returns
+    /// None if the message is beyond the buffer window and was dropped, it must not be
+    /// acknowledged then. Duplicates are accepted without delivering anything.
+    pub fn receive(&mut self, sequence: Sequence, payload: Vec<u8>) -> Option<Vec<Vec<u8>>> {
+        if sequence < self.next_expected {
+            return Some(vec![]);
+        }
+        if sequence - self.next_expected >= MAX_BUFFERED_MESSAGES {
+            return None;
+        }
+
+        self.buffered.entry(sequence).or_insert(payload);
+
+        let mut deliverable = vec![];
+
+        while let Some(payload) = self.buffered.remove(&self.next_expected) {
+            deliverable.push(payload);
+            self.next_expected += 1;
+        }
+
+        Some(deliverable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sender_resends_unacknowledged_messages_after_interval() {
+        let mut sender = ReliableSender::default();
+        let start = Instant::now();
+        let interval = Duration::from_millis(100);
+
+        let first = sender.push(vec![1], start);
+        let second = sender.push(vec![2], start);
+        assert_eq!((first, second), (0, 1));
+
+        assert!(sender.due_for_resend(start, interval).is_empty());
+
+        sender.acknowledge(first);
+
+        let resend = sender.due_for_resend(start + interval, interval);
+        assert_eq!(resend, vec![(second, vec![2])]);
+
+        // the message was just resent, so it is not due again right away
+        assert!(sender.due_for_resend(start + interval, interval).is_empty());
+
+        sender.acknowledge(second);
+        assert_eq!(sender.unacknowledged_count(), 0);
+    }
+
+    #[test]
+    fn receiver_delivers_messages_in_order() {
+        let mut receiver = ReliableReceiver::default();
+
+        assert_eq!(receiver.receive(0, vec![0]), Some(vec![vec![0]]));
+        assert_eq!(receiver.receive(2, vec![2]), Some(vec![]));
+        assert_eq!(receiver.receive(3, vec![3]), Some(vec![]));
+        assert_eq!(
+            receiver.receive(1, vec![1]),
+            Some(vec![vec![1], vec![2], vec![3]])
+        );
+    }
+
+    #[test]
+    fn receiver_drops_duplicate_messages() {
+        let mut receiver = ReliableReceiver::default();
+
+        assert_eq!(receiver.receive(0, vec![0]), Some(vec![vec![0]]));
+        assert_eq!(receiver.receive(0, vec![0]), Some(vec![]));
+
+        assert_eq!(receiver.receive(2, vec![2]), Some(vec![]));
+        assert_eq!(receiver.receive(2, vec![2]), Some(vec![]));
+        assert_eq!(receiver.receive(1, vec![1]), Some(vec![vec![1], vec![2]]));
+    }
+
+    #[test]
+    fn receiver_drops_messages_outside_of_buffer_window() {
+        let mut receiver = ReliableReceiver::default();
+
+        assert_eq!(receiver.receive(MAX_BUFFERED_MESSAGES, vec![0]), None);
+        assert!(receiver.buffered.is_empty());
+    }
+}
@@ -0,0 +1,1325 @@
+use crate::ecs::{Storage, System};
+use crate::net::wire::{WireReader, WireWriter};
+use crate::net::{
+    Channel, ConnectionId, MessageId, MessageRegistry, NetError, Transport, TransportEvent,
+    MAX_PAYLOAD_SIZE,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+
+/// A unique id for a player of a session. The server assigns the id when a player joins, clients
+/// learn their own id from the server.
+pub type ClientId = u64;
+
+/// The sender of all messages that a client receives.
+pub const SERVER_ID: ClientId = ClientId::MAX;
+
+/// The position of a player in the session, from 0 up to the maximum number of players. Freed
+/// slots are reused by players joining later.
+pub type PlayerSlot = u16;
+
+/// Arbitrary key-value data of a player, e.g. the player name or the selected team.
+pub type PlayerMetadata = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerInfo {
+    pub client: ClientId,
+    pub slot: PlayerSlot,
+    pub metadata: PlayerMetadata,
+}
+
+/// ECS event that is sent when a player joined the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerConnected {
+    pub client: ClientId,
+    pub slot: PlayerSlot,
+}
+
+/// ECS event that is sent when a player left the session, either on purpose or because the
+/// connection was lost. Clients that lose the connection to the server receive this event for
+/// every player, including themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerDisconnected {
+    pub client: ClientId,
+    pub slot: PlayerSlot,
+}
+
+/// ECS event for a message that was sent by a player (on the server) or by the server (on
+/// clients, with [`SERVER_ID`] as client).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionMessage {
+    pub client: ClientId,
+    pub channel: Channel,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    SessionFull,
+    /// The metadata of the player does not fit into a single message.
+    MetadataTooLarge,
+}
+
+/// ECS event that is sent on a client when the server did not let it join.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinRejected(pub RejectReason);
+
+/// Something that happened in a session since it was last polled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    PlayerConnected(PlayerConnected),
+    PlayerDisconnected(PlayerDisconnected),
+    Message(SessionMessage),
+    JoinRejected(JoinRejected),
+    /// A typed message that still has to be decoded with the [`MessageRegistry`] of the session.
+    TypedMessage {
+        client: ClientId,
+        message: MessageId,
+        payload: Vec<u8>,
+    },
+}
+
+/// Common interface of [`ServerSession`] and [`ClientSession`], used by the [`SessionSystem`].
+pub trait Session {
+    /// Poll the underlying transport and process everything it received.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport failed. Events collected before the error are kept.
+    fn poll(&mut self) -> Result<(), NetError>;
+
+    /// Remove all events collected since the last call and return them in the order they happened.
+    fn drain_events(&mut self) -> Vec<SessionEvent>;
+
+    /// The typed messages this session can send and receive.
+    fn message_registry(&self) -> &MessageRegistry;
+}
+
+/// The first byte of every message sent through a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum MessageKind {
+    Join = 0,
+    Welcome = 1,
+    Reject = 2,
+    PlayerJoined = 3,
+    PlayerLeft = 4,
+    PlayerUpdated = 5,
+    Leave = 6,
+    User = 7,
+    Typed = 8,
+}
+
+impl MessageKind {
+    const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Join),
+            1 => Some(Self::Welcome),
+            2 => Some(Self::Reject),
+            3 => Some(Self::PlayerJoined),
+            4 => Some(Self::PlayerLeft),
+            5 => Some(Self::PlayerUpdated),
+            6 => Some(Self::Leave),
+            7 => Some(Self::User),
+            8 => Some(Self::Typed),
+            _ => None,
+        }
+    }
+
+    fn writer(self) -> WireWriter {
+        let mut writer = WireWriter::default();
+        writer.write_u8(self as u8);
+        writer
+    }
+}
+
+fn write_metadata(writer: &mut WireWriter, metadata: &PlayerMetadata) {
+    // metadata is meant for a handful of small values, so larger maps are simply cut off
+    let count = u16::try_from(metadata.len()).unwrap_or(u16::MAX);
+    writer.write_u16(count);
+
+    for (key, value) in metadata.iter().take(usize::from(count)) {
+        writer.write_str(key).write_str(value);
+    }
+}
+
+fn read_metadata(reader: &mut WireReader) -> Option<PlayerMetadata> {
+    let count = reader.read_u16()?;
+
+    (0..count)
+        .map(|_| Some((reader.read_str()?.to_owned(), reader.read_str()?.to_owned())))
+        .collect()
+}
+
+fn write_player(writer: &mut WireWriter, player: &PlayerInfo) {
+    writer.write_u64(player.client).write_u16(player.slot);
+    write_metadata(writer, &player.metadata);
+}
+
+fn read_player(reader: &mut WireReader) -> Option<PlayerInfo> {
+    Some(PlayerInfo {
+        client: reader.read_u64()?,
+        slot: reader.read_u16()?,
+        metadata: read_metadata(reader)?,
+    })
+}
+
+fn user_message(payload: &[u8]) -> Vec<u8> {
+    let mut writer = MessageKind::User.writer();
+    writer.write_bytes(payload);
+    writer.into_bytes()
+}
+
+fn typed_message<MessageType: Serialize + 'static>(
+    registry: &MessageRegistry,
+    message: &MessageType,
+) -> Result<(Channel, Vec<u8>), NetError> {
+    let (channel, payload) = registry.encode(message)?;
+
+    let mut writer = MessageKind::Typed.writer();
+    writer.write_bytes(&payload);
+
+    Ok((channel, writer.into_bytes()))
+}
+
+fn read_typed_message(client: ClientId, reader: &mut WireReader) -> Option<SessionEvent> {
+    let (message, payload) = MessageRegistry::split_id(reader.read_remaining())?;
+
+    Some(SessionEvent::TypedMessage {
+        client,
+        message,
+        payload: payload.to_vec(),
+    })
+}
+
+/// Server side of a session. Accepts incoming connections, assigns player slots and keeps all
+/// clients informed about who is in the session. A connection only becomes a player once its
+/// client asked to join, so [`PlayerConnected`] is a good point to spawn the player's avatar.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::ecs::{System, World};
+/// use game_engine::net::{LoopbackTransport, ServerSession, SessionSystem};
+///
+/// let (transport, _client_transport) = LoopbackTransport::pair();
+///
+/// let mut world = World::init().expect("Failed to initialize world");
+/// world.storage.insert_resource(ServerSession::new(transport, 4));
+/// world.add_system(SessionSystem::<ServerSession<LoopbackTransport>>::new());
+/// ```
+pub struct ServerSession<TransportType> {
+    transport: TransportType,
+    max_players: PlayerSlot,
+    players: HashMap<ClientId, PlayerInfo>,
+    messages: MessageRegistry,
+    events: Vec<SessionEvent>,
+}
+
+impl<TransportType: Transport> ServerSession<TransportType> {
+    /// Create a session on a transport. The transport is switched to accept incoming connections.
+    pub fn new(mut transport: TransportType, max_players: PlayerSlot) -> Self {
+        transport.accept_incoming(true);
+
+        Self {
+            transport,
+            max_players,
+            players: HashMap::new(),
+            messages: MessageRegistry::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Set the typed messages this session can send and receive.
+    #[must_use]
+    pub fn with_messages(mut self, messages: MessageRegistry) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Iterate over all players in the session, in no particular order.
+    pub fn players(&self) -> impl Iterator<Item = &PlayerInfo> {
+        self.players.values()
+    }
+
+    #[must_use]
+    pub fn player(&self, client: ClientId) -> Option<&PlayerInfo> {
+        self.players.get(&client)
+    }
+
+    #[must_use]
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    #[must_use]
+    pub const fn max_players(&self) -> PlayerSlot {
+        self.max_players
+    }
+
+    pub const fn transport(&self) -> &TransportType {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut TransportType {
+        &mut self.transport
+    }
+
+    /// Set a metadata value of a player. The change is sent to all clients.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not in the session or the update could not be sent.
+    pub fn set_metadata(
+        &mut self,
+        client: ClientId,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<(), NetError> {
+        let player = self
+            .players
+            .get_mut(&client)
+            .ok_or(NetError::NotConnected(client))?;
+
+        player.metadata.insert(key.into(), value.into());
+
+        let mut writer = MessageKind::PlayerUpdated.writer();
+        write_player(&mut writer, player);
+
+        self.broadcast_raw(&writer.into_bytes(), None)
+    }
+
+    /// Send a message to a player.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not in the session or sending failed.
+    pub fn send(
+        &mut self,
+        client: ClientId,
+        channel: Channel,
+        payload: &[u8],
+    ) -> Result<(), NetError> {
+        if !self.players.contains_key(&client) {
+            return Err(NetError::NotConnected(client));
+        }
+
+        self.transport.send(client, channel, &user_message(payload))
+    }
+
+    /// Send a message to all players.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last error if sending to any of the players failed. The message is still sent
+    /// to all other players.
+    pub fn broadcast(&mut self, channel: Channel, payload: &[u8]) -> Result<(), NetError> {
+        let message = user_message(payload);
+        let mut result = Ok(());
+
+        for &client in self.players.keys() {
+            if let Err(error) = self.transport.send(client, channel, &message) {
+                result = Err(error);
+            }
+        }
+
+        result
+    }
+
+    /// Send a typed message to a player, on the channel it was registered with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the player is not in the session, the message type is not registered
+    /// or the message could not be encoded or sent.
+    pub fn send_message<MessageType: Serialize + 'static>(
+        &mut self,
+        client: ClientId,
+        message: &MessageType,
+    ) -> Result<(), NetError> {
+        if !self.players.contains_key(&client) {
+            return Err(NetError::NotConnected(client));
+        }
+
+        let (channel, payload) = typed_message(&self.messages, message)?;
+
+        self.transport.send(client, channel, &payload)
+    }
+
+    /// Send a typed message to all players, on the channel it was registered with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message type is not registered or the message could not be
+    /// encoded. If sending to any of the players failed, the last error is returned after the
+    /// message was sent to all other players.
+    pub fn broadcast_message<MessageType: Serialize + 'static>(
+        &mut self,
+        message: &MessageType,
+    ) -> Result<(), NetError> {
+        let (channel, payload) = typed_message(&self.messages, message)?;
+        let mut result = Ok(());
+
+        for &client in self.players.keys() {
+            if let Err(error) = self.transport.send(client, channel, &payload) {
+                result = Err(error);
+            }
+        }
+
+        result
+    }
+
+    /// Remove a player from the session and close its connection.
+    pub fn kick(&mut self, client: ClientId) {
+        self.remove_player(client);
+        self.transport.disconnect(client);
+    }
+
+    fn broadcast_raw(&mut self, message: &[u8], except: Option<ClientId>) -> Result<(), NetError> {
+        let mut result = Ok(());
+
+        for &client in self.players.keys() {
+            if Some(client) == except {
+                continue;
+            }
+
+            if let Err(error) = self
+                .transport
+                .send(client, Channel::ReliableOrdered, message)
+            {
+                result = Err(error);
+            }
+        }
+
+        result
+    }
+
+    fn join(&mut self, connection: ConnectionId, metadata: PlayerMetadata) -> Result<(), NetError> {
+        let Some(slot) = (0..self.max_players)
+            .find(|&slot| !self.players.values().any(|player| player.slot == slot))
+        else {
+            return self.reject(connection, RejectReason::SessionFull);
+        };
+
+        let player = PlayerInfo {
+            client: connection,
+            slot,
+            metadata,
+        };
+
+        let mut writer = MessageKind::PlayerJoined.writer();
+        write_player(&mut writer, &player);
+        let joined = writer.into_bytes();
+
+        // players are sent in a message each, which has to fit into a packet of every transport
+        if joined.len() > MAX_PAYLOAD_SIZE {
+            return self.reject(connection, RejectReason::MetadataTooLarge);
+        }
+
+        // the player is only added once it knows about the session
+        if let Err(error) = self.welcome(&player) {
+            self.transport.disconnect(connection);
+            return Err(error);
+        }
+
+        let result = self.broadcast_raw(&joined, None);
+
+        self.players.insert(connection, player);
+        self.events
+            .push(SessionEvent::PlayerConnected(PlayerConnected {
+                client: connection,
+                slot,
+            }));
+
+        result
+    }
+
+    /// Send the id of a joining player and all players of the session to it, one message per
+    /// player so that the messages don't grow with the session.
+    fn welcome(&mut self, player: &PlayerInfo) -> Result<(), NetError> {
+        let mut writer = MessageKind::Welcome.writer();
+        writer.write_u64(player.client);
+        self.transport.send(
+            player.client,
+            Channel::ReliableOrdered,
+            &writer.into_bytes(),
+        )?;
+
+        let mut players: Vec<_> = self
+            .players
+            .values()
+            .chain(std::iter::once(player))
+            .collect();
+        players.sort_by_key(|player| player.slot);
+
+        for other in players {
+            let mut writer = MessageKind::PlayerJoined.writer();
+            write_player(&mut writer, other);
+            self.transport.send(
+                player.client,
+                Channel::ReliableOrdered,
+                &writer.into_bytes(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Tell the client why it can't join and close the connection. If the rejection gets lost, the
+    /// client only notices the closed connection.
+    fn reject(&mut self, connection: ConnectionId, reason: RejectReason) -> Result<(), NetError> {
+        let mut writer = MessageKind::Reject.writer();
+        writer.write_u8(reason as u8);
+
+        let result =
+            self.transport
+                .send(connection, Channel::ReliableOrdered, &writer.into_bytes());
+        self.transport.disconnect(connection);
+
+        result
+    }
+
+    fn remove_player(&mut self, client: ClientId) {
+        let Some(player) = self.players.remove(&client) else {
+            return;
+        };
+
+        let mut writer = MessageKind::PlayerLeft.writer();
+        writer.write_u64(client);
+        // players that could not be informed are disconnected and leave the session as well
+        let _ = self.broadcast_raw(&writer.into_bytes(), None);
+
+        self.events
+            .push(SessionEvent::PlayerDisconnected(PlayerDisconnected {
+                client,
+                slot: player.slot,
+            }));
+    }
+
+    fn handle_message(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        payload: &[u8],
+    ) -> Result<(), NetError> {
+        let mut reader = WireReader::new(payload);
+        let Some(kind) = reader.read_u8().and_then(MessageKind::from_byte) else {
+            return Ok(());
+        };
+
+        let is_player = self.players.contains_key(&connection);
+
+        match kind {
+            MessageKind::Join if !is_player => {
+                if let Some(metadata) = read_metadata(&mut reader) {
+                    return self.join(connection, metadata);
+                }
+            }
+            MessageKind::Leave if is_player => self.kick(connection),
+            MessageKind::User if is_player => {
+                self.events.push(SessionEvent::Message(SessionMessage {
+                    client: connection,
+                    channel,
+                    payload: reader.read_remaining().to_vec(),
+                }));
+            }
+            MessageKind::Typed if is_player => {
+                self.events
+                    .extend(read_typed_message(connection, &mut reader));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl<TransportType: Transport> Session for ServerSession<TransportType> {
+    fn poll(&mut self) -> Result<(), NetError> {
+        let mut result = self.transport.poll();
+
+        for event in self.transport.drain_events() {
+            match event {
+                // a connection only becomes a player after its join request
+                TransportEvent::Connected(_) => {}
+                TransportEvent::Disconnected(connection) => self.remove_player(connection),
+                TransportEvent::Message {
+                    connection,
+                    channel,
+                    payload,
+                } => {
+                    if let Err(error) = self.handle_message(connection, channel, &payload) {
+                        result = Err(error);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    fn drain_events(&mut self) -> Vec<SessionEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn message_registry(&self) -> &MessageRegistry {
+        &self.messages
+    }
+}
+
+/// Client side of a session. Joins a server with some metadata and keeps track of all players in
+/// the session.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::net::{ClientSession, LoopbackTransport, PlayerMetadata};
+///
+/// let (_server_transport, transport) = LoopbackTransport::pair();
+///
+/// let mut session = ClientSession::new(transport);
+/// let metadata = PlayerMetadata::from([("name".to_owned(), "Player".to_owned())]);
+/// session.join((), metadata).expect("Failed to join");
+/// ```
+pub struct ClientSession<TransportType> {
+    transport: TransportType,
+    connection: Option<ConnectionId>,
+    join_metadata: PlayerMetadata,
+    client_id: Option<ClientId>,
+    players: HashMap<ClientId, PlayerInfo>,
+    messages: MessageRegistry,
+    events: Vec<SessionEvent>,
+}
+
+impl<TransportType: Transport> ClientSession<TransportType> {
+    pub fn new(transport: TransportType) -> Self {
+        Self {
+            transport,
+            connection: None,
+            join_metadata: PlayerMetadata::new(),
+            client_id: None,
+            players: HashMap::new(),
+            messages: MessageRegistry::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Set the typed messages this session can send and receive.
+    #[must_use]
+    pub fn with_messages(mut self, messages: MessageRegistry) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Connect to a server and ask to join its session. A [`PlayerConnected`] event for this
+    /// client is sent once the server accepted, a [`JoinRejected`] event otherwise. If the client
+    /// already is in a session, it leaves that session first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport could not connect.
+    pub fn join(
+        &mut self,
+        address: TransportType::Address,
+        metadata: PlayerMetadata,
+    ) -> Result<(), NetError> {
+        self.leave();
+
+        self.connection = Some(self.transport.connect(address)?);
+        self.join_metadata = metadata;
+
+        Ok(())
+    }
+
+    /// Leave the current session. [`PlayerDisconnected`] events are sent for all players.
+    pub fn leave(&mut self) {
+        let Some(connection) = self.connection else {
+            return;
+        };
+
+        if self.transport.is_connected(connection) {
+            // the server notices the closed connection anyway if this gets lost
+            let _ = self.transport.send(
+                connection,
+                Channel::ReliableOrdered,
+                &MessageKind::Leave.writer().into_bytes(),
+            );
+        }
+
+        self.transport.disconnect(connection);
+        self.reset();
+    }
+
+    /// The id of this client, if it is in a session.
+    #[must_use]
+    pub const fn client_id(&self) -> Option<ClientId> {
+        self.client_id
+    }
+
+    /// The slot of this client, if it is in a session.
+    #[must_use]
+    pub fn slot(&self) -> Option<PlayerSlot> {
+        self.players.get(&self.client_id?).map(|player| player.slot)
+    }
+
+    #[must_use]
+    pub const fn is_joined(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    /// Iterate over all players in the session (including this client), in no particular order.
+    pub fn players(&self) -> impl Iterator<Item = &PlayerInfo> {
+        self.players.values()
+    }
+
+    #[must_use]
+    pub fn player(&self, client: ClientId) -> Option<&PlayerInfo> {
+        self.players.get(&client)
+    }
+
+    pub const fn transport(&self) -> &TransportType {
+        &self.transport
+    }
+
+    pub fn transport_mut(&mut self) -> &mut TransportType {
+        &mut self.transport
+    }
+
+    /// Send a message to the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not in a session or sending failed.
+    pub fn send(&mut self, channel: Channel, payload: &[u8]) -> Result<(), NetError> {
+        let connection = self
+            .connection
+            .filter(|_| self.is_joined())
+            .ok_or(NetError::NotJoined)?;
+
+        self.transport
+            .send(connection, channel, &user_message(payload))
+    }
+
+    /// Send a typed message to the server, on the channel it was registered with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not in a session, the message type is not registered or
+    /// the message could not be encoded or sent.
+    pub fn send_message<MessageType: Serialize + 'static>(
+        &mut self,
+        message: &MessageType,
+    ) -> Result<(), NetError> {
+        let connection = self
+            .connection
+            .filter(|_| self.is_joined())
+            .ok_or(NetError::NotJoined)?;
+        let (channel, payload) = typed_message(&self.messages, message)?;
+
+        self.transport.send(connection, channel, &payload)
+    }
+
+    fn reset(&mut self) {
+        self.connection = None;
+        self.client_id = None;
+
+        let mut players: Vec<_> = self.players.drain().map(|(_, player)| player).collect();
+        players.sort_by_key(|player| player.slot);
+
+        self.events.extend(players.into_iter().map(|player| {
+            SessionEvent::PlayerDisconnected(PlayerDisconnected {
+                client: player.client,
+                slot: player.slot,
+            })
+        }));
+    }
+
+    fn add_player(&mut self, player: PlayerInfo) {
+        self.events
+            .push(SessionEvent::PlayerConnected(PlayerConnected {
+                client: player.client,
+                slot: player.slot,
+            }));
+        self.players.insert(player.client, player);
+    }
+
+    fn handle_message(&mut self, channel: Channel, payload: &[u8]) -> Option<()> {
+        let mut reader = WireReader::new(payload);
+
+        match MessageKind::from_byte(reader.read_u8()?)? {
+            // the players of the session follow as `PlayerJoined` messages
+            MessageKind::Welcome => self.client_id = Some(reader.read_u64()?),
+            MessageKind::Reject => {
+                let reason = match reader.read_u8()? {
+                    0 => RejectReason::SessionFull,
+                    1 => RejectReason::MetadataTooLarge,
+                    _ => return None,
+                };
+
+                self.events
+                    .push(SessionEvent::JoinRejected(JoinRejected(reason)));
+                self.leave();
+            }
+            MessageKind::PlayerJoined if self.is_joined() => {
+                let player = read_player(&mut reader)?;
+
+                if !self.players.contains_key(&player.client) {
+                    self.add_player(player);
+                }
+            }
+            MessageKind::PlayerLeft if self.is_joined() => {
+                let player = self.players.remove(&reader.read_u64()?)?;
+
+                self.events
+                    .push(SessionEvent::PlayerDisconnected(PlayerDisconnected {
+                        client: player.client,
+                        slot: player.slot,
+                    }));
+            }
+            MessageKind::PlayerUpdated if self.is_joined() => {
+                let player = read_player(&mut reader)?;
+
+                if let Some(existing) = self.players.get_mut(&player.client) {
+                    *existing = player;
+                }
+            }
+            MessageKind::User if self.is_joined() => {
+                self.events.push(SessionEvent::Message(SessionMessage {
+                    client: SERVER_ID,
+                    channel,
+                    payload: reader.read_remaining().to_vec(),
+                }));
+            }
+            MessageKind::Typed if self.is_joined() => {
+                self.events
+                    .push(read_typed_message(SERVER_ID, &mut reader)?);
+            }
+            _ => {}
+        }
+
+        Some(())
+    }
+}
+
+impl<TransportType: Transport> Session for ClientSession<TransportType> {
+    fn poll(&mut self) -> Result<(), NetError> {
+        let mut result = self.transport.poll();
+
+        for event in self.transport.drain_events() {
+            match event {
+                TransportEvent::Connected(connection) if Some(connection) == self.connection => {
+                    let mut writer = MessageKind::Join.writer();
+                    write_metadata(&mut writer, &self.join_metadata);
+
+                    if let Err(error) = self.transport.send(
+                        connection,
+                        Channel::ReliableOrdered,
+                        &writer.into_bytes(),
+                    ) {
+                        result = Err(error);
+                    }
+                }
+                TransportEvent::Disconnected(connection) if Some(connection) == self.connection => {
+                    self.reset();
+                }
+                TransportEvent::Message {
+                    connection,
+                    channel,
+                    payload,
+                } if Some(connection) == self.connection => {
+                    // malformed messages from the server are ignored
+                    let _ = self.handle_message(channel, &payload);
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    fn drain_events(&mut self) -> Vec<SessionEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn message_registry(&self) -> &MessageRegistry {
+        &self.messages
+    }
+}
+
+/// Drives a session that is stored as a resource. Every update the session is polled and its
+/// events are sent as ECS events: [`PlayerConnected`], [`PlayerDisconnected`],
+/// [`SessionMessage`], [`JoinRejected`] and [`MessageReceived`](crate::net::MessageReceived)
+/// for typed messages. Errors are sent as [`NetError`] events.
+pub struct SessionSystem<SessionType> {
+    marker: PhantomData<SessionType>,
+}
+
+impl<SessionType: Session + 'static> System for SessionSystem<SessionType> {
+    fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let Some(session) = storage.get_resource_mut::<SessionType>() else {
+            return;
+        };
+
+        let result = session.poll();
+
+        // look up the dispatchers of typed messages while the session is still borrowed
+        let events: Vec<_> = session
+            .drain_events()
+            .into_iter()
+            .map(|event| {
+                let dispatch = match &event {
+                    SessionEvent::TypedMessage { message, .. } => {
+                        session.message_registry().dispatcher(*message)
+                    }
+                    _ => None,
+                };
+
+                (event, dispatch)
+            })
+            .collect();
+
+        for (event, dispatch) in events {
+            match event {
+                SessionEvent::PlayerConnected(event) => storage.send_event(event),
+                SessionEvent::PlayerDisconnected(event) => storage.send_event(event),
+                SessionEvent::Message(event) => storage.send_event(event),
+                SessionEvent::JoinRejected(event) => storage.send_event(event),
+                SessionEvent::TypedMessage {
+                    client, payload, ..
+                } => {
+                    // messages of unknown types are dropped, the peers use different registries
+                    if let Some(Err(error)) =
+                        dispatch.map(|dispatch| dispatch(storage, client, &payload))
+                    {
+                        storage.send_event(error);
+                    }
+                }
+            }
+        }
+
+        if let Err(error) = result {
+            storage.send_event(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{LoopbackTransport, MessageReceived, UdpTransport};
+    use std::time::{Duration, Instant};
+
+    fn metadata(name: &str) -> PlayerMetadata {
+        PlayerMetadata::from([("name".to_owned(), name.to_owned())])
+    }
+
+    fn loopback_session() -> (
+        ServerSession<LoopbackTransport>,
+        ClientSession<LoopbackTransport>,
+    ) {
+        let (server_transport, client_transport) = LoopbackTransport::pair();
+        let mut server = ServerSession::new(server_transport, 4);
+        let mut client = ClientSession::new(client_transport);
+
+        client.join((), metadata("alice")).unwrap();
+
+        // connect, join request, welcome
+        for _ in 0..3 {
+            server.poll().unwrap();
+            client.poll().unwrap();
+        }
+
+        (server, client)
+    }
+
+    /// Poll all sessions until the condition holds or a second has passed.
+    fn poll_until(
+        server: &mut ServerSession<UdpTransport>,
+        clients: &mut [&mut ClientSession<UdpTransport>],
+        mut condition: impl FnMut(&ServerSession<UdpTransport>) -> bool,
+    ) {
+        let start = Instant::now();
+
+        while !condition(server) {
+            assert!(
+                start.elapsed() < Duration::from_secs(1),
+                "Condition not met in time."
+            );
+
+            server.poll().unwrap();
+            clients.iter_mut().for_each(|client| client.poll().unwrap());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // let the clients process everything the server sent last
+        std::thread::sleep(Duration::from_millis(10));
+        clients.iter_mut().for_each(|client| client.poll().unwrap());
+    }
+
+    #[test]
+    fn metadata_is_read_as_written() {
+        let player = PlayerInfo {
+            client: 3,
+            slot: 1,
+            metadata: PlayerMetadata::from([
+                ("name".to_owned(), "alice".to_owned()),
+                ("team".to_owned(), "red".to_owned()),
+            ]),
+        };
+
+        let mut writer = WireWriter::default();
+        write_player(&mut writer, &player);
+        let bytes = writer.into_bytes();
+
+        assert_eq!(read_player(&mut WireReader::new(&bytes)), Some(player));
+        assert_eq!(read_player(&mut WireReader::new(&bytes[..8])), None);
+    }
+
+    #[test]
+    fn join_adds_player_on_server_and_client() {
+        let (mut server, mut client) = loopback_session();
+
+        let client_id = client.client_id().unwrap();
+        let expected = PlayerConnected {
+            client: client_id,
+            slot: 0,
+        };
+
+        assert_eq!(
+            server.drain_events(),
+            vec![SessionEvent::PlayerConnected(expected)]
+        );
+        assert_eq!(
+            client.drain_events(),
+            vec![SessionEvent::PlayerConnected(expected)]
+        );
+
+        assert_eq!(
+            server.player(client_id).unwrap().metadata,
+            metadata("alice")
+        );
+        assert_eq!(
+            client.player(client_id).unwrap().metadata,
+            metadata("alice")
+        );
+        assert_eq!(client.slot(), Some(0));
+    }
+
+    #[test]
+    fn messages_are_delivered_in_both_directions() {
+        let (mut server, mut client) = loopback_session();
+        server.drain_events();
+        client.drain_events();
+        let client_id = client.client_id().unwrap();
+
+        client.send(Channel::ReliableOrdered, b"ping").unwrap();
+        server.poll().unwrap();
+        server
+            .send(client_id, Channel::Unreliable, b"pong")
+            .unwrap();
+        client.poll().unwrap();
+
+        assert_eq!(
+            server.drain_events(),
+            vec![SessionEvent::Message(SessionMessage {
+                client: client_id,
+                channel: Channel::ReliableOrdered,
+                payload: b"ping".to_vec(),
+            })]
+        );
+        assert_eq!(
+            client.drain_events(),
+            vec![SessionEvent::Message(SessionMessage {
+                client: SERVER_ID,
+                channel: Channel::Unreliable,
+                payload: b"pong".to_vec(),
+            })]
+        );
+    }
+
+    #[test]
+    fn send_fails_before_joining() {
+        let (_server_transport, client_transport) = LoopbackTransport::pair();
+        let mut client = ClientSession::new(client_transport);
+
+        assert!(matches!(
+            client.send(Channel::ReliableOrdered, b"ping"),
+            Err(NetError::NotJoined)
+        ));
+    }
+
+    #[test]
+    fn leave_removes_player_on_server_and_client() {
+        let (mut server, mut client) = loopback_session();
+        server.drain_events();
+        client.drain_events();
+        let client_id = client.client_id().unwrap();
+
+        client.leave();
+        server.poll().unwrap();
+
+        let expected = PlayerDisconnected {
+            client: client_id,
+            slot: 0,
+        };
+
+        assert_eq!(
+            client.drain_events(),
+            vec![SessionEvent::PlayerDisconnected(expected)]
+        );
+        assert_eq!(
+            server.drain_events(),
+            vec![SessionEvent::PlayerDisconnected(expected)]
+        );
+        assert!(!client.is_joined());
+        assert_eq!(server.player_count(), 0);
+    }
+
+    #[test]
+    fn set_metadata_updates_clients() {
+        let (mut server, mut client) = loopback_session();
+        let client_id = client.client_id().unwrap();
+
+        server.set_metadata(client_id, "team", "blue").unwrap();
+        client.poll().unwrap();
+
+        assert_eq!(
+            client.player(client_id).unwrap().metadata["team"],
+            "blue".to_owned()
+        );
+        assert!(server.set_metadata(42, "team", "red").is_err());
+    }
+
+    #[test]
+    fn players_are_informed_about_each_other() {
+        let mut server = ServerSession::new(UdpTransport::bind("127.0.0.1:0").unwrap(), 4);
+        let address = server.transport().local_addr().unwrap();
+
+        let mut alice = ClientSession::new(UdpTransport::bind("127.0.0.1:0").unwrap());
+        let mut bob = ClientSession::new(UdpTransport::bind("127.0.0.1:0").unwrap());
+
+        alice.join(address, metadata("alice")).unwrap();
+        poll_until(&mut server, &mut [&mut alice, &mut bob], |server| {
+            server.player_count() == 1
+        });
+
+        bob.join(address, metadata("bob")).unwrap();
+        poll_until(&mut server, &mut [&mut alice, &mut bob], |server| {
+            server.player_count() == 2
+        });
+
+        assert_eq!(alice.slot(), Some(0));
+        assert_eq!(bob.slot(), Some(1));
+        assert_eq!(alice.players().count(), 2);
+        assert_eq!(bob.players().count(), 2);
+        assert_eq!(
+            alice.player(bob.client_id().unwrap()).unwrap().metadata,
+            metadata("bob")
+        );
+
+        alice.drain_events();
+        let alice_id = alice.client_id().unwrap();
+        alice.leave();
+        poll_until(&mut server, &mut [&mut alice, &mut bob], |server| {
+            server.player_count() == 1
+        });
+
+        assert!(bob
+            .drain_events()
+            .contains(&SessionEvent::PlayerDisconnected(PlayerDisconnected {
+                client: alice_id,
+                slot: 0,
+            })));
+        assert_eq!(bob.players().count(), 1);
+    }
+
+    #[test]
+    fn join_is_rejected_if_session_is_full() {
+        let mut server = ServerSession::new(UdpTransport::bind("127.0.0.1:0").unwrap(), 1);
+        let address = server.transport().local_addr().unwrap();
+
+        let mut alice = ClientSession::new(UdpTransport::bind("127.0.0.1:0").unwrap());
+        let mut bob = ClientSession::new(UdpTransport::bind("127.0.0.1:0").unwrap());
+
+        alice.join(address, metadata("alice")).unwrap();
+        poll_until(&mut server, &mut [&mut alice, &mut bob], |server| {
+            server.player_count() == 1
+        });
+
+        bob.join(address, metadata("bob")).unwrap();
+        let start = Instant::now();
+
+        while !bob
+            .drain_events()
+            .contains(&SessionEvent::JoinRejected(JoinRejected(
+                RejectReason::SessionFull,
+            )))
+        {
+            assert!(
+                start.elapsed() < Duration::from_secs(1),
+                "Join not rejected."
+            );
+
+            server.poll().unwrap();
+            bob.poll().unwrap();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert!(!bob.is_joined());
+        assert_eq!(server.player_count(), 1);
+    }
+
+    #[test]
+    fn rejected_connections_are_closed() {
+        let (server_transport, client_transport) = LoopbackTransport::pair();
+        let mut server = ServerSession::new(server_transport, 4);
+        let mut client = ClientSession::new(client_transport);
+        let name = "a".repeat(MAX_PAYLOAD_SIZE);
+
+        client.join((), metadata(&name)).unwrap();
+        for _ in 0..3 {
+            server.poll().unwrap();
+            client.poll().unwrap();
+        }
+
+        assert!(client
+            .drain_events()
+            .contains(&SessionEvent::JoinRejected(JoinRejected(
+                RejectReason::MetadataTooLarge
+            ))));
+        assert!(!server.transport().is_connected(0));
+        assert_eq!(server.player_count(), 0);
+    }
+
+    #[test]
+    fn large_sessions_fit_into_packets() {
+        let mut server = ServerSession::new(UdpTransport::bind("127.0.0.1:0").unwrap(), 64);
+        let address = server.transport().local_addr().unwrap();
+        let name = "a".repeat(40);
+
+        // players without a connection, which are enough to exceed a single packet
+        for slot in 0..40 {
+            let client = 1000 + u64::from(slot);
+            server.players.insert(
+                client,
+                PlayerInfo {
+                    client,
+                    slot,
+                    metadata: metadata(&name),
+                },
+            );
+        }
+
+        let mut alice = ClientSession::new(UdpTransport::bind("127.0.0.1:0").unwrap());
+        alice.join(address, metadata("alice")).unwrap();
+        let start = Instant::now();
+
+        while alice.players().count() < 41 {
+            assert!(
+                start.elapsed() < Duration::from_secs(1),
+                "Players not received."
+            );
+
+            // sending to the players without a connection fails
+            let _ = server.poll();
+            alice.poll().unwrap();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(alice.slot(), Some(40));
+        assert_eq!(server.player_count(), 41);
+    }
+
+    #[test]
+    fn typed_messages_are_sent_as_ecs_events() {
+        #[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
+        struct ChatMessage(String);
+
+        fn protocol() -> MessageRegistry {
+            let mut registry = MessageRegistry::new();
+            registry.register::<ChatMessage>(Channel::ReliableOrdered);
+            registry
+        }
+
+        let (server_transport, client_transport) = LoopbackTransport::pair();
+        let mut server = ServerSession::new(server_transport, 4).with_messages(protocol());
+        let mut client = ClientSession::new(client_transport).with_messages(protocol());
+
+        client.join((), metadata("alice")).unwrap();
+
+        for _ in 0..3 {
+            server.poll().unwrap();
+            client.poll().unwrap();
+        }
+
+        let client_id = client.client_id().unwrap();
+        client
+            .send_message(&ChatMessage("hello".to_owned()))
+            .unwrap();
+        server
+            .broadcast_message(&ChatMessage("welcome".to_owned()))
+            .unwrap();
+        assert!(matches!(
+            client.send_message(&42),
+            Err(NetError::UnregisteredMessage(_))
+        ));
+
+        let mut server_storage = Storage::new();
+        server_storage.insert_resource(server);
+        SessionSystem::<ServerSession<LoopbackTransport>>::new().update(&mut server_storage);
+
+        let mut client_storage = Storage::new();
+        client_storage.insert_resource(client);
+        SessionSystem::<ClientSession<LoopbackTransport>>::new().update(&mut client_storage);
+
+        assert_eq!(
+            server_storage
+                .drain_events::<MessageReceived<ChatMessage>>()
+                .collect::<Vec<_>>(),
+            vec![MessageReceived {
+                client: client_id,
+                message: ChatMessage("hello".to_owned()),
+            }]
+        );
+        assert_eq!(
+            client_storage
+                .drain_events::<MessageReceived<ChatMessage>>()
+                .collect::<Vec<_>>(),
+            vec![MessageReceived {
+                client: SERVER_ID,
+                message: ChatMessage("welcome".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn session_system_sends_ecs_events() {
+        let (server, mut client) = loopback_session();
+        let client_id = client.client_id().unwrap();
+
+        let mut storage = Storage::new();
+        storage.insert_resource(server);
+        let mut system = SessionSystem::<ServerSession<LoopbackTransport>>::new();
+
+        client.send(Channel::ReliableOrdered, b"ping").unwrap();
+        system.update(&mut storage);
+
+        assert_eq!(
+            storage
+                .drain_events::<PlayerConnected>()
+                .collect::<Vec<_>>(),
+            vec![PlayerConnected {
+                client: client_id,
+                slot: 0,
+            }]
+        );
+        assert_eq!(storage.drain_events::<SessionMessage>().count(), 1);
+    }
+}
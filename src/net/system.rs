@@ -0,0 +1,92 @@
+use crate::ecs::{Storage, System};
+use crate::net::Transport;
+use std::marker::PhantomData;
+
+/// Drives a transport that is stored as a resource. Every update the transport is polled and all
+/// of its events are sent as [`TransportEvent`](crate::net::TransportEvent) ECS events. Errors of
+/// the transport are sent as [`NetError`](crate::net::NetError) events.
+///
+/// # Example
+///
+/// ```
+/// use game_engine::ecs::{System, World};
+/// use game_engine::net::{LoopbackTransport, TransportSystem};
+///
+/// let (server, _client) = LoopbackTransport::pair();
+///
+/// let mut world = World::init().expect("Failed to initialize world");
+/// world.storage.insert_resource(server);
+/// world.add_system(TransportSystem::<LoopbackTransport>::new());
+/// ```
+pub struct TransportSystem<TransportType> {
+    marker: PhantomData<TransportType>,
+}
+
+impl<TransportType: Transport + 'static> System for TransportSystem<TransportType> {
+    fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let Some(transport) = storage.get_resource_mut::<TransportType>() else {
+            return;
+        };
+
+        let result = transport.poll();
+        let events = transport.drain_events();
+
+        for event in events {
+            storage.send_event(event);
+        }
+
+        if let Err(error) = result {
+            storage.send_event(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{Channel, LoopbackTransport, TransportEvent};
+
+    #[test]
+    fn update_forwards_transport_events() {
+        let (server, mut client) = LoopbackTransport::pair();
+        let mut storage = Storage::new();
+        storage.insert_resource(server);
+        storage
+            .get_resource_mut::<LoopbackTransport>()
+            .unwrap()
+            .accept_incoming(true);
+
+        let mut system = TransportSystem::<LoopbackTransport>::new();
+
+        let connection = client.connect(()).unwrap();
+        system.update(&mut storage);
+        client.poll().unwrap();
+        client
+            .send(connection, Channel::ReliableOrdered, b"hello")
+            .unwrap();
+        system.update(&mut storage);
+
+        let events: Vec<_> = storage.drain_events::<TransportEvent>().collect();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], TransportEvent::Connected(_)));
+        assert!(
+            matches!(&events[1], TransportEvent::Message { payload, .. } if payload == b"hello")
+        );
+    }
+
+    #[test]
+    fn update_without_transport_does_nothing() {
+        let mut storage = Storage::new();
+        let mut system = TransportSystem::<LoopbackTransport>::new();
+
+        system.update(&mut storage);
+
+        assert_eq!(storage.read_events::<TransportEvent>().count(), 0);
+    }
+}
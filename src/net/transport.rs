@@ -0,0 +1,137 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+/// A unique id for a connection. Ids are local to the transport that created the connection, two
+/// peers generally use different ids for the same connection.
+pub type ConnectionId = u64;
+
+/// The delivery guarantees of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Messages arrive exactly once and in the order they were sent.
+    ReliableOrdered,
+    /// Messages may be lost or arrive out of order, but are never delayed by lost messages. Use
+    /// this for data that is outdated quickly, like positions that are sent every frame.
+    Unreliable,
+}
+
+/// Something that happened on a transport since it was last polled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportEvent {
+    /// A connection was established, either by a [`Transport::connect`] call of this peer or by
+    /// accepting an incoming connection.
+    Connected(ConnectionId),
+    /// A connection was closed by either peer, timed out, or could not be established at all.
+    Disconnected(ConnectionId),
+    /// A message was received on an established connection.
+    Message {
+        connection: ConnectionId,
+        channel: Channel,
+        payload: Vec<u8>,
+    },
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    /// The connection does not exist or is not established yet.
+    NotConnected(ConnectionId),
+    /// The client has not joined a session yet.
+    NotJoined,
+    PayloadTooLarge {
+        size: usize,
+        max: usize,
+    },
+    /// The message type was not registered in the [`MessageRegistry`](crate::net::MessageRegistry).
+    UnregisteredMessage(&'static str),
+    /// A typed message could not be encoded or decoded.
+    Encoding(bincode::Error),
+}
+
+impl Display for NetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "network io error: {error}"),
+            Self::NotConnected(connection) => {
+                write!(f, "connection {connection} is not established")
+            }
+            Self::NotJoined => write!(f, "not joined to a session"),
+            Self::PayloadTooLarge { size, max } => {
+                write!(
+                    f,
+                    "payload of {size} bytes exceeds the maximum of {max} bytes"
+                )
+            }
+            Self::UnregisteredMessage(name) => write!(f, "message type {name} is not registered"),
+            Self::Encoding(error) => write!(f, "message encoding error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for NetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Encoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Base trait for network transports. A transport manages connections to other peers and
+/// delivers byte messages over them. Transports never block: all incoming data is processed in
+/// [`Transport::poll`], which should be called once per frame, and the results are collected as
+/// [`TransportEvent`]s.
+pub trait Transport {
+    /// How a remote peer is addressed when connecting to it.
+    type Address;
+
+    /// Start connecting to a remote peer. The returned connection can be used for sending once a
+    /// [`TransportEvent::Connected`] event was reported for it. If the peer does not accept the
+    /// connection, a [`TransportEvent::Disconnected`] event is reported instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection request could not be sent.
+    fn connect(&mut self, address: Self::Address) -> Result<ConnectionId, NetError>;
+
+    /// Set whether incoming connections are accepted. Transports start out not accepting
+    /// connections, so only peers acting as a server need to call this.
+    fn accept_incoming(&mut self, accept: bool);
+
+    /// Close a connection. A [`TransportEvent::Disconnected`] event is reported on both peers.
+    fn disconnect(&mut self, connection: ConnectionId);
+
+    /// Send a message to the peer of a connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection is not established, the payload is too large for the
+    /// transport, or sending failed.
+    fn send(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        payload: &[u8],
+    ) -> Result<(), NetError>;
+
+    /// Process incoming data and timers (resending unacknowledged messages, timeouts, ...).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying connection failed. Events collected before the error
+    /// are kept.
+    fn poll(&mut self) -> Result<(), NetError>;
+
+    /// Remove all events collected since the last call and return them in the order they happened.
+    fn drain_events(&mut self) -> Vec<TransportEvent>;
+
+    /// Returns true if the connection exists and is established.
+    fn is_connected(&self, connection: ConnectionId) -> bool;
+}
@@ -0,0 +1,778 @@
+use crate::net::reliability::{ReliableReceiver, ReliableSender, Sequence};
+use crate::net::{Channel, ConnectionId, NetError, Transport, TransportEvent};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// The maximum size of a single message. Larger messages are not split up by the transport, so
+/// this is kept below the common internet MTU to avoid IP fragmentation.
+pub const MAX_PAYLOAD_SIZE: usize = 1200;
+
+/// Prefix of every packet, packets from other applications are ignored.
+const PROTOCOL_ID: [u8; 2] = *b"GE";
+const HEADER_SIZE: usize = PROTOCOL_ID.len() + 1;
+const SEQUENCE_SIZE: usize = std::mem::size_of::<Sequence>();
+const NONCE_SIZE: usize = std::mem::size_of::<Nonce>();
+const RECEIVE_BUFFER_SIZE: usize = HEADER_SIZE + SEQUENCE_SIZE + MAX_PAYLOAD_SIZE;
+
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(250);
+const RESEND_INTERVAL: Duration = Duration::from_millis(100);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum PacketKind {
+    Connect = 0,
+    Accept = 1,
+    Disconnect = 2,
+    Heartbeat = 3,
+    Unreliable = 4,
+    Reliable = 5,
+    Ack = 6,
+}
+
+impl PacketKind {
+    const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Connect),
+            1 => Some(Self::Accept),
+            2 => Some(Self::Disconnect),
+            3 => Some(Self::Heartbeat),
+            4 => Some(Self::Unreliable),
+            5 => Some(Self::Reliable),
+            6 => Some(Self::Ack),
+            _ => None,
+        }
+    }
+}
+
+fn encode_packet(kind: PacketKind, sequence: Option<Sequence>, payload: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(HEADER_SIZE + SEQUENCE_SIZE + payload.len());
+    packet.extend_from_slice(&PROTOCOL_ID);
+    packet.push(kind as u8);
+
+    if let Some(sequence) = sequence {
+        packet.extend_from_slice(&sequence.to_be_bytes());
+    }
+
+    packet.extend_from_slice(payload);
+    packet
+}
+
+fn split_sequence(body: &[u8]) -> Option<(Sequence, &[u8])> {
+    let (sequence, rest) = body.split_first_chunk::<SEQUENCE_SIZE>()?;
+
+    Some((Sequence::from_be_bytes(*sequence), rest))
+}
+
+/// Random number that a peer picks for every connection attempt. It is sent with Connect and
+/// Accept packets, so that a new connection from the same address can be told apart from a
+/// stale one, e.g. when the peer restarted and its Disconnect packet got lost.
+type Nonce = u64;
+
+fn new_nonce() -> Nonce {
+    Uuid::new_v4().as_u64_pair().0
+}
+
+fn read_nonce(body: &[u8]) -> Option<Nonce> {
+    body.first_chunk::<NONCE_SIZE>()
+        .map(|nonce| Nonce::from_be_bytes(*nonce))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConnectionState {
+    Connecting,
+    Connected,
+}
+
+struct Connection {
+    address: SocketAddr,
+    state: ConnectionState,
+    nonce: Nonce,
+    reliable_sender: ReliableSender,
+    reliable_receiver: ReliableReceiver,
+    last_received: Instant,
+    last_sent: Instant,
+}
+
+impl Connection {
+    fn new(address: SocketAddr, state: ConnectionState, nonce: Nonce, now: Instant) -> Self {
+        Self {
+            address,
+            state,
+            nonce,
+            reliable_sender: ReliableSender::default(),
+            reliable_receiver: ReliableReceiver::default(),
+            last_received: now,
+            last_sent: now,
+        }
+    }
+}
+
+/// Transport that talks to remote peers over UDP. Reliable-ordered messages are numbered,
+/// acknowledged by the receiver and resent until they are acknowledged. Connections are kept
+/// alive with heartbeats and closed if nothing was received from the peer within the timeout.
+///
+/// # Example
+///
+/// ```no_run
+/// use game_engine::net::{Transport, UdpTransport};
+///
+/// let mut server = UdpTransport::bind("0.0.0.0:7777").expect("Failed to bind server socket");
+/// server.accept_incoming(true);
+///
+/// let mut client = UdpTransport::bind("0.0.0.0:0").expect("Failed to bind client socket");
+/// let connection = client
+///     .connect("127.0.0.1:7777".parse().unwrap())
+///     .expect("Failed to connect");
+/// ```
+pub struct UdpTransport {
+    socket: UdpSocket,
+    connections: HashMap<ConnectionId, Connection>,
+    addresses: HashMap<SocketAddr, ConnectionId>,
+    next_connection_id: ConnectionId,
+    accepting: bool,
+    timeout: Duration,
+    events: Vec<TransportEvent>,
+}
+
+impl UdpTransport {
+    /// Create a transport on a local socket address. Use port 0 to let the operating system pick a
+    /// free port, which is usually what clients want.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket could not be bound.
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, NetError> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            connections: HashMap::new(),
+            addresses: HashMap::new(),
+            next_connection_id: 0,
+            accepting: false,
+            timeout: DEFAULT_TIMEOUT,
+            events: Vec::new(),
+        })
+    }
+
+    /// The local address the transport is bound to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address could not be queried from the socket.
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Set how long a connection may stay silent before it is closed. This also limits how long
+    /// connecting to a peer may take.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn add_connection(
+        &mut self,
+        address: SocketAddr,
+        state: ConnectionState,
+        nonce: Nonce,
+    ) -> ConnectionId {
+        let connection_id = self.next_connection_id;
+        self.next_connection_id += 1;
+
+        self.connections.insert(
+            connection_id,
+            Connection::new(address, state, nonce, Instant::now()),
+        );
+        self.addresses.insert(address, connection_id);
+
+        connection_id
+    }
+
+    fn accept_connection(&mut self, address: SocketAddr, nonce: Nonce) -> Result<(), NetError> {
+        if !self.accepting {
+            return Ok(());
+        }
+
+        let connection_id = self.add_connection(address, ConnectionState::Connected, nonce);
+        self.events.push(TransportEvent::Connected(connection_id));
+        self.send_packet(
+            connection_id,
+            PacketKind::Accept,
+            None,
+            &nonce.to_be_bytes(),
+        )
+    }
+
+    fn remove_connection(&mut self, connection_id: ConnectionId) {
+        if let Some(connection) = self.connections.remove(&connection_id) {
+            self.addresses.remove(&connection.address);
+            self.events
+                .push(TransportEvent::Disconnected(connection_id));
+        }
+    }
+
+    fn send_packet(
+        &mut self,
+        connection_id: ConnectionId,
+        kind: PacketKind,
+        sequence: Option<Sequence>,
+        payload: &[u8],
+    ) -> Result<(), NetError> {
+        let connection = self
+            .connections
+            .get_mut(&connection_id)
+            .ok_or(NetError::NotConnected(connection_id))?;
+
+        connection.last_sent = Instant::now();
+
+        match self
+            .socket
+            .send_to(&encode_packet(kind, sequence, payload), connection.address)
+        {
+            // a full send buffer behaves like a lost packet, reliable messages are resent later
+            Err(error) if error.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(error) => Err(error.into()),
+            Ok(_) => Ok(()),
+        }
+    }
+
+    fn receive_packets(&mut self) -> Result<(), NetError> {
+        let mut buffer = [0; RECEIVE_BUFFER_SIZE];
+
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((size, address)) => self.handle_packet(address, &buffer[..size])?,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(()),
+                // reported on some platforms when a previous packet could not be delivered, the
+                // affected connection will time out on its own
+                Err(error) if error.kind() == ErrorKind::ConnectionReset => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, address: SocketAddr, packet: &[u8]) -> Result<(), NetError> {
+        let Some((header, body)) = packet.split_first_chunk::<HEADER_SIZE>() else {
+            return Ok(());
+        };
+
+        if header[..PROTOCOL_ID.len()] != PROTOCOL_ID {
+            return Ok(());
+        }
+
+        let Some(kind) = PacketKind::from_byte(header[PROTOCOL_ID.len()]) else {
+            return Ok(());
+        };
+
+        let Some(&connection_id) = self.addresses.get(&address) else {
+            if kind == PacketKind::Connect {
+                if let Some(nonce) = read_nonce(body) {
+                    self.accept_connection(address, nonce)?;
+                }
+            }
+
+            return Ok(());
+        };
+
+        let connection = self
+            .connections
+            .get_mut(&connection_id)
+            .expect("Internal transport error. Address points to invalid connection id.");
+        connection.last_received = Instant::now();
+
+        match (kind, connection.state) {
+            (PacketKind::Connect, ConnectionState::Connected) => {
+                let Some(nonce) = read_nonce(body) else {
+                    return Ok(());
+                };
+
+                if nonce == connection.nonce {
+                    // the peer did not receive our accept yet
+                    self.send_packet(
+                        connection_id,
+                        PacketKind::Accept,
+                        None,
+                        &nonce.to_be_bytes(),
+                    )?;
+                } else {
+                    // the peer started over without our connection being closed, so its
+                    // sequence numbers start at zero again
+                    self.remove_connection(connection_id);
+                    self.accept_connection(address, nonce)?;
+                }
+            }
+            // accepts of an earlier connection attempt are ignored
+            (PacketKind::Accept, ConnectionState::Connecting)
+                if read_nonce(body) == Some(connection.nonce) =>
+            {
+                connection.state = ConnectionState::Connected;
+                self.events.push(TransportEvent::Connected(connection_id));
+            }
+            (PacketKind::Disconnect, _) => self.remove_connection(connection_id),
+            (PacketKind::Unreliable, ConnectionState::Connected) => {
+                self.events.push(TransportEvent::Message {
+                    connection: connection_id,
+                    channel: Channel::Unreliable,
+                    payload: body.to_vec(),
+                });
+            }
+            (PacketKind::Reliable, ConnectionState::Connected) => {
+                let Some((sequence, payload)) = split_sequence(body) else {
+                    return Ok(());
+                };
+
+                // messages beyond the receive window are not acknowledged, so they are resent
+                let Some(deliverable) = connection
+                    .reliable_receiver
+                    .receive(sequence, payload.to_vec())
+                else {
+                    return Ok(());
+                };
+
+                self.events.extend(deliverable.into_iter().map(|payload| {
+                    TransportEvent::Message {
+                        connection: connection_id,
+                        channel: Channel::ReliableOrdered,
+                        payload,
+                    }
+                }));
+
+                // duplicates are acknowledged as well, since the previous ack might have been lost
+                self.send_packet(connection_id, PacketKind::Ack, Some(sequence), &[])?;
+            }
+            (PacketKind::Ack, ConnectionState::Connected) => {
+                if let Some((sequence, _)) = split_sequence(body) {
+                    connection.reliable_sender.acknowledge(sequence);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn update_connections(&mut self) -> Result<(), NetError> {
+        let now = Instant::now();
+        let connection_ids: Vec<_> = self.connections.keys().copied().collect();
+
+        for connection_id in connection_ids {
+            let connection = self
+                .connections
+                .get_mut(&connection_id)
+                .expect("Internal transport error. Invalid connection id.");
+
+            if now.duration_since(connection.last_received) > self.timeout {
+                self.remove_connection(connection_id);
+                continue;
+            }
+
+            let since_last_sent = now.duration_since(connection.last_sent);
+
+            match connection.state {
+                ConnectionState::Connecting => {
+                    if since_last_sent >= CONNECT_RETRY_INTERVAL {
+                        let nonce = connection.nonce.to_be_bytes();
+                        self.send_packet(connection_id, PacketKind::Connect, None, &nonce)?;
+                    }
+                }
+                ConnectionState::Connected => {
+                    let resend = connection
+                        .reliable_sender
+                        .due_for_resend(now, RESEND_INTERVAL);
+
+                    if resend.is_empty() && since_last_sent >= HEARTBEAT_INTERVAL {
+                        self.send_packet(connection_id, PacketKind::Heartbeat, None, &[])?;
+                    }
+
+                    for (sequence, payload) in resend {
+                        self.send_packet(
+                            connection_id,
+                            PacketKind::Reliable,
+                            Some(sequence),
+                            &payload,
+                        )?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Transport for UdpTransport {
+    type Address = SocketAddr;
+
+    fn connect(&mut self, address: Self::Address) -> Result<ConnectionId, NetError> {
+        if let Some(&connection_id) = self.addresses.get(&address) {
+            return Ok(connection_id);
+        }
+
+        let nonce = new_nonce();
+        let connection_id = self.add_connection(address, ConnectionState::Connecting, nonce);
+        self.send_packet(
+            connection_id,
+            PacketKind::Connect,
+            None,
+            &nonce.to_be_bytes(),
+        )?;
+
+        Ok(connection_id)
+    }
+
+    fn accept_incoming(&mut self, accept: bool) {
+        self.accepting = accept;
+    }
+
+    fn disconnect(&mut self, connection: ConnectionId) {
+        if self.connections.contains_key(&connection) {
+            // the peer times out if this gets lost
+            let _ = self.send_packet(connection, PacketKind::Disconnect, None, &[]);
+            self.remove_connection(connection);
+        }
+    }
+
+    fn send(
+        &mut self,
+        connection: ConnectionId,
+        channel: Channel,
+        payload: &[u8],
+    ) -> Result<(), NetError> {
+        if !self.is_connected(connection) {
+            return Err(NetError::NotConnected(connection));
+        }
+
+        if payload.len() > MAX_PAYLOAD_SIZE {
+            return Err(NetError::PayloadTooLarge {
+                size: payload.len(),
+                max: MAX_PAYLOAD_SIZE,
+            });
+        }
+
+        match channel {
+            Channel::Unreliable => {
+                self.send_packet(connection, PacketKind::Unreliable, None, payload)
+            }
+            Channel::ReliableOrdered => {
+                let sequence = self
+                    .connections
+                    .get_mut(&connection)
+                    .expect("Internal transport error. Invalid connection id.")
+                    .reliable_sender
+                    .push(payload.to_vec(), Instant::now());
+
+                self.send_packet(connection, PacketKind::Reliable, Some(sequence), payload)
+            }
+        }
+    }
+
+    fn poll(&mut self) -> Result<(), NetError> {
+        self.receive_packets()?;
+        self.update_connections()
+    }
+
+    fn drain_events(&mut self) -> Vec<TransportEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn is_connected(&self, connection: ConnectionId) -> bool {
+        self.connections
+            .get(&connection)
+            .is_some_and(|connection| connection.state == ConnectionState::Connected)
+    }
+}
+
+impl Drop for UdpTransport {
+    fn drop(&mut self) {
+        let connection_ids: Vec<_> = self.connections.keys().copied().collect();
+
+        for connection_id in connection_ids {
+            self.disconnect(connection_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::reliability::MAX_BUFFERED_MESSAGES;
+
+    /// Poll both transports until the condition holds or a second has passed.
+    fn poll_until(
+        server: &mut UdpTransport,
+        client: &mut UdpTransport,
+        mut condition: impl FnMut(&UdpTransport, &UdpTransport) -> bool,
+    ) {
+        let start = Instant::now();
+
+        while !condition(server, client) {
+            assert!(
+                start.elapsed() < Duration::from_secs(1),
+                "Condition not met in time."
+            );
+
+            server.poll().unwrap();
+            client.poll().unwrap();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn connected_pair() -> (UdpTransport, UdpTransport, ConnectionId) {
+        let mut server = UdpTransport::bind("127.0.0.1:0").unwrap();
+        server.accept_incoming(true);
+
+        let mut client = UdpTransport::bind("127.0.0.1:0").unwrap();
+        let connection = client.connect(server.local_addr().unwrap()).unwrap();
+
+        poll_until(&mut server, &mut client, |server, client| {
+            client.is_connected(connection) && server.connections.len() == 1
+        });
+
+        (server, client, connection)
+    }
+
+    fn received_payloads(transport: &mut UdpTransport) -> Vec<(Channel, Vec<u8>)> {
+        transport
+            .drain_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                TransportEvent::Message {
+                    channel, payload, ..
+                } => Some((channel, payload)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn encode_packet_writes_header_sequence_and_payload() {
+        let packet = encode_packet(PacketKind::Reliable, Some(258), &[42]);
+
+        assert_eq!(packet, vec![b'G', b'E', 5, 0, 0, 1, 2, 42]);
+        assert_eq!(
+            split_sequence(&packet[HEADER_SIZE..]),
+            Some((258, &[42][..]))
+        );
+    }
+
+    #[test]
+    fn connect_reports_connected_on_both_peers() {
+        let (mut server, mut client, connection) = connected_pair();
+
+        assert_eq!(
+            client.drain_events(),
+            vec![TransportEvent::Connected(connection)]
+        );
+        assert!(matches!(
+            server.drain_events().as_slice(),
+            [TransportEvent::Connected(_)]
+        ));
+    }
+
+    #[test]
+    fn connect_is_ignored_if_not_accepting() {
+        let mut server = UdpTransport::bind("127.0.0.1:0").unwrap();
+        let mut client = UdpTransport::bind("127.0.0.1:0").unwrap();
+        client.set_timeout(Duration::from_millis(50));
+
+        let connection = client.connect(server.local_addr().unwrap()).unwrap();
+
+        poll_until(&mut server, &mut client, |_, client| {
+            client.connections.is_empty()
+        });
+
+        assert!(server.drain_events().is_empty());
+        assert_eq!(
+            client.drain_events(),
+            vec![TransportEvent::Disconnected(connection)]
+        );
+    }
+
+    #[test]
+    fn send_delivers_reliable_messages_in_order() {
+        let (mut server, mut client, connection) = connected_pair();
+        server.drain_events();
+
+        for i in 0..10 {
+            client
+                .send(connection, Channel::ReliableOrdered, &[i])
+                .unwrap();
+        }
+
+        let mut received = vec![];
+        poll_until(&mut server, &mut client, |server, _| {
+            server
+                .events
+                .iter()
+                .filter(|event| matches!(event, TransportEvent::Message { .. }))
+                .count()
+                == 10
+        });
+        received.extend(received_payloads(&mut server));
+
+        let expected: Vec<_> = (0..10)
+            .map(|i| (Channel::ReliableOrdered, vec![i]))
+            .collect();
+        assert_eq!(received, expected);
+
+        poll_until(&mut server, &mut client, |_, client| {
+            client.connections[&connection]
+                .reliable_sender
+                .unacknowledged_count()
+                == 0
+        });
+    }
+
+    #[test]
+    fn send_recovers_from_a_lost_message_in_a_large_burst() {
+        let (mut server, mut client, connection) = connected_pair();
+        server.drain_events();
+        let count = MAX_BUFFERED_MESSAGES + 100;
+
+        // the first message is registered for resending, but never arrives at the server
+        client
+            .connections
+            .get_mut(&connection)
+            .unwrap()
+            .reliable_sender
+            .push(0u32.to_be_bytes().to_vec(), Instant::now());
+        // polling in between keeps the socket buffers from overflowing, which would lose acks
+        for i in 1..count {
+            client
+                .send(connection, Channel::ReliableOrdered, &i.to_be_bytes())
+                .unwrap();
+            if i % 32 == 0 {
+                server.poll().unwrap();
+                client.poll().unwrap();
+            }
+        }
+
+        let mut received = vec![];
+        let start = Instant::now();
+        while received.len() < count as usize {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "Messages not received in time."
+            );
+
+            server.poll().unwrap();
+            client.poll().unwrap();
+            received.extend(received_payloads(&mut server));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let expected: Vec<_> = (0..count)
+            .map(|i| (Channel::ReliableOrdered, i.to_be_bytes().to_vec()))
+            .collect();
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn send_delivers_unreliable_messages() {
+        let (mut server, mut client, connection) = connected_pair();
+        server.drain_events();
+
+        client
+            .send(connection, Channel::Unreliable, b"ping")
+            .unwrap();
+
+        poll_until(&mut server, &mut client, |server, _| {
+            !server.events.is_empty()
+        });
+
+        assert_eq!(
+            received_payloads(&mut server),
+            vec![(Channel::Unreliable, b"ping".to_vec())]
+        );
+    }
+
+    #[test]
+    fn send_rejects_too_large_payloads() {
+        let (_server, mut client, connection) = connected_pair();
+
+        assert!(matches!(
+            client.send(connection, Channel::Unreliable, &[0; MAX_PAYLOAD_SIZE + 1]),
+            Err(NetError::PayloadTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn send_fails_for_unknown_connection() {
+        let mut client = UdpTransport::bind("127.0.0.1:0").unwrap();
+
+        assert!(matches!(
+            client.send(42, Channel::Unreliable, &[]),
+            Err(NetError::NotConnected(42))
+        ));
+    }
+
+    #[test]
+    fn reconnecting_from_the_same_address_replaces_the_connection() {
+        let (mut server, mut client, client_connection) = connected_pair();
+        let old_connection = *server.connections.keys().next().unwrap();
+        client
+            .send(client_connection, Channel::ReliableOrdered, &[1])
+            .unwrap();
+        poll_until(&mut server, &mut client, |server, _| {
+            server.events.len() == 2
+        });
+        server.drain_events();
+
+        // the client forgets the connection without the server noticing, like after a restart
+        client.connections.clear();
+        client.addresses.clear();
+        let connection = client.connect(server.local_addr().unwrap()).unwrap();
+        poll_until(&mut server, &mut client, |_, client| {
+            client.is_connected(connection)
+        });
+        client
+            .send(connection, Channel::ReliableOrdered, &[2])
+            .unwrap();
+        poll_until(&mut server, &mut client, |server, _| {
+            server.events.len() == 3
+        });
+
+        let events = server.drain_events();
+        assert_eq!(events[0], TransportEvent::Disconnected(old_connection));
+        let TransportEvent::Connected(new_connection) = events[1] else {
+            panic!("Expected a new connection, got {:?}", events[1]);
+        };
+        assert_ne!(new_connection, old_connection);
+        assert_eq!(
+            events[2],
+            TransportEvent::Message {
+                connection: new_connection,
+                channel: Channel::ReliableOrdered,
+                payload: vec![2],
+            }
+        );
+    }
+
+    #[test]
+    fn disconnect_reports_disconnected_on_both_peers() {
+        let (mut server, mut client, connection) = connected_pair();
+        server.drain_events();
+        client.drain_events();
+
+        client.disconnect(connection);
+
+        poll_until(&mut server, &mut client, |server, _| {
+            server.connections.is_empty()
+        });
+
+        assert_eq!(
+            client.drain_events(),
+            vec![TransportEvent::Disconnected(connection)]
+        );
+        assert!(matches!(
+            server.drain_events().as_slice(),
+            [TransportEvent::Disconnected(_)]
+        ));
+    }
+}
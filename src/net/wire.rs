@@ -0,0 +1,145 @@
+/// Minimal binary encoding used for the messages of the networking layer. All integers are
+/// written in big endian byte order, strings are prefixed with their length as `u16`.
+#[derive(Default)]
+pub struct WireWriter {
+    bytes: Vec<u8>,
+}
+
+impl WireWriter {
+    pub fn write_u8(&mut self, value: u8) -> &mut Self {
+        self.bytes.push(value);
+        self
+    }
+
+    pub fn write_u16(&mut self, value: u16) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    pub fn write_u64(&mut self, value: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    /// Write a string. Strings longer than `u16::MAX` bytes are truncated at the last character
+    /// boundary that fits.
+    pub fn write_str(&mut self, value: &str) -> &mut Self {
+        let mut length = value.len().min(usize::from(u16::MAX));
+
+        while !value.is_char_boundary(length) {
+            length -= 1;
+        }
+
+        self.write_u16(u16::try_from(length).expect("Length was clamped to u16."));
+        self.bytes.extend_from_slice(&value.as_bytes()[..length]);
+        self
+    }
+
+    /// Write raw bytes without a length prefix, usually the remaining payload of a message.
+    pub fn write_bytes(&mut self, value: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(value);
+        self
+    }
+
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads values written by a [`WireWriter`]. All read methods return None if there is not enough
+/// data left, which means the message was malformed.
+pub struct WireReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    #[must_use]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn read_u8(&mut self) -> Option<u8> {
+        let (&value, rest) = self.bytes.split_first()?;
+        self.bytes = rest;
+        Some(value)
+    }
+
+    pub fn read_u16(&mut self) -> Option<u16> {
+        let (value, rest) = self.bytes.split_first_chunk()?;
+        self.bytes = rest;
+        Some(u16::from_be_bytes(*value))
+    }
+
+    pub fn read_u64(&mut self) -> Option<u64> {
+        let (value, rest) = self.bytes.split_first_chunk()?;
+        self.bytes = rest;
+        Some(u64::from_be_bytes(*value))
+    }
+
+    pub fn read_str(&mut self) -> Option<&'a str> {
+        let length = usize::from(self.read_u16()?);
+
+        if self.bytes.len() < length {
+            return None;
+        }
+
+        let (value, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        std::str::from_utf8(value).ok()
+    }
+
+    /// Read all remaining bytes.
+    pub fn read_remaining(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_reads_values_written_by_writer() {
+        let mut writer = WireWriter::default();
+        writer
+            .write_u8(1)
+            .write_u16(2)
+            .write_u64(3)
+            .write_str("four")
+            .write_bytes(&[5, 6]);
+
+        let bytes = writer.into_bytes();
+        let mut reader = WireReader::new(&bytes);
+
+        assert_eq!(reader.read_u8(), Some(1));
+        assert_eq!(reader.read_u16(), Some(2));
+        assert_eq!(reader.read_u64(), Some(3));
+        assert_eq!(reader.read_str(), Some("four"));
+        assert_eq!(reader.read_remaining(), &[5, 6]);
+        assert_eq!(reader.read_u8(), None);
+    }
+
+    #[test]
+    fn reader_returns_none_for_truncated_data() {
+        let mut writer = WireWriter::default();
+        writer.write_str("truncated");
+        let bytes = writer.into_bytes();
+
+        assert_eq!(WireReader::new(&bytes[..4]).read_str(), None);
+        assert_eq!(WireReader::new(&bytes[..1]).read_u16(), None);
+    }
+
+    #[test]
+    fn write_str_truncates_at_char_boundary() {
+        let value = "ä".repeat(usize::from(u16::MAX));
+        let mut writer = WireWriter::default();
+        writer.write_str(&value);
+
+        let bytes = writer.into_bytes();
+        let read = WireReader::new(&bytes).read_str().unwrap();
+
+        assert_eq!(read.len(), usize::from(u16::MAX) - 1);
+        assert!(value.starts_with(read));
+    }
+}
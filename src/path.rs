@@ -0,0 +1,245 @@
+//! # Path following
+//! A [`FollowPath`] moves an entity along a curve at a constant speed, e.g. a camera on a rail, a
+//! moving platform or a homing projectile. Each transform type is driven by its own
+//! [`FollowPathSystem`]:
+//!
+//! ```
+//! use game_engine::ecs::{System, World};
+//! use game_engine::math::{CubicCurve, Transform2D, Vec2};
+//! use game_engine::path::{FollowPath, FollowPathSystem};
+//! use game_engine::time::Time;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.storage.insert_resource(Time::new());
+//! world.add_system(FollowPathSystem::<Transform2D>::new());
+//!
+//! let points = [Vec2::ZERO, Vec2::new(4.0, 2.0), Vec2::new(8.0, 0.0), Vec2::ZERO];
+//! let path = CubicCurve::catmull_rom(&points).arc_length_parameterize(16);
+//!
+//! let _ = world
+//!     .build_entity()
+//!     .with_component(Transform2D::IDENTITY)
+//!     .with_component(FollowPath::<Transform2D>::new(path, 2.0).with_looping(true))
+//!     .build();
+//! ```
+use crate::ecs::{Query, Storage, System};
+use crate::math::{ArcLengthCurve, CurvePoint, Transform2D, Transform3D, Vec2, Vec3};
+use crate::time::Time;
+use std::marker::PhantomData;
+
+/// A transform component that can be moved along a path.
+pub trait PathTransform: 'static {
+    type Point: CurvePoint;
+
+    /// Move the transform to a position on the path. If `orient` is set, the transform is also
+    /// rotated to face the direction of movement.
+    fn follow(&mut self, position: Self::Point, direction: Self::Point, orient: bool);
+}
+
+impl PathTransform for Transform2D {
+    type Point = Vec2;
+
+    fn follow(&mut self, position: Vec2, direction: Vec2, orient: bool) {
+        self.translation = position;
+
+        if orient {
+            self.look_at(position + direction);
+        }
+    }
+}
+
+impl PathTransform for Transform3D {
+    type Point = Vec3;
+
+    fn follow(&mut self, position: Vec3, direction: Vec3, orient: bool) {
+        self.translation = position;
+
+        if orient {
+            self.look_at(position + direction, Vec3::Y);
+        }
+    }
+}
+
+/// Moves the transform of type `T` on the same entity along a path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowPath<T: PathTransform> {
+    path: ArcLengthCurve<T::Point>,
+    /// Distance travelled per second. Negative speeds move backwards along the path.
+    pub speed: f32,
+    distance: f32,
+    looping: bool,
+    orient: bool,
+}
+
+impl<T: PathTransform> FollowPath<T> {
+    #[must_use]
+    pub const fn new(path: ArcLengthCurve<T::Point>, speed: f32) -> Self {
+        Self {
+            path,
+            speed,
+            distance: 0.0,
+            looping: false,
+            orient: false,
+        }
+    }
+
+    /// If set, the entity starts over at the other end of the path after reaching its end.
+    /// Otherwise it stops at the end.
+    #[must_use]
+    pub const fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// If set, the entity is rotated to face the direction of movement, see
+    /// [`PathTransform::follow`].
+    #[must_use]
+    pub const fn with_orientation(mut self, orient: bool) -> Self {
+        self.orient = orient;
+        self
+    }
+
+    #[must_use]
+    pub const fn path(&self) -> &ArcLengthCurve<T::Point> {
+        &self.path
+    }
+
+    /// The distance travelled from the start of the path.
+    #[must_use]
+    pub const fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Jump to the given distance from the start of the path.
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = self.wrap(distance);
+    }
+
+    /// Returns true if a non-looping path was followed to its end in the direction of movement.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        !self.looping
+            && ((self.speed > 0.0 && self.distance >= self.path.length())
+                || (self.speed < 0.0 && self.distance <= 0.0))
+    }
+
+    /// Move along the path and update the transform.
+    pub fn advance(&mut self, seconds: f32, transform: &mut T) {
+        if self.path.curve().segment_count() == 0 {
+            return;
+        }
+
+        self.distance = self.wrap(self.speed.mul_add(seconds, self.distance));
+
+        let mut direction = self.path.velocity_at(self.distance);
+        if self.speed < 0.0 {
+            direction = direction * -1.0;
+        }
+
+        transform.follow(self.path.position_at(self.distance), direction, self.orient);
+    }
+
+    fn wrap(&self, distance: f32) -> f32 {
+        let length = self.path.length();
+
+        if self.looping && length > 0.0 {
+            distance.rem_euclid(length)
+        } else {
+            distance.clamp(0.0, length)
+        }
+    }
+}
+
+/// Advances all [`FollowPath<T>`](FollowPath) components by the frame time of the [`Time`]
+/// resource and moves the `T` transform of the same entity. Does nothing if there is no time
+/// resource.
+pub struct FollowPathSystem<T> {
+    marker: PhantomData<T>,
+}
+
+impl<T: PathTransform> System for FollowPathSystem<T> {
+    fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let Some(seconds) = storage.get_resource::<Time>().map(Time::delta_seconds) else {
+            return;
+        };
+
+        for (follow_path, transform) in storage.query_two_mut::<FollowPath<T>, T>() {
+            if !follow_path.is_finished() {
+                follow_path.advance(seconds, transform);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::World;
+    use crate::math::CubicCurve;
+    use std::time::Duration;
+
+    fn straight_path() -> ArcLengthCurve<Vec2> {
+        CubicCurve::catmull_rom(&[Vec2::ZERO, Vec2::new(10.0, 0.0)]).arc_length_parameterize(32)
+    }
+
+    #[test]
+    fn advance_moves_at_constant_speed_and_stops_at_end() {
+        let mut follow_path = FollowPath::<Transform2D>::new(straight_path(), 4.0);
+        let mut transform = Transform2D::IDENTITY;
+
+        follow_path.advance(1.0, &mut transform);
+        assert!(transform.translation.abs_diff_eq(Vec2::new(4.0, 0.0), 1e-2));
+        assert!(!follow_path.is_finished());
+
+        follow_path.advance(2.0, &mut transform);
+        assert!(transform
+            .translation
+            .abs_diff_eq(Vec2::new(10.0, 0.0), 1e-4));
+        assert!(follow_path.is_finished());
+    }
+
+    #[test]
+    fn looping_path_wraps_around() {
+        let mut follow_path = FollowPath::<Transform2D>::new(straight_path(), -4.0)
+            .with_looping(true)
+            .with_orientation(true);
+        let mut transform = Transform2D::IDENTITY;
+
+        follow_path.advance(1.0, &mut transform);
+
+        assert!((follow_path.distance() - 6.0).abs() < 1e-3);
+        assert!(transform.translation.abs_diff_eq(Vec2::new(6.0, 0.0), 1e-2));
+        assert!(transform.right().abs_diff_eq(Vec2::NEG_X, 1e-4));
+        assert!(!follow_path.is_finished());
+    }
+
+    #[test]
+    fn follow_path_system_moves_transforms() {
+        let mut world = World::init().unwrap();
+        let mut time = Time::new();
+        time.advance(Duration::from_millis(500));
+        world.storage.insert_resource(time);
+
+        let path = CubicCurve::catmull_rom(&[Vec3::ZERO, Vec3::new(0.0, 0.0, -10.0)])
+            .arc_length_parameterize(32);
+        let _ = world
+            .build_entity()
+            .with_component(Transform3D::IDENTITY)
+            .with_component(FollowPath::<Transform3D>::new(path, 2.0).with_orientation(true))
+            .build();
+
+        FollowPathSystem::<Transform3D>::new().update(&mut world.storage);
+
+        let transform = world.storage.query_one::<Transform3D>().next().unwrap();
+        assert!(transform
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, 0.0, -1.0), 1e-2));
+        assert!(transform.forward().abs_diff_eq(Vec3::NEG_Z, 1e-4));
+    }
+}
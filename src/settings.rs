@@ -0,0 +1,393 @@
+//! # Settings
+//! The [`Settings`] resource holds the user configuration of a game, split into video, audio,
+//! controls and gameplay sections. The [`SettingsPlugin`] loads it at startup from a TOML file in
+//! the platform's config directory. Changes are made with [`Storage::change_settings`], which
+//! saves the file and sends a [`SettingsChanged`] event for every changed section, so that
+//! systems can react immediately:
+//!
+//! ```no_run
+//! use game_engine::engine::Engine;
+//! use game_engine::settings::{SettingsChanged, SettingsPlugin, SettingsSection};
+//!
+//! let mut engine = Engine::new().add_plugin(SettingsPlugin::new("my-game"));
+//! let storage = &mut engine.world_mut().storage;
+//!
+//! storage
+//!     .change_settings(|settings| settings.audio.music_volume = 0.5)
+//!     .expect("Failed to save the settings");
+//!
+//! let changed: Vec<_> = storage.drain_events::<SettingsChanged>().collect();
+//! assert_eq!(changed, [SettingsChanged(SettingsSection::Audio)]);
+//! ```
+use crate::ecs::{Storage, World};
+use crate::engine::Plugin;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    /// The settings file is not valid TOML or does not match the settings.
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "settings io error: {error}"),
+            Self::Parse(error) => write!(f, "invalid settings file: {error}"),
+            Self::Serialize(error) => write!(f, "settings could not be serialized: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse(error) => Some(error),
+            Self::Serialize(error) => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for SettingsError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            fullscreen: false,
+            vsync: true,
+        }
+    }
+}
+
+/// Volumes between 0.0 and 1.0.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            music_volume: 1.0,
+            effects_volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    /// The key or button bound to each action, e.g. `"jump" = "Space"`.
+    pub bindings: BTreeMap<String, String>,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: 1.0,
+            invert_y: false,
+            bindings: BTreeMap::new(),
+        }
+    }
+}
+
+/// Game specific settings, stored as named values of any serializable type.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GameplaySettings {
+    values: BTreeMap<String, toml::Value>,
+}
+
+impl GameplaySettings {
+    /// The value with the given name. Returns None if there is no such value or it has a
+    /// different type.
+    #[must_use]
+    pub fn get<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.values
+            .get(name)
+            .and_then(|value| value.clone().try_into().ok())
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the value can not be represented in TOML.
+    pub fn set<T: Serialize>(&mut self, name: &str, value: T) -> Result<(), SettingsError> {
+        let value = toml::Value::try_from(value).map_err(SettingsError::Serialize)?;
+        self.values.insert(name.to_owned(), value);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.values.remove(name);
+    }
+}
+
+/// The user configuration of a game, see the [module documentation](self). Values that are
+/// missing in a settings file keep their defaults, so files of older versions still load.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub video: VideoSettings,
+    pub audio: AudioSettings,
+    pub controls: ControlSettings,
+    pub gameplay: GameplaySettings,
+}
+
+impl Settings {
+    /// The default location of the settings file of an application, e.g.
+    /// `~/.config/<app_name>/settings.toml` on Linux. Returns None if the config directory of the
+    /// platform is unknown.
+    #[must_use]
+    pub fn default_path(app_name: &str) -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(app_name).join("settings.toml"))
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or is not a valid settings file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SettingsError> {
+        let contents = fs::read_to_string(path)?;
+
+        toml::from_str(&contents).map_err(SettingsError::Parse)
+    }
+
+    /// Write the settings to a file. Missing parent directories are created.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SettingsError> {
+        let path = path.as_ref();
+        let contents = toml::to_string_pretty(self).map_err(SettingsError::Serialize)?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// The per-user config directory of the platform.
+fn config_dir() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))
+    }
+}
+
+/// A section of the [`Settings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsSection {
+    Video,
+    Audio,
+    Controls,
+    Gameplay,
+}
+
+/// Sent as ECS event by [`Storage::change_settings`] for every section that changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SettingsChanged(pub SettingsSection);
+
+/// The file the [`Settings`] resource is saved to, inserted by the [`SettingsPlugin`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsFile(pub PathBuf);
+
+impl Storage {
+    /// Change the [`Settings`] resource, which is created with the defaults if it does not exist.
+    /// A [`SettingsChanged`] event is sent for every changed section, and the settings are saved
+    /// if there is a [`SettingsFile`] resource.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings could not be saved. The change is applied anyway.
+    pub fn change_settings(
+        &mut self,
+        change: impl FnOnce(&mut Settings),
+    ) -> Result<(), SettingsError> {
+        if !self.has_resource::<Settings>() {
+            self.insert_resource(Settings::default());
+        }
+        let settings = self
+            .get_resource_mut::<Settings>()
+            .expect("Settings resource was just inserted");
+
+        let previous = settings.clone();
+        change(settings);
+        let settings = settings.clone();
+
+        let changed = [
+            (SettingsSection::Video, previous.video != settings.video),
+            (SettingsSection::Audio, previous.audio != settings.audio),
+            (
+                SettingsSection::Controls,
+                previous.controls != settings.controls,
+            ),
+            (
+                SettingsSection::Gameplay,
+                previous.gameplay != settings.gameplay,
+            ),
+        ];
+        let mut any_changed = false;
+        for (section, changed) in changed {
+            if changed {
+                self.send_event(SettingsChanged(section));
+                any_changed = true;
+            }
+        }
+
+        match self.get_resource::<SettingsFile>() {
+            Some(SettingsFile(path)) if any_changed => settings.save(path),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Loads the [`Settings`] at startup and inserts them together with their [`SettingsFile`]. A
+/// missing or invalid settings file falls back to the defaults.
+pub struct SettingsPlugin {
+    path: PathBuf,
+}
+
+impl SettingsPlugin {
+    /// Use the [default path](Settings::default_path) of the application. If the config directory
+    /// of the platform is unknown, `settings.toml` in the working directory is used.
+    #[must_use]
+    pub fn new(app_name: &str) -> Self {
+        Self {
+            path: Settings::default_path(app_name)
+                .unwrap_or_else(|| PathBuf::from("settings.toml")),
+        }
+    }
+
+    #[must_use]
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, world: &mut World) {
+        let settings = Settings::load(&self.path).unwrap_or_default();
+
+        world.storage.insert_resource(settings);
+        world
+            .storage
+            .insert_resource(SettingsFile(self.path.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("game-engine-settings-{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn settings_are_saved_and_loaded() {
+        let path = temp_path("roundtrip.toml");
+        let mut settings = Settings::default();
+        settings.video.vsync = false;
+        settings
+            .controls
+            .bindings
+            .insert("jump".to_owned(), "Space".to_owned());
+        settings.gameplay.set("difficulty", "hard").unwrap();
+        settings.gameplay.set("hints", false).unwrap();
+
+        settings.save(&path).unwrap();
+        let loaded = Settings::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, settings);
+        assert_eq!(
+            loaded.gameplay.get::<String>("difficulty").as_deref(),
+            Some("hard")
+        );
+        assert_eq!(loaded.gameplay.get::<u32>("hints"), None);
+    }
+
+    #[test]
+    fn missing_values_keep_their_defaults() {
+        let settings: Settings = toml::from_str("[audio]\nmuted = true\n").unwrap();
+
+        assert!(settings.audio.muted);
+        assert_eq!(settings.audio.master_volume, 1.0);
+        assert_eq!(settings.video, VideoSettings::default());
+    }
+
+    #[test]
+    fn change_settings_sends_events_and_saves() {
+        let path = temp_path("plugin.toml");
+        let mut world = World::init().unwrap();
+        SettingsPlugin::from_path(&path).build(&mut world);
+
+        world
+            .storage
+            .change_settings(|settings| {
+                settings.video.fullscreen = true;
+                settings.audio.muted = true;
+            })
+            .unwrap();
+        world.storage.change_settings(|_| {}).unwrap();
+
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<SettingsChanged>()
+                .collect::<Vec<_>>(),
+            [
+                SettingsChanged(SettingsSection::Video),
+                SettingsChanged(SettingsSection::Audio)
+            ]
+        );
+
+        let loaded = Settings::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(loaded.video.fullscreen);
+        assert!(loaded.audio.muted);
+    }
+}
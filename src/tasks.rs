@@ -0,0 +1,472 @@
+//! # Async tasks
+//! Long-running work like procedural chunk generation or pathfinding is spawned on the
+//! [`ComputePool`] and attached to an entity as an [`AsyncTask<T>`](AsyncTask). Once the task has
+//! finished, the [`AsyncTaskSystem<T>`](AsyncTaskSystem) replaces it with the resulting `T`
+//! component:
+//!
+//! ```
+//! use game_engine::ecs::{System, World};
+//! use game_engine::tasks::{AsyncTaskSystem, ComputePool};
+//!
+//! struct Chunk(Vec<u8>);
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.add_system(AsyncTaskSystem::<Chunk>::new());
+//!
+//! let pool = ComputePool::default();
+//! let task = pool.spawn(async { Chunk(vec![0; 16 * 16]) });
+//! let _ = world.build_entity().with_component(task).build();
+//! world.storage.insert_resource(pool);
+//! ```
+use crate::ecs::{Storage, System};
+use std::any::Any;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::mem;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Wake, Waker};
+use std::thread;
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// A spawned future. Waking it puts it back into the queue of the pool.
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    queue: Sender<Option<Arc<Task>>>,
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        let queue = self.queue.clone();
+
+        // the pool was dropped, nobody will run the task anymore
+        let _ = queue.send(Some(self));
+    }
+}
+
+/// A pool of worker threads that run spawned futures. Usually inserted as a resource, so that
+/// systems can spawn tasks.
+pub struct ComputePool {
+    queue: Sender<Option<Arc<Task>>>,
+    workers: usize,
+}
+
+impl ComputePool {
+    #[must_use]
+    pub fn new(workers: NonZeroUsize) -> Self {
+        let (queue, receiver) = mpsc::channel::<Option<Arc<Task>>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for _ in 0..workers.get() {
+            let receiver = Arc::clone(&receiver);
+            thread::spawn(move || run_worker(&receiver));
+        }
+
+        Self {
+            queue,
+            workers: workers.get(),
+        }
+    }
+
+    /// Run the future on the pool. The returned task receives its output.
+    pub fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> AsyncTask<T> {
+        let (sender, receiver) = mpsc::channel();
+        self.run(async move {
+            // the task may have been dropped in the meantime
+            let _ = sender.send(future.await);
+        });
+
+        AsyncTask {
+            receiver,
+            finished: false,
+        }
+    }
+
+    /// Call `work` on the calling thread and on up to `helpers` workers of the pool, with the
+    /// index of the thread (0 for the calling thread), and wait until all calls have returned.
+    /// Unlike spawned futures, the work may borrow from the caller, e.g. to run a
+    /// [`JobGraph`](crate::jobs::JobGraph) without starting new threads every frame.
+    ///
+    /// Workers that are busy with other tasks until the call on the calling thread has returned
+    /// don't call `work` anymore, so the work has to be done once that call returns.
+    ///
+    /// # Panics
+    ///
+    /// A panic of the work on a worker is resumed on the calling thread.
+    pub fn scope(&self, helpers: usize, work: &(dyn Fn(usize) + Sync)) {
+        let scope = Arc::new(Scope {
+            state: Mutex::new(ScopeState {
+                open: true,
+                active: 0,
+                panic: None,
+            }),
+            finished: Condvar::new(),
+        });
+
+        let work: *const (dyn Fn(usize) + Sync + '_) = work;
+        // SAFETY: only the lifetime is erased. The guard below keeps this function from returning
+        // or unwinding while a worker calls the work, and workers only call it while the scope is
+        // open.
+        let work: *const (dyn Fn(usize) + Sync + 'static) = unsafe { mem::transmute(work) };
+
+        for index in 1..=helpers {
+            let scope = Arc::clone(&scope);
+            let work = ScopedWork(work);
+            self.run(async move { run_scoped_work(&scope, &work, index) });
+        }
+
+        let guard = ScopeGuard(&scope);
+        // SAFETY: the work is borrowed by this function
+        unsafe { (*work)(0) };
+        drop(guard);
+
+        let panic = scope.lock().panic.take();
+        if let Some(payload) = panic {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    /// The number of worker threads.
+    #[must_use]
+    pub const fn workers(&self) -> usize {
+        self.workers
+    }
+
+    fn run(&self, future: impl Future<Output = ()> + Send + 'static) {
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(future))),
+            queue: self.queue.clone(),
+        });
+
+        task.wake();
+    }
+}
+
+impl Default for ComputePool {
+    /// A pool with one worker per available core.
+    fn default() -> Self {
+        Self::new(thread::available_parallelism().unwrap_or(NonZeroUsize::MIN))
+    }
+}
+
+impl Drop for ComputePool {
+    fn drop(&mut self) {
+        // stop the workers once they are done with the futures they are currently polling
+        for _ in 0..self.workers {
+            let _ = self.queue.send(None);
+        }
+    }
+}
+
+/// Work passed to [`ComputePool::scope`], with the lifetime of the borrow erased.
+struct ScopedWork(*const (dyn Fn(usize) + Sync));
+
+// SAFETY: the work is Sync and only called while the scope that borrows it is open
+unsafe impl Send for ScopedWork {}
+
+struct ScopeState {
+    open: bool,
+    /// The number of workers that are calling the work.
+    active: usize,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+struct Scope {
+    state: Mutex<ScopeState>,
+    finished: Condvar,
+}
+
+impl Scope {
+    fn lock(&self) -> MutexGuard<'_, ScopeState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Closes the scope and waits for the workers that are still calling the work, also if the calling
+/// thread unwinds.
+struct ScopeGuard<'a>(&'a Scope);
+
+impl Drop for ScopeGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.lock();
+        state.open = false;
+
+        while state.active > 0 {
+            state = self
+                .0
+                .finished
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+fn run_scoped_work(scope: &Scope, work: &ScopedWork, index: usize) {
+    {
+        let mut state = scope.lock();
+        if !state.open {
+            return;
+        }
+        state.active += 1;
+    }
+
+    // SAFETY: the scope was open, so the caller waits for this call before the work is dropped
+    let result = panic::catch_unwind(AssertUnwindSafe(|| unsafe { (*work.0)(index) }));
+
+    let mut state = scope.lock();
+    if let Err(payload) = result {
+        state.panic.get_or_insert(payload);
+    }
+    state.active -= 1;
+    drop(state);
+    scope.finished.notify_all();
+}
+
+fn run_worker(receiver: &Mutex<Receiver<Option<Arc<Task>>>>) {
+    loop {
+        let task = receiver
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .recv();
+        let Ok(Some(task)) = task else {
+            return;
+        };
+
+        // keep the future locked while polling, so that a wake-up during the poll waits for it
+        let mut future = task.future.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(pending) = future.as_mut() {
+            let waker = Waker::from(Arc::clone(&task));
+            let poll = panic::catch_unwind(AssertUnwindSafe(|| {
+                pending.as_mut().poll(&mut Context::from_waker(&waker))
+            }));
+
+            // a panicking future is dropped, which disconnects its task
+            if poll.map_or(true, |poll| poll.is_ready()) {
+                *future = None;
+            }
+        }
+    }
+}
+
+/// The pending result of a future that was [spawned](ComputePool::spawn) on the compute pool.
+pub struct AsyncTask<T> {
+    receiver: Receiver<T>,
+    finished: bool,
+}
+
+impl<T> AsyncTask<T> {
+    /// Take the output of the task if it is finished. Returns None while the task is running and
+    /// after the output was taken.
+    pub fn try_take(&mut self) -> Option<T> {
+        match self.receiver.try_recv() {
+            Ok(output) => {
+                self.finished = true;
+                Some(output)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.finished = true;
+                None
+            }
+        }
+    }
+
+    /// Returns true if the output was taken or the task can not finish anymore, e.g. because its
+    /// future panicked.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+/// Replaces every finished [`AsyncTask<T>`](AsyncTask) component with the `T` it resolved to. A
+/// `T` the entity already has is replaced. Tasks that can not finish anymore are removed without a
+/// replacement.
+pub struct AsyncTaskSystem<T> {
+    marker: PhantomData<T>,
+}
+
+impl<T: 'static> System for AsyncTaskSystem<T> {
+    fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        for entity in storage.entities_with_component::<AsyncTask<T>>() {
+            let Some(task) = storage.get_entity_component_mut::<AsyncTask<T>>(entity) else {
+                continue;
+            };
+            let output = task.try_take();
+            let finished = task.is_finished();
+
+            // the output is added first, so that an entity with only the task is not removed
+            if let Some(output) = output {
+                storage.insert_component(entity, output);
+            }
+            if finished {
+                storage.remove_entity_component::<AsyncTask<T>>(entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Query, World};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::task::Poll;
+    use std::time::{Duration, Instant};
+
+    /// Returns pending once before it completes, so that the task has to be woken.
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                return Poll::Ready(());
+            }
+
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    fn wait_for<T>(task: &mut AsyncTask<T>) -> Option<T> {
+        let start = Instant::now();
+
+        while !task.is_finished() && start.elapsed() < Duration::from_secs(5) {
+            if let Some(output) = task.try_take() {
+                return Some(output);
+            }
+            thread::yield_now();
+        }
+        None
+    }
+
+    #[test]
+    fn pool_runs_futures_that_yield() {
+        let pool = ComputePool::new(NonZeroUsize::new(2).unwrap());
+        let mut task = pool.spawn(async {
+            YieldOnce(false).await;
+            YieldOnce(false).await;
+            42
+        });
+
+        assert_eq!(wait_for(&mut task), Some(42));
+        assert!(task.is_finished());
+        assert_eq!(task.try_take(), None);
+    }
+
+    #[test]
+    fn system_replaces_finished_tasks_with_their_output() {
+        #[derive(Debug, PartialEq)]
+        struct Path(Vec<u32>);
+        struct Marker;
+
+        let mut world = World::init().unwrap();
+        let pool = ComputePool::new(NonZeroUsize::MIN);
+        let task = pool.spawn(async { Path(vec![1, 2, 3]) });
+        let _ = world
+            .build_entity()
+            .with_component(Marker)
+            .with_component(task)
+            .build();
+
+        let mut system = AsyncTaskSystem::<Path>::new();
+        let start = Instant::now();
+        while world.storage.query_one::<Path>().next().is_none() {
+            assert!(start.elapsed() < Duration::from_secs(5), "task timed out");
+            system.update(&mut world.storage);
+            thread::yield_now();
+        }
+
+        assert_eq!(world.storage.query_one::<AsyncTask<Path>>().count(), 0);
+        assert_eq!(
+            world.storage.query_two::<Marker, Path>().next().unwrap().1,
+            &Path(vec![1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn system_replaces_existing_components() {
+        let mut world = World::init().unwrap();
+        let pool = ComputePool::new(NonZeroUsize::MIN);
+        let entity = world
+            .build_entity()
+            .with_component(1u32)
+            .with_component(pool.spawn(async { 3u32 }))
+            .build();
+
+        let mut system = AsyncTaskSystem::<u32>::new();
+        let start = Instant::now();
+        while world.storage.has::<AsyncTask<u32>>(entity) {
+            assert!(start.elapsed() < Duration::from_secs(5), "task timed out");
+            system.update(&mut world.storage);
+            thread::yield_now();
+        }
+
+        assert_eq!(world.storage.get_entity_component::<u32>(entity), Some(&3));
+    }
+
+    #[test]
+    fn scope_does_not_wait_for_busy_workers() {
+        let pool = ComputePool::new(NonZeroUsize::new(2).unwrap());
+        let (unblock, blocked) = mpsc::channel::<()>();
+        let mut busy = pool.spawn(async move {
+            let _ = blocked.recv();
+        });
+        let calls = Mutex::new(Vec::new());
+
+        pool.scope(2, &|index| calls.lock().unwrap().push(index));
+
+        let calls = calls.into_inner().unwrap();
+        assert!(calls.contains(&0));
+        assert!(calls.len() <= 2);
+        unblock.send(()).unwrap();
+        assert_eq!(wait_for(&mut busy), Some(()));
+    }
+
+    #[test]
+    #[should_panic(expected = "work failed")]
+    fn scope_resumes_panics_of_workers() {
+        let pool = ComputePool::new(NonZeroUsize::MIN);
+        let started = AtomicBool::new(false);
+
+        pool.scope(1, &|index| {
+            if index == 0 {
+                // keep the scope open until the worker has started
+                let start = Instant::now();
+                while !started.load(Ordering::Acquire) && start.elapsed() < Duration::from_secs(5) {
+                    thread::yield_now();
+                }
+            } else {
+                started.store(true, Ordering::Release);
+                panic!("work failed");
+            }
+        });
+    }
+
+    #[test]
+    fn panicking_futures_finish_their_task() {
+        let pool = ComputePool::new(NonZeroUsize::MIN);
+        let mut failed = pool.spawn(async { panic!("task failed") });
+        let mut task = pool.spawn(async { 1 });
+
+        assert_eq!(wait_for(&mut task), Some(1));
+        assert_eq!(wait_for(&mut failed), None);
+        assert!(failed.is_finished());
+    }
+}
@@ -0,0 +1,218 @@
+//! # Time
+//! The [`Time`] resource tells systems how much time passed since the last frame, so that movement,
+//! animations and timers are independent of the frame rate:
+//!
+//! ```
+//! use game_engine::ecs::World;
+//! use game_engine::time::Time;
+//! use std::time::Duration;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.storage.insert_resource(Time::new());
+//!
+//! // done by the game loop once per frame
+//! if let Some(time) = world.storage.get_resource_mut::<Time>() {
+//!     time.advance(Duration::from_millis(16));
+//! }
+//!
+//! assert_eq!(world.storage.get_resource::<Time>().unwrap().frame_count(), 1);
+//! ```
+//!
+//! The game time can be [slowed down or sped up](Time::set_scale) and [paused](Time::pause), which
+//! affects every system that uses [`Time::delta`]. Systems that have to keep running in real time,
+//! like UI animations in a pause menu, use [`Time::unscaled_delta`] instead.
+use std::time::Duration;
+
+/// Frame timing information. The time is advanced once per frame by the game loop; systems only
+/// read it.
+///
+/// There are two clocks: the game clock, which is affected by the [scale](Time::set_scale) and
+/// [pausing](Time::pause), and the unscaled clock, which always follows the real time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    unscaled_delta: Duration,
+    unscaled_elapsed: Duration,
+    scale: f32,
+    paused: bool,
+    frame_count: u64,
+}
+
+impl Time {
+    /// The largest [scale](Time::set_scale). Larger scales would let a single frame overflow the
+    /// game clock.
+    pub const MAX_SCALE: f32 = 1000.0;
+
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            unscaled_delta: Duration::ZERO,
+            unscaled_elapsed: Duration::ZERO,
+            scale: 1.0,
+            paused: false,
+            frame_count: 0,
+        }
+    }
+
+    /// Start a new frame that took `delta` of real time since the previous one.
+    pub fn advance(&mut self, delta: Duration) {
+        self.unscaled_delta = delta;
+        self.unscaled_elapsed += delta;
+        self.delta = if self.paused {
+            Duration::ZERO
+        } else {
+            delta.mul_f64(f64::from(self.scale))
+        };
+        self.elapsed += self.delta;
+        self.frame_count += 1;
+    }
+
+    /// Set how fast the game time runs compared to real time, e.g. 0.5 for slow motion. Negative
+    /// scales and NaN are treated as 0.0, scales above [`Time::MAX_SCALE`] as the maximum. The new
+    /// scale applies from the next frame on.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = if scale.is_nan() {
+            0.0
+        } else {
+            scale.clamp(0.0, Self::MAX_SCALE)
+        };
+    }
+
+    #[must_use]
+    pub const fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Stop the game time, starting with the next frame. The unscaled time keeps running.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Continue the game time after it was [paused](Time::pause).
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    #[must_use]
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The game time between the previous and the current frame. This is zero while the time is
+    /// paused.
+    #[must_use]
+    pub const fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    #[must_use]
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// The total game time that passed in all frames so far.
+    #[must_use]
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    #[must_use]
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    /// The real time between the previous and the current frame, ignoring scale and pausing.
+    #[must_use]
+    pub const fn unscaled_delta(&self) -> Duration {
+        self.unscaled_delta
+    }
+
+    #[must_use]
+    pub fn unscaled_delta_seconds(&self) -> f32 {
+        self.unscaled_delta.as_secs_f32()
+    }
+
+    /// The total real time that passed in all frames so far.
+    #[must_use]
+    pub const fn unscaled_elapsed(&self) -> Duration {
+        self.unscaled_elapsed
+    }
+
+    /// The number of frames so far.
+    #[must_use]
+    pub const fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_accumulates_time_and_frames() {
+        let mut time = Time::new();
+
+        time.advance(Duration::from_millis(10));
+        time.advance(Duration::from_millis(20));
+
+        assert_eq!(time.delta(), Duration::from_millis(20));
+        assert_eq!(time.elapsed(), Duration::from_millis(30));
+        assert_eq!(time.frame_count(), 2);
+        assert!((time.delta_seconds() - 0.02).abs() < 1e-6);
+    }
+
+    #[test]
+    fn scale_and_pause_only_affect_game_time() {
+        let mut time = Time::new();
+
+        time.set_scale(0.5);
+        time.advance(Duration::from_millis(100));
+        assert_eq!(time.delta(), Duration::from_millis(50));
+        assert_eq!(time.unscaled_delta(), Duration::from_millis(100));
+
+        time.pause();
+        time.advance(Duration::from_millis(100));
+        assert!(time.is_paused());
+        assert_eq!(time.delta(), Duration::ZERO);
+        assert_eq!(time.unscaled_delta(), Duration::from_millis(100));
+
+        time.resume();
+        time.set_scale(-1.0);
+        time.advance(Duration::from_millis(100));
+        assert_eq!(time.scale(), 0.0);
+        assert_eq!(time.delta(), Duration::ZERO);
+
+        assert_eq!(time.elapsed(), Duration::from_millis(50));
+        assert_eq!(time.unscaled_elapsed(), Duration::from_millis(300));
+        assert_eq!(time.frame_count(), 3);
+    }
+
+    #[test]
+    fn scale_is_kept_finite() {
+        let mut time = Time::new();
+
+        time.set_scale(f32::INFINITY);
+        assert_eq!(time.scale(), Time::MAX_SCALE);
+        time.advance(Duration::ZERO);
+        time.advance(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::from_secs(10));
+
+        time.set_scale(f32::MAX);
+        assert_eq!(time.scale(), Time::MAX_SCALE);
+
+        time.set_scale(f32::NAN);
+        assert_eq!(time.scale(), 0.0);
+        time.advance(Duration::from_millis(10));
+        assert_eq!(time.delta(), Duration::ZERO);
+    }
+}
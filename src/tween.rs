@@ -0,0 +1,585 @@
+//! # Tweening
+//! A [`Tween`] smoothly changes a component of an entity from a start to an end value over time,
+//! following an [`Easing`] curve. By default the whole component is animated, a [`Lens`] selects
+//! a single field instead, e.g. the translation of a transform, so that other systems can still
+//! rotate or scale it. Each tweened component type or lens is driven by its own [`TweenSystem`]:
+//!
+//! ```
+//! use game_engine::ecs::{System, World};
+//! use game_engine::math::{Transform2D, Vec2};
+//! use game_engine::time::Time;
+//! use game_engine::tween::{Easing, Repeat, Translation2D, Tween, TweenSystem};
+//! use std::time::Duration;
+//!
+//! let mut world = World::init().expect("Failed to initialize world");
+//! world.storage.insert_resource(Time::new());
+//! world.add_system(TweenSystem::<Vec2, Translation2D>::new());
+//!
+//! // let a pickup bob up and down forever
+//! let tween = Tween::new(Vec2::ZERO, Vec2::Y, Duration::from_millis(500))
+//!     .with_easing(Easing::SineInOut)
+//!     .with_repeat(Repeat::Forever)
+//!     .with_yoyo(true)
+//!     .with_lens::<Translation2D>();
+//!
+//! let _ = world
+//!     .build_entity()
+//!     .with_component(Transform2D::from_xy(0.0, 0.0))
+//!     .with_component(tween)
+//!     .build();
+//! ```
+use crate::ecs::{EntityId, Storage, System};
+use crate::math::{Quat, Transform2D, Transform3D, Vec2, Vec3, Vec4};
+use crate::time::Time;
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// A value that can be interpolated between a start and an end value.
+pub trait Tweenable: Clone + 'static {
+    /// The value at `t` between `self` (at 0.0) and `end` (at 1.0). Easing curves may overshoot,
+    /// so `t` can be slightly outside of that range.
+    #[must_use]
+    fn interpolate(&self, end: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        (end - self).mul_add(t, *self)
+    }
+}
+
+impl Tweenable for Vec2 {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        self.lerp(*end, t)
+    }
+}
+
+impl Tweenable for Vec3 {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        self.lerp(*end, t)
+    }
+}
+
+impl Tweenable for Vec4 {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        self.lerp(*end, t)
+    }
+}
+
+impl Tweenable for Quat {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        self.slerp(*end, t)
+    }
+}
+
+impl Tweenable for Transform2D {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.interpolate(&end.translation, t),
+            rotation: self.rotation.interpolate(&end.rotation, t),
+            scale: self.scale.interpolate(&end.scale, t),
+        }
+    }
+}
+
+impl Tweenable for Transform3D {
+    fn interpolate(&self, end: &Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.interpolate(&end.translation, t),
+            rotation: self.rotation.interpolate(&end.rotation, t),
+            scale: self.scale.interpolate(&end.scale, t),
+        }
+    }
+}
+
+/// Selects the value of a component that a [`Tween`] animates, so that the rest of the component
+/// is left alone.
+pub trait Lens: 'static {
+    /// The component that contains the value.
+    type Component: 'static;
+    /// The animated value.
+    type Value: Tweenable;
+
+    fn set(component: &mut Self::Component, value: Self::Value);
+}
+
+/// The default [`Lens`], which animates the whole component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Whole<T>(PhantomData<T>);
+
+impl<T: Tweenable> Lens for Whole<T> {
+    type Component = T;
+    type Value = T;
+
+    fn set(component: &mut T, value: T) {
+        *component = value;
+    }
+}
+
+macro_rules! impl_field_lens {
+    ($(#[$meta:meta])* $lens:ident, $component:ty, $field:ident: $value:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $lens;
+
+        impl Lens for $lens {
+            type Component = $component;
+            type Value = $value;
+
+            fn set(component: &mut $component, value: $value) {
+                component.$field = value;
+            }
+        }
+    };
+}
+
+impl_field_lens!(
+    /// Animates the translation of a [`Transform2D`].
+    Translation2D, Transform2D, translation: Vec2
+);
+impl_field_lens!(
+    /// Animates the rotation of a [`Transform2D`].
+    Rotation2D, Transform2D, rotation: f32
+);
+impl_field_lens!(
+    /// Animates the scale of a [`Transform2D`].
+    Scale2D, Transform2D, scale: Vec2
+);
+impl_field_lens!(
+    /// Animates the translation of a [`Transform3D`].
+    Translation3D, Transform3D, translation: Vec3
+);
+impl_field_lens!(
+    /// Animates the rotation of a [`Transform3D`].
+    Rotation3D, Transform3D, rotation: Quat
+);
+impl_field_lens!(
+    /// Animates the scale of a [`Transform3D`].
+    Scale3D, Transform3D, scale: Vec3
+);
+
+/// Easing curves that map the linear progress of a tween to the progress of its value. `In`
+/// curves start slow, `Out` curves end slow and `InOut` curves do both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Overshoots the end value slightly before settling on it.
+    BackOut,
+    /// Oscillates around the end value before settling on it.
+    ElasticOut,
+    /// Bounces off the end value like a dropped ball.
+    BounceOut,
+}
+
+impl Easing {
+    /// Apply the curve to a progress between 0.0 and 1.0.
+    #[must_use]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Self::QuadInOut if t < 0.5 => 2.0 * t * t,
+            Self::QuadInOut => 1.0 - (-2.0f32).mul_add(t, 2.0).powi(2) / 2.0,
+            Self::CubicIn => t.powi(3),
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut if t < 0.5 => 4.0 * t.powi(3),
+            Self::CubicInOut => 1.0 - (-2.0f32).mul_add(t, 2.0).powi(3) / 2.0,
+            Self::SineIn => 1.0 - (t * FRAC_PI_2).cos(),
+            Self::SineOut => (t * FRAC_PI_2).sin(),
+            Self::SineInOut => (1.0 - (t * PI).cos()) / 2.0,
+            Self::BackOut => {
+                const OVERSHOOT: f32 = 1.701_58;
+                let t = t - 1.0;
+                (OVERSHOOT + 1.0).mul_add(t.powi(3), OVERSHOOT * t.powi(2)) + 1.0
+            }
+            Self::ElasticOut if t == 0.0 || t == 1.0 => t,
+            Self::ElasticOut => {
+                let period = 2.0 * PI / 3.0;
+                2.0f32.powf(-10.0 * t) * (t.mul_add(10.0, -0.75) * period).sin() + 1.0
+            }
+            Self::BounceOut => bounce_out(t),
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const SCALE: f32 = 7.5625;
+    const WIDTH: f32 = 2.75;
+
+    if t < 1.0 / WIDTH {
+        SCALE * t * t
+    } else if t < 2.0 / WIDTH {
+        let t = t - 1.5 / WIDTH;
+        SCALE.mul_add(t * t, 0.75)
+    } else if t < 2.5 / WIDTH {
+        let t = t - 2.25 / WIDTH;
+        SCALE.mul_add(t * t, 0.9375)
+    } else {
+        let t = t - 2.625 / WIDTH;
+        SCALE.mul_add(t * t, 0.984_375)
+    }
+}
+
+/// How often a tween is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Repeat {
+    #[default]
+    Once,
+    Times(u32),
+    Forever,
+}
+
+/// Sent as ECS event when a tween that was created with [`Tween::with_completion_event`]
+/// finishes. Tweens that repeat forever never finish.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TweenCompleted {
+    /// The id passed to [`Tween::with_completion_event`].
+    pub id: u64,
+    /// The entity of the tween.
+    pub entity: EntityId,
+}
+
+/// Animates the value selected by the [lens](Lens) `L` on the same entity from `start` to `end`,
+/// by default the whole component of type `T`. Finished tweens stay on the entity and leave the
+/// value at its end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T, L = Whole<T>> {
+    start: T,
+    end: T,
+    duration: Duration,
+    easing: Easing,
+    repeat: Repeat,
+    yoyo: bool,
+    completion_event: Option<u64>,
+    elapsed: Duration,
+    cycle: u32,
+    finished: bool,
+    lens: PhantomData<L>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    #[must_use]
+    pub const fn new(start: T, end: T, duration: Duration) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+            easing: Easing::Linear,
+            repeat: Repeat::Once,
+            yoyo: false,
+            completion_event: None,
+            elapsed: Duration::ZERO,
+            cycle: 0,
+            finished: false,
+            lens: PhantomData,
+        }
+    }
+}
+
+impl<T: Tweenable, L: Lens<Value = T>> Tween<T, L> {
+    /// Animate the value selected by another [lens](Lens), e.g. [`Translation2D`].
+    #[must_use]
+    pub fn with_lens<Other: Lens<Value = T>>(self) -> Tween<T, Other> {
+        Tween {
+            start: self.start,
+            end: self.end,
+            duration: self.duration,
+            easing: self.easing,
+            repeat: self.repeat,
+            yoyo: self.yoyo,
+            completion_event: self.completion_event,
+            elapsed: self.elapsed,
+            cycle: self.cycle,
+            finished: self.finished,
+            lens: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// If set, every other cycle of a repeating tween is played backwards, from `end` to `start`.
+    #[must_use]
+    pub const fn with_yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    /// Send a [`TweenCompleted`] event with the given id when the tween finishes.
+    #[must_use]
+    pub const fn with_completion_event(mut self, id: u64) -> Self {
+        self.completion_event = Some(id);
+        self
+    }
+
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The linear progress of the current cycle between 0.0 and 1.0.
+    #[must_use]
+    pub fn progress(&self) -> f32 {
+        if self.finished || self.duration.is_zero() {
+            return 1.0;
+        }
+
+        (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    /// Restart the tween from the beginning of its first cycle.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.cycle = 0;
+        self.finished = false;
+    }
+
+    /// Advance the tween and return the current value. Returns true as second value if the tween
+    /// finished during this step.
+    pub fn tick(&mut self, delta: Duration) -> (T, bool) {
+        let was_finished = self.finished;
+
+        if !self.finished {
+            self.elapsed += delta;
+        }
+
+        while !self.finished && self.elapsed >= self.duration {
+            let cycles = match self.repeat {
+                Repeat::Once => 1,
+                Repeat::Times(times) => times.max(1),
+                Repeat::Forever => u32::MAX,
+            };
+
+            if self.cycle + 1 >= cycles && self.repeat != Repeat::Forever {
+                self.finished = true;
+            } else if self.duration.is_zero() {
+                // a zero duration tween repeating forever would never leave this loop
+                break;
+            } else {
+                self.elapsed -= self.duration;
+                self.cycle = self.cycle.wrapping_add(1);
+            }
+        }
+
+        (self.value(), self.finished && !was_finished)
+    }
+
+    /// The value at the current position of the tween.
+    #[must_use]
+    pub fn value(&self) -> T {
+        let t = self.easing.apply(self.progress());
+        let backwards = self.yoyo && self.cycle % 2 == 1;
+
+        if backwards {
+            self.end.interpolate(&self.start, t)
+        } else {
+            self.start.interpolate(&self.end, t)
+        }
+    }
+}
+
+/// Advances all [`Tween<T, L>`](Tween) components by the frame time of the [`Time`] resource and
+/// writes the values into the component of the same entity with the [lens](Lens) `L`. Does
+/// nothing if there is no time resource.
+pub struct TweenSystem<T, L = Whole<T>> {
+    marker: PhantomData<(T, L)>,
+}
+
+impl<T: Tweenable, L: Lens<Value = T>> System for TweenSystem<T, L> {
+    fn new() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+
+    fn update(&mut self, storage: &mut Storage) {
+        let Some(delta) = storage.get_resource::<Time>().map(Time::delta) else {
+            return;
+        };
+
+        for entity in storage.entities_with_component::<Tween<T, L>>() {
+            if !storage.has::<L::Component>(entity) {
+                continue;
+            }
+            let Some(tween) = storage.get_entity_component_mut::<Tween<T, L>>(entity) else {
+                continue;
+            };
+            if tween.is_finished() {
+                continue;
+            }
+
+            let (value, finished) = tween.tick(delta);
+            let completion_event = tween.completion_event.filter(|_| finished);
+
+            if let Some(component) = storage.get_entity_component_mut::<L::Component>(entity) {
+                L::set(component, value);
+            }
+            if let Some(id) = completion_event {
+                storage.send_event(TweenCompleted { id, entity });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Query, World};
+
+    const ALL_EASINGS: [Easing; 13] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::BackOut,
+        Easing::ElasticOut,
+        Easing::BounceOut,
+    ];
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        for easing in ALL_EASINGS {
+            assert!(easing.apply(0.0).abs() < 1e-5, "{easing:?} at 0.0");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-5, "{easing:?} at 1.0");
+        }
+
+        assert!((Easing::QuadInOut.apply(0.5) - 0.5).abs() < 1e-5);
+        assert!(Easing::QuadIn.apply(0.5) < 0.5);
+        assert!(Easing::QuadOut.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn tween_interpolates_and_finishes() {
+        let mut tween = Tween::new(0.0, 10.0, millis(100));
+
+        assert_eq!(tween.tick(millis(50)), (5.0, false));
+        assert_eq!(tween.tick(millis(60)), (10.0, true));
+        assert_eq!(tween.tick(millis(10)), (10.0, false));
+        assert!(tween.is_finished());
+
+        tween.reset();
+        assert_eq!(tween.tick(millis(25)), (2.5, false));
+    }
+
+    #[test]
+    fn tween_repeats_with_yoyo() {
+        let mut tween = Tween::new(0.0, 10.0, millis(100))
+            .with_repeat(Repeat::Times(3))
+            .with_yoyo(true);
+
+        assert_eq!(tween.tick(millis(50)).0, 5.0);
+        assert_eq!(tween.tick(millis(75)).0, 7.5);
+        assert_eq!(tween.tick(millis(100)).0, 2.5);
+        assert_eq!(tween.tick(millis(75)), (10.0, true));
+    }
+
+    #[test]
+    fn tween_repeating_forever_never_finishes() {
+        let mut tween = Tween::new(0.0, 1.0, millis(10)).with_repeat(Repeat::Forever);
+
+        for _ in 0..100 {
+            assert!(!tween.tick(millis(7)).1);
+        }
+
+        assert!(!tween.is_finished());
+    }
+
+    #[test]
+    fn tween_system_updates_components_and_sends_completion_events() {
+        let mut world = World::init().unwrap();
+        let mut time = Time::new();
+        time.advance(millis(100));
+        world.storage.insert_resource(time);
+
+        let entity = world
+            .build_entity()
+            .with_component(Vec2::ZERO)
+            .with_component(Tween::new(Vec2::ZERO, Vec2::ONE, millis(200)).with_completion_event(7))
+            .build();
+
+        let mut system = TweenSystem::<Vec2>::new();
+
+        system.update(&mut world.storage);
+        assert_eq!(
+            world.storage.query_one::<Vec2>().collect::<Vec<_>>(),
+            vec![&Vec2::splat(0.5)]
+        );
+        assert_eq!(world.storage.drain_events::<TweenCompleted>().count(), 0);
+
+        system.update(&mut world.storage);
+        assert_eq!(
+            world.storage.query_one::<Vec2>().collect::<Vec<_>>(),
+            vec![&Vec2::ONE]
+        );
+        assert_eq!(
+            world
+                .storage
+                .drain_events::<TweenCompleted>()
+                .collect::<Vec<_>>(),
+            vec![TweenCompleted { id: 7, entity }]
+        );
+    }
+
+    #[test]
+    fn lenses_only_change_their_field() {
+        let mut world = World::init().unwrap();
+        let mut time = Time::new();
+        time.advance(millis(100));
+        world.storage.insert_resource(time);
+
+        let entity = world
+            .build_entity()
+            .with_component(Transform2D::from_xy(0.0, 0.0))
+            .with_component(
+                Tween::new(Vec2::ZERO, Vec2::X, millis(200)).with_lens::<Translation2D>(),
+            )
+            .build();
+        world
+            .storage
+            .get_entity_component_mut::<Transform2D>(entity)
+            .unwrap()
+            .rotation = 1.0;
+
+        TweenSystem::<Vec2, Translation2D>::new().update(&mut world.storage);
+
+        let transform = world
+            .storage
+            .get_entity_component::<Transform2D>(entity)
+            .unwrap();
+        assert_eq!(transform.translation, Vec2::new(0.5, 0.0));
+        assert_eq!(transform.rotation, 1.0);
+    }
+}
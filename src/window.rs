@@ -0,0 +1,230 @@
+//! # Window
+//! The main loop forwards changes of the game window as events: [`WindowResized`],
+//! [`WindowFocused`], [`WindowMoved`], [`ScaleFactorChanged`] and [`CloseRequested`].
+//!
+//! Closing the window (with its close button or Escape) only sends a [`CloseRequested`] event at
+//! first. The window is closed at the end of the frame unless a system
+//! [vetoes](WindowClose::veto) it through the [`WindowClose`] resource, e.g. to ask about unsaved
+//! progress first:
+//!
+//! ```
+//! use game_engine::ecs::{EventReader, Storage, System};
+//! use game_engine::window::{CloseRequested, WindowClose};
+//!
+//! struct UnsavedProgress(bool);
+//!
+//! struct ConfirmQuit {
+//!     requests: EventReader<CloseRequested>,
+//! }
+//!
+//! impl System for ConfirmQuit {
+//!     fn new() -> Self {
+//!         Self { requests: EventReader::new() }
+//!     }
+//!
+//!     fn update(&mut self, storage: &mut Storage) {
+//!         let unsaved = storage.get_resource::<UnsavedProgress>().is_some_and(|u| u.0);
+//!
+//!         if self.requests.read(storage).count() > 0 && unsaved {
+//!             if let Some(close) = storage.get_resource_mut::<WindowClose>() {
+//!                 close.veto();
+//!             }
+//!             // show a prompt that calls `WindowClose::request` once the player confirmed
+//!         }
+//!     }
+//! }
+//! ```
+use crate::ecs::Storage;
+use winit::event::WindowEvent;
+
+/// Sent when the drawable area of the window changed its size, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowResized {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Sent when the window gained (true) or lost (false) the keyboard focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowFocused(pub bool);
+
+/// Sent when the window was moved, with the new position of its top left corner on the desktop
+/// in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowMoved {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Sent when the ratio of physical to logical pixels changed, e.g. because the window was moved
+/// to another monitor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleFactorChanged(pub f64);
+
+/// Sent when the user asked to close the window, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloseRequested;
+
+/// Decides whether the window closes at the end of the current frame. The resource is inserted by
+/// the main loop.
+#[derive(Debug, Default)]
+pub struct WindowClose {
+    requested: bool,
+    vetoed: bool,
+}
+
+impl WindowClose {
+    /// Close the window at the end of the frame, e.g. after the player confirmed quitting. Unlike
+    /// the close button, this does not send a [`CloseRequested`] event.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Keep the window open although closing was requested in this frame.
+    pub fn veto(&mut self) {
+        self.vetoed = true;
+    }
+
+    #[must_use]
+    pub const fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    #[must_use]
+    pub const fn is_vetoed(&self) -> bool {
+        self.vetoed
+    }
+
+    /// Returns true if the window has to be closed now and resets the request for the next frame.
+    pub(crate) fn finish_frame(&mut self) -> bool {
+        let close = self.requested && !self.vetoed;
+        *self = Self::default();
+        close
+    }
+}
+
+/// Send a [`CloseRequested`] event and request closing the window at the end of the frame.
+pub(crate) fn request_close(storage: &mut Storage) {
+    storage.send_event(CloseRequested);
+
+    if !storage.has_resource::<WindowClose>() {
+        storage.insert_resource(WindowClose::default());
+    }
+    if let Some(close) = storage.get_resource_mut::<WindowClose>() {
+        close.request();
+    }
+}
+
+/// Forward a window event of winit as the matching engine event. Other events are ignored.
+pub(crate) fn send_window_event(storage: &mut Storage, event: &WindowEvent) {
+    match event {
+        WindowEvent::Resized(size) => storage.send_event(WindowResized {
+            width: size.width,
+            height: size.height,
+        }),
+        WindowEvent::Focused(focused) => storage.send_event(WindowFocused(*focused)),
+        WindowEvent::Moved(position) => storage.send_event(WindowMoved {
+            x: position.x,
+            y: position.y,
+        }),
+        WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+            storage.send_event(ScaleFactorChanged(*scale_factor));
+        }
+        WindowEvent::CloseRequested => request_close(storage),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{EventReader, System, World};
+    use std::time::Duration;
+    use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+    /// Keeps the window open once.
+    struct VetoClose {
+        requests: EventReader<CloseRequested>,
+        vetoed: bool,
+    }
+
+    impl System for VetoClose {
+        fn new() -> Self {
+            Self {
+                requests: EventReader::new(),
+                vetoed: false,
+            }
+        }
+
+        fn update(&mut self, storage: &mut Storage) {
+            if self.requests.read(storage).count() > 0 && !self.vetoed {
+                storage.get_resource_mut::<WindowClose>().unwrap().veto();
+                self.vetoed = true;
+            }
+        }
+    }
+
+    fn should_close(world: &mut World) -> bool {
+        world
+            .storage
+            .get_resource_mut::<WindowClose>()
+            .is_some_and(WindowClose::finish_frame)
+    }
+
+    #[test]
+    fn window_events_are_forwarded() {
+        let mut storage = Storage::new();
+
+        send_window_event(
+            &mut storage,
+            &WindowEvent::Resized(PhysicalSize::new(800, 600)),
+        );
+        send_window_event(&mut storage, &WindowEvent::Focused(false));
+        send_window_event(
+            &mut storage,
+            &WindowEvent::Moved(PhysicalPosition::new(10, -20)),
+        );
+        send_window_event(&mut storage, &WindowEvent::Destroyed);
+
+        assert_eq!(
+            storage.drain_events::<WindowResized>().collect::<Vec<_>>(),
+            [WindowResized {
+                width: 800,
+                height: 600
+            }]
+        );
+        assert_eq!(
+            storage.drain_events::<WindowFocused>().collect::<Vec<_>>(),
+            [WindowFocused(false)]
+        );
+        assert_eq!(
+            storage.drain_events::<WindowMoved>().collect::<Vec<_>>(),
+            [WindowMoved { x: 10, y: -20 }]
+        );
+        assert!(!storage.has_resource::<WindowClose>());
+    }
+
+    #[test]
+    fn systems_can_veto_closing() {
+        let mut world = World::init().unwrap();
+        world.add_system(VetoClose::new());
+
+        send_window_event(&mut world.storage, &WindowEvent::CloseRequested);
+        world.tick(Duration::from_millis(16));
+        assert!(!should_close(&mut world));
+
+        world.tick(Duration::from_millis(16));
+        assert!(!should_close(&mut world));
+
+        send_window_event(&mut world.storage, &WindowEvent::CloseRequested);
+        world.tick(Duration::from_millis(16));
+        assert!(should_close(&mut world));
+        assert_eq!(
+            world
+                .storage
+                .read_events::<CloseRequested>()
+                .collect::<Vec<_>>(),
+            [&CloseRequested]
+        );
+    }
+}